@@ -0,0 +1,36 @@
+//! Byte-oriented tests counting ones by popcount, compare with `--features simd`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use rngcheck::nist::*;
+
+fn byte_counts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("byte_counts");
+
+    // Suite with only the block frequency test, using whole byte blocks
+    let config = SuiteTest::ALL
+        .iter()
+        .fold(SuiteConfig::default(), |c, t| c.with_test(*t, false))
+        .with_test(SuiteTest::BlockFrequency, true);
+
+    for len in [1 << 16, 1 << 20] {
+        let mut buff = vec![0u8; len];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("monobit", len), &buff, |b, buff| {
+            b.iter(|| nist_freq_monobit_bytes_result(buff))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("block_frequency", len),
+            &buff,
+            |b, buff| b.iter(|| run_suite(buff, &config)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, byte_counts);
+criterion_main!(benches);
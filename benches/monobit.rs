@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use rngcheck::{helpers::BitIter, nist::*};
+
+fn monobit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("monobit");
+
+    for len in [1024, 65536] {
+        let mut buff = vec![0u8; len];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::new("bits", len), &buff, |b, buff| {
+            b.iter(|| nist_freq_monobit_result(BitIter::new(buff)))
+        });
+        group.bench_with_input(BenchmarkId::new("bytes", len), &buff, |b, buff| {
+            b.iter(|| nist_freq_monobit_bytes_result(buff))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, monobit);
+criterion_main!(benches);
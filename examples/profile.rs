@@ -0,0 +1,249 @@
+//! Time each test over 1 Mbit from a seeded RNG
+//!
+//! This prints the wall-clock time and P-value (or estimate) of each test, giving
+//! relative costs for budgeting continuous health checks. Run with `--release` for
+//! representative numbers.
+//!
+//! Tests with a fixed minimum input longer than 1 Mbit (such as the Diehard tests in
+//! their standard configurations) are run over that minimum instead, with the number of
+//! bits used printed alongside each result. Timing uses [std::time::Instant], so this
+//! example requires `std`.
+
+use std::time::{Duration, Instant};
+
+use rand::RngCore;
+
+use rngcheck::{
+    ais31::{procedure_a, PROCEDURE_A_BYTES, T0_WORDS},
+    diehard::{monkey::*, *},
+    fips::ContinuousRngTest,
+    helpers::{chi_square_bytes, BitIter, TestRng},
+    nist::*,
+    sp800_90b::*,
+    Error,
+};
+
+/// RNG seed, giving enough cycles for the random excursions tests
+const SEED: u64 = 0;
+
+/// Sequence length in bytes, 1 Mbit
+const BYTES: usize = 1_000_000 / 8;
+
+/// Sequence length in bytes for the DFT test
+const DFT_BYTES: usize = 1 << 16;
+
+/// Samples for the IID permutation test, which computes each statistic 10,001 times
+const IID_BYTES: usize = 1000;
+
+/// Per-sample min-entropy for the SP 800-90B health test cutoffs
+const HEALTH_H: f32 = 8.0;
+
+/// False positive probability for the SP 800-90B health test cutoffs, 2^-20
+const HEALTH_ALPHA: f32 = 1.0 / (1u32 << 20) as f32;
+
+/// Result of a successful test
+enum Outcome {
+    /// P-value (the lowest, for tests returning several)
+    P(f32),
+    /// Min-entropy estimate in bits per sample
+    Entropy(f32),
+    /// Pass or fail, for tests without a P-value
+    Pass(bool),
+}
+
+use Outcome::*;
+
+/// A test over a buffer of bytes
+struct Profile {
+    name: &'static str,
+    /// Bytes provided to the test
+    bytes: usize,
+    run: fn(&[u8]) -> Result<Outcome, Error>,
+}
+
+impl Profile {
+    const fn new(name: &'static str, run: fn(&[u8]) -> Result<Outcome, Error>) -> Self {
+        Self::with_len(name, BYTES, run)
+    }
+
+    const fn with_len(
+        name: &'static str,
+        bytes: usize,
+        run: fn(&[u8]) -> Result<Outcome, Error>,
+    ) -> Self {
+        Self { name, bytes, run }
+    }
+}
+
+/// Read bytes as little endian 32-bit values
+fn words(d: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    d.chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+}
+
+/// Bytes for `trials` of `bits_per_trial` bits
+const fn trial_bytes(trials: usize, bits_per_trial: usize) -> usize {
+    trials * bits_per_trial / 8
+}
+
+/// Bytes for the monkey tests, with margin for the extra letters
+const MONKEY_BYTES: usize = (MONKEY_WORDS + 16) * 4;
+
+/// Tests not executed by `run_suite`
+const TESTS: &[Profile] = &[
+    // The FFT requires a power of two length, so this uses the first 2^19 bits
+    Profile::with_len("dft", DFT_BYTES, |d| {
+        let mut scratch = vec![0f32; nist_dft_scratch_len(d.len() * 8)];
+        nist_dft(BitIter::new(d), &mut scratch).map(P)
+    }),
+    Profile::new("autocorrelation", |d| {
+        autocorrelation(BitIter::new(d), 1).map(P)
+    }),
+    Profile::new("chi_square_bytes", |d| chi_square_bytes(d).map(P)),
+    // Diehard tests in their standard configurations
+    Profile::with_len(
+        "birthday_spacings",
+        trial_bytes(500, BIRTHDAY_SPACINGS_N * BIRTHDAY_SPACINGS_M),
+        |d| birthday_spacings(BitIter::new(d), BIRTHDAY_SPACINGS_M, BIRTHDAY_SPACINGS_N).map(P),
+    ),
+    Profile::new("count_the_ones_stream", |d| count_the_ones_stream(d).map(P)),
+    Profile::with_len("craps", CRAPS_GAMES * 4, |d| {
+        craps(BitIter::new(d), CRAPS_GAMES).map(|(w, t)| P(w.min(t)))
+    }),
+    Profile::new("gap_test", |d| {
+        gap_test(uniform_floats(BitIter::new(d)), 0.0, 0.5).map(P)
+    }),
+    Profile::with_len(
+        "minimum_distance",
+        trial_bytes(100, 64 * MINIMUM_DISTANCE_N),
+        |d| minimum_distance(BitIter::new(d), MINIMUM_DISTANCE_N).map(P),
+    ),
+    Profile::with_len("opso", MONKEY_BYTES, |d| {
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+        opso(BitIter::new(d), 0, &mut scratch).map(P)
+    }),
+    Profile::with_len("oqso", MONKEY_BYTES, |d| {
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+        oqso(BitIter::new(d), 0, &mut scratch).map(P)
+    }),
+    Profile::with_len("dna", MONKEY_BYTES, |d| {
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+        dna(BitIter::new(d), 0, &mut scratch).map(P)
+    }),
+    Profile::new("overlapping_permutations", |d| {
+        overlapping_permutations(words(d)).map(P)
+    }),
+    Profile::new("overlapping_sums", |d| {
+        overlapping_sums(uniform_floats(BitIter::new(d))).map(P)
+    }),
+    Profile::with_len("parking_lot", trial_bytes(10, 384_000), |d| {
+        parking_lot(BitIter::new(d), 10).map(P)
+    }),
+    Profile::new("poker_test", |d| {
+        poker_test(d, POKER_DIGITS, POKER_RADIX).map(P)
+    }),
+    Profile::new("runs_up_down", |d| {
+        runs_up_down(uniform_floats(BitIter::new(d))).map(|(u, v)| P(u.min(v)))
+    }),
+    Profile::with_len(
+        "spheres_3d",
+        trial_bytes(SPHERES_MIN_TRIALS, 96 * SPHERES_N),
+        |d| spheres_3d(BitIter::new(d)).map(P),
+    ),
+    // Squeeze consumes ~23 words per trial
+    Profile::with_len("squeeze", SQUEEZE_TRIALS * 100, |d| {
+        squeeze(BitIter::new(d), SQUEEZE_TRIALS).map(P)
+    }),
+    // FIPS 140-2 and AIS 31
+    Profile::new("continuous_rng", |d| {
+        let mut t = ContinuousRngTest::<4>::default();
+        d.chunks_exact(4).try_for_each(|b| t.check_block(b))?;
+        Ok(Pass(true))
+    }),
+    Profile::with_len("ais31_procedure_a", PROCEDURE_A_BYTES, |d| {
+        let mut scratch = vec![0u64; T0_WORDS];
+        procedure_a(d.try_into().unwrap(), &mut scratch).map(|_| Pass(true))
+    }),
+    // SP 800-90B estimators and health tests over byte samples
+    Profile::new("most_common_value", |d| most_common_value(d).map(Entropy)),
+    Profile::new("collision_estimate", |d| collision_estimate(d).map(Entropy)),
+    Profile::new("markov_estimate", |d| {
+        markov_estimate(BitIter::new(d)).map(Entropy)
+    }),
+    Profile::new("t_tuple_estimate", |d| {
+        let mut scratch = vec![0u32; tuple_scratch_len(d.len())];
+        t_tuple_estimate(d, &mut scratch).map(Entropy)
+    }),
+    Profile::new("lrs_estimate", |d| {
+        let mut scratch = vec![0u32; tuple_scratch_len(d.len())];
+        lrs_estimate(d, &mut scratch).map(Entropy)
+    }),
+    Profile::with_len("iid_permutation_test", IID_BYTES, |d| {
+        let mut scratch = vec![0u8; d.len()];
+        iid_permutation_test(d, &mut scratch, &mut TestRng::seed(SEED)).map(Pass)
+    }),
+    Profile::new("repetition_count", |d| {
+        let mut t = RepetitionCount::new(repetition_count_cutoff(HEALTH_H, HEALTH_ALPHA));
+        d.iter().try_for_each(|s| t.push(*s))?;
+        Ok(Pass(true))
+    }),
+    Profile::new("adaptive_proportion", |d| {
+        let w = ADAPTIVE_PROPORTION_WINDOW;
+        let mut t =
+            AdaptiveProportion::new(w, adaptive_proportion_cutoff(w, HEALTH_H, HEALTH_ALPHA));
+        d.iter().try_for_each(|s| t.push(*s))?;
+        Ok(Pass(true))
+    }),
+];
+
+/// Run a closure, returning the elapsed time
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let r = f();
+    (r, start.elapsed())
+}
+
+fn report(name: &str, bytes: usize, r: &Result<Outcome, Error>, t: Duration) {
+    let t = format!(
+        "{:>9} bits {:>10.3} ms",
+        bytes * 8,
+        t.as_secs_f64() * 1000.0
+    );
+
+    match r {
+        Ok(P(p)) => println!("{name:>30}: {t}  p = {p:.6}"),
+        Ok(Entropy(h)) => println!("{name:>30}: {t}  H = {h:.6} bits / sample"),
+        Ok(Pass(true)) => println!("{name:>30}: {t}  pass"),
+        Ok(Pass(false)) => println!("{name:>30}: {t}  fail"),
+        Err(Error::BadPValue { p, statistic }) => {
+            println!("{name:>30}: {t}  p = {p:.6} (fail, statistic {statistic})")
+        }
+        Err(e) => println!("{name:>30}: {t}  {e}"),
+    }
+}
+
+fn main() {
+    let len = TESTS.iter().map(|t| t.bytes).max().unwrap_or(0).max(BYTES);
+    let mut data = vec![0u8; len];
+    TestRng::seed(SEED).fill_bytes(&mut data);
+
+    println!("Timing tests over {} bits", BYTES * 8);
+
+    // Run each suite test alone for uniform configuration
+    for test in SuiteTest::ALL {
+        let config = SuiteTest::ALL
+            .iter()
+            .fold(SuiteConfig::default(), |c, t| c.with_test(*t, *t == test));
+
+        let (suite, t) = timed(|| run_suite(&data[..BYTES], &config));
+        if let Some(r) = suite.get(test) {
+            let r = r.clone().map(P);
+            report(test.name(), BYTES, &r, t);
+        }
+    }
+
+    for test in TESTS {
+        let (r, t) = timed(|| (test.run)(&data[..test.bytes]));
+        report(test.name, test.bytes, &r, t);
+    }
+}
@@ -0,0 +1,65 @@
+//! Run a selection of tests over a deterministic RNG
+//!
+//! As [TestRng] is seeded the results are identical on every run, so output from this
+//! example can be reproduced when reporting issues. Pass a seed as the first argument to
+//! try others.
+
+use rngcheck::{
+    helpers::{BitsFromRng, TestRng},
+    nist::*,
+};
+
+const BITS: usize = 1_000_000;
+
+/// Fetch bits from the RNG, restarting each test from the seed
+fn bits(rng: &mut TestRng) -> BitsFromRng<'_, TestRng> {
+    BitsFromRng::new(rng, BITS)
+}
+
+fn main() {
+    let seed = std::env::args()
+        .nth(1)
+        .map(|s| s.parse().expect("seed must be a u64"))
+        .unwrap_or(0);
+
+    println!("Testing {BITS} bits from TestRng::seed({seed})");
+
+    let results = [
+        (
+            "monobit",
+            nist_freq_monobit_result(bits(&mut TestRng::seed(seed))),
+        ),
+        (
+            "block frequency",
+            nist_freq_block_result(bits(&mut TestRng::seed(seed)), 20_000),
+        ),
+        ("runs", nist_runs_result(bits(&mut TestRng::seed(seed)))),
+        (
+            "longest run",
+            nist_longest_run_result(bits(&mut TestRng::seed(seed))),
+        ),
+        (
+            "linear complexity",
+            nist_linear_complexity_result(bits(&mut TestRng::seed(seed)), 500),
+        ),
+        (
+            "approx entropy",
+            nist_approx_entropy_result(bits(&mut TestRng::seed(seed)), 10),
+        ),
+        (
+            "cusum",
+            nist_cusum_result(bits(&mut TestRng::seed(seed)), CusumMode::Forward),
+        ),
+    ];
+
+    for (name, r) in results {
+        match r {
+            Ok(r) => println!(
+                "{name:>20}: p = {:.6} ({})",
+                r.p_value,
+                if r.passed { "pass" } else { "fail" }
+            ),
+            Err(e) => println!("{name:>20}: {e}"),
+        }
+    }
+}
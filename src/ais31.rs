@@ -0,0 +1,369 @@
+//! BSI AIS 31 procedure A tests
+//!
+//! These implement the basic tests T0 to T4 of procedure A from AIS 20 / AIS 31 (W.
+//! Killmann, W. Schindler, "A proposal for: Functionality classes for random number
+//! generators", 2011, §5.4.4) with the fixed acceptance intervals from the standard,
+//! which differ from the P-value thresholds of SP 800-22. Sequences are read with the
+//! first bit lowest as for [BitIter]. The autocorrelation test T5 is not included, see
+//! [autocorrelation](crate::nist::autocorrelation).
+
+use crate::helpers::BitIter;
+use crate::popcount::popcount;
+use crate::Error;
+
+/// Number of 48-bit words compared by the disjointness test T0
+pub const T0_WORDS: usize = 1 << 16;
+
+/// Bytes consumed by the disjointness test T0
+pub const T0_BYTES: usize = T0_WORDS * 6;
+
+/// Bytes in each 20,000 bit sequence for tests T1 to T4
+pub const SEQUENCE_BYTES: usize = 20_000 / 8;
+
+/// Number of sequences tested by procedure A
+pub const PROCEDURE_A_SEQUENCES: usize = 257;
+
+/// Bytes consumed by procedure A, T0 followed by each sequence
+pub const PROCEDURE_A_BYTES: usize = T0_BYTES + PROCEDURE_A_SEQUENCES * SEQUENCE_BYTES;
+
+/// Exclusive bounds on the number of ones for the monobit test T1
+const T1_BOUNDS: (usize, usize) = (9654, 10346);
+
+/// Exclusive bounds on the statistic for the poker test T2
+const T2_BOUNDS: (f32, f32) = (1.03, 57.4);
+
+/// Inclusive bounds on the number of runs of lengths 1 to 5 and >= 6 for the runs test
+/// T3, applying to runs of both zeros and ones
+const T3_BOUNDS: [(usize, usize); 6] = [
+    (2267, 2733),
+    (1079, 1421),
+    (502, 748),
+    (223, 402),
+    (90, 223),
+    (90, 223),
+];
+
+/// Minimum length of a failing run for the long run test T4
+const T4_RUN_LEN: usize = 34;
+
+/// AIS 31 procedure A over T0 and 257 sequences of tests T1 to T4, returning the first
+/// failure
+///
+/// The disjointness test uses the first [T0_BYTES], with each subsequent
+/// [SEQUENCE_BYTES] tested in turn. As this is `no_std` and does not allocate, the caller
+/// provides a `scratch` buffer of at least [T0_WORDS] `u64`s (512 KiB) for T0.
+///
+/// The standard allows procedure A to be repeated once with fresh data where exactly one
+/// basic test fails, this is left to the caller.
+pub fn procedure_a(data: &[u8; PROCEDURE_A_BYTES], scratch: &mut [u64]) -> Result<(), Error> {
+    let (t0, sequences) = data.split_at(T0_BYTES);
+
+    t0_disjointness(t0, scratch)?;
+
+    for s in sequences.chunks_exact(SEQUENCE_BYTES) {
+        t1_monobit(s)?;
+        t2_poker(s)?;
+        t3_runs(s)?;
+        t4_long_run(s)?;
+    }
+
+    Ok(())
+}
+
+/// Check a sequence is of the expected length
+fn check_len(data: &[u8], len: usize) -> Result<(), Error> {
+    match data.len() == len {
+        true => Ok(()),
+        false => Err(Error::InsufficientSampleSize(data.len())),
+    }
+}
+
+/// AIS 31 disjointness test T0 over [T0_BYTES]
+///
+/// Splits the data into [T0_WORDS] 48-bit words, returning [Error::RepeatedBlock] where
+/// any two are equal. The words are sorted in the caller-provided `scratch` buffer of at
+/// least [T0_WORDS] `u64`s, returning [Error::InvalidParameter] where this is too short.
+/// Other lengths of `data` return [Error::InsufficientSampleSize].
+pub fn t0_disjointness(data: &[u8], scratch: &mut [u64]) -> Result<(), Error> {
+    let words = match scratch.get_mut(..T0_WORDS) {
+        Some(w) => w,
+        None => return Err(Error::InvalidParameter),
+    };
+    check_len(data, T0_BYTES)?;
+
+    for (w, b) in words.iter_mut().zip(data.chunks_exact(6)) {
+        let mut v = [0u8; 8];
+        v[..6].copy_from_slice(b);
+        *w = u64::from_le_bytes(v);
+    }
+
+    words.sort_unstable();
+
+    match words.windows(2).any(|w| w[0] == w[1]) {
+        true => Err(Error::RepeatedBlock),
+        false => Ok(()),
+    }
+}
+
+/// AIS 31 monobit test T1 over a 20,000 bit sequence
+///
+/// Passes where the number of ones is within (9654, 10346), returning
+/// [Error::HealthTestFailed] otherwise. Sequences other than [SEQUENCE_BYTES] long return
+/// [Error::InsufficientSampleSize].
+pub fn t1_monobit(data: &[u8]) -> Result<(), Error> {
+    check_len(data, SEQUENCE_BYTES)?;
+
+    let ones = popcount(data);
+
+    match ones > T1_BOUNDS.0 && ones < T1_BOUNDS.1 {
+        true => Ok(()),
+        false => Err(Error::HealthTestFailed),
+    }
+}
+
+/// AIS 31 poker test T2 over a 20,000 bit sequence
+///
+/// Counts the frequencies f_i of the 5000 4-bit segments, passing where
+/// `16 / 5000 Σ f_i^2 - 5000` is within (1.03, 57.4) and returning
+/// [Error::HealthTestFailed] otherwise. Sequences other than [SEQUENCE_BYTES] long return
+/// [Error::InsufficientSampleSize].
+pub fn t2_poker(data: &[u8]) -> Result<(), Error> {
+    check_len(data, SEQUENCE_BYTES)?;
+
+    let mut f = [0usize; 16];
+    for b in data {
+        f[(b & 0x0f) as usize] += 1;
+        f[(b >> 4) as usize] += 1;
+    }
+
+    let sum_sq = f.iter().map(|f| f * f).sum::<usize>();
+    let t = 16.0 * sum_sq as f32 / 5000.0 - 5000.0;
+
+    match t > T2_BOUNDS.0 && t < T2_BOUNDS.1 {
+        true => Ok(()),
+        false => Err(Error::HealthTestFailed),
+    }
+}
+
+/// Count runs of zeros and ones by length (1 to 5 and >= 6), and the longest run
+fn count_runs(data: &[u8]) -> ([[usize; 6]; 2], usize) {
+    let mut counts = [[0usize; 6]; 2];
+    let mut longest = 0;
+
+    let mut bits = BitIter::new(data);
+    let Some(mut last) = bits.next() else {
+        return (counts, 0);
+    };
+    let mut len = 1;
+
+    for b in bits {
+        if b == last {
+            len += 1;
+            continue;
+        }
+
+        counts[last as usize][len.min(6) - 1] += 1;
+        longest = longest.max(len);
+        (last, len) = (b, 1);
+    }
+
+    counts[last as usize][len.min(6) - 1] += 1;
+    (counts, longest.max(len))
+}
+
+/// AIS 31 runs test T3 over a 20,000 bit sequence
+///
+/// Counts the runs of zeros and of ones with lengths 1 to 5 and >= 6, passing where all
+/// twelve counts are within the intervals [2267, 2733], [1079, 1421], [502, 748],
+/// [223, 402], [90, 223] and [90, 223] respectively, and returning
+/// [Error::HealthTestFailed] otherwise. Sequences other than [SEQUENCE_BYTES] long return
+/// [Error::InsufficientSampleSize].
+pub fn t3_runs(data: &[u8]) -> Result<(), Error> {
+    check_len(data, SEQUENCE_BYTES)?;
+
+    let (counts, _) = count_runs(data);
+
+    let ok = counts.iter().all(|c| {
+        c.iter()
+            .zip(T3_BOUNDS)
+            .all(|(c, (lo, hi))| (lo..=hi).contains(c))
+    });
+
+    match ok {
+        true => Ok(()),
+        false => Err(Error::HealthTestFailed),
+    }
+}
+
+/// AIS 31 long run test T4 over a 20,000 bit sequence
+///
+/// Passes where there is no run of zeros or ones of length 34 or more, returning
+/// [Error::HealthTestFailed] otherwise. Sequences other than [SEQUENCE_BYTES] long return
+/// [Error::InsufficientSampleSize].
+pub fn t4_long_run(data: &[u8]) -> Result<(), Error> {
+    check_len(data, SEQUENCE_BYTES)?;
+
+    let (_, longest) = count_runs(data);
+
+    match longest < T4_RUN_LEN {
+        true => Ok(()),
+        false => Err(Error::HealthTestFailed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::{boxed::Box, vec, vec::Vec};
+
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+
+    fn random(len: usize, seed: u64) -> Vec<u8> {
+        let mut buff = vec![0u8; len];
+        StdRng::seed_from_u64(seed).fill_bytes(&mut buff);
+        buff
+    }
+
+    #[test]
+    fn procedure_a_ok() {
+        let data: Box<[u8; PROCEDURE_A_BYTES]> = random(PROCEDURE_A_BYTES, 1)
+            .into_boxed_slice()
+            .try_into()
+            .unwrap();
+        let mut scratch = vec![0u64; T0_WORDS];
+
+        procedure_a(&data, &mut scratch).expect("Procedure A failed");
+    }
+
+    #[test]
+    fn procedure_a_fail() {
+        let mut data = random(PROCEDURE_A_BYTES, 1);
+        let mut scratch = vec![0u64; T0_WORDS];
+
+        // A single stuck sequence fails procedure A
+        let s = T0_BYTES + 100 * SEQUENCE_BYTES;
+        data[s..s + SEQUENCE_BYTES].fill(0);
+
+        let data: Box<[u8; PROCEDURE_A_BYTES]> = data.into_boxed_slice().try_into().unwrap();
+        assert_eq!(
+            procedure_a(&data, &mut scratch),
+            Err(Error::HealthTestFailed)
+        );
+    }
+
+    #[test]
+    fn t0() {
+        let mut data = random(T0_BYTES, 2);
+        let mut scratch = vec![0u64; T0_WORDS];
+
+        t0_disjointness(&data, &mut scratch).unwrap();
+
+        // Any repeated 48-bit word fails, regardless of position
+        data.copy_within(6 * 17..6 * 18, 6 * 60_000);
+        assert_eq!(
+            t0_disjointness(&data, &mut scratch),
+            Err(Error::RepeatedBlock)
+        );
+
+        assert_eq!(
+            t0_disjointness(&data, &mut scratch[..T0_WORDS - 1]),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            t0_disjointness(&data[1..], &mut scratch),
+            Err(Error::InsufficientSampleSize(T0_BYTES - 1))
+        );
+    }
+
+    /// Set the first `ones` bits of a sequence
+    fn with_ones(ones: usize) -> [u8; SEQUENCE_BYTES] {
+        let mut s = [0u8; SEQUENCE_BYTES];
+        s[..ones / 8].fill(0xff);
+        if ones % 8 != 0 {
+            s[ones / 8] = (1 << (ones % 8)) - 1;
+        }
+        s
+    }
+
+    #[test]
+    fn t1() {
+        // Acceptance interval 9654 < X < 10346
+        for (ones, ok) in [(9654, false), (9655, true), (10345, true), (10346, false)] {
+            assert_eq!(t1_monobit(&with_ones(ones)).is_ok(), ok, "{ones} ones");
+        }
+
+        t1_monobit(&random(SEQUENCE_BYTES, 3)).unwrap();
+        assert_eq!(
+            t1_monobit(&[0xa5; 100]),
+            Err(Error::InsufficientSampleSize(100))
+        );
+    }
+
+    /// Build a sequence from 4-bit segment frequencies
+    fn with_segments(f: &[usize; 16]) -> Vec<u8> {
+        let segments: Vec<u8> = (0..16u8)
+            .flat_map(|v| core::iter::repeat(v).take(f[v as usize]))
+            .collect();
+        segments.chunks(2).map(|s| s[0] | (s[1] << 4)).collect()
+    }
+
+    #[test]
+    fn t2() {
+        // Acceptance interval 1.03 < T < 57.4, with T = 0.0032 Σ (f_i - 312.5)^2
+        let with_pair = |a: usize| {
+            let mut f = [313, 312].repeat(8);
+            (f[0], f[1]) = (312 + a, 313 - a);
+            with_segments(&f.try_into().unwrap())
+        };
+
+        // T = 1.0112, 1.1776, 57.1648 and 58.3808
+        for (a, ok) in [(13, false), (14, true), (95, true), (96, false)] {
+            assert_eq!(t2_poker(&with_pair(a)).is_ok(), ok, "f_0 = {}", 312 + a);
+        }
+
+        // Uniform segments give T = 0
+        assert_eq!(
+            t2_poker(&with_segments(&[313, 312].repeat(8).try_into().unwrap())),
+            Err(Error::HealthTestFailed)
+        );
+
+        t2_poker(&random(SEQUENCE_BYTES, 4)).unwrap();
+    }
+
+    #[test]
+    fn t3() {
+        t3_runs(&random(SEQUENCE_BYTES, 5)).unwrap();
+
+        // Alternating bits give only runs of length 1
+        assert_eq!(
+            t3_runs(&[0x55; SEQUENCE_BYTES]),
+            Err(Error::HealthTestFailed)
+        );
+
+        // Runs are counted by length for both zeros and ones
+        let (counts, longest) = count_runs(&[0b0110_0010, 0b1111_1100]);
+        assert_eq!(counts[0], [1, 0, 2, 0, 0, 0]);
+        assert_eq!(counts[1], [1, 1, 0, 0, 0, 1]);
+        assert_eq!(longest, 6);
+    }
+
+    #[test]
+    fn t4() {
+        t4_long_run(&random(SEQUENCE_BYTES, 6)).unwrap();
+
+        // Runs of 34 or more fail, in either value
+        let mut s = [0x55u8; SEQUENCE_BYTES];
+        s[100..104].fill(0xff);
+        t4_long_run(&s).unwrap();
+        s[104] = 0x03;
+        assert_eq!(t4_long_run(&s), Err(Error::HealthTestFailed));
+
+        let mut s = [0x55u8; SEQUENCE_BYTES];
+        s[100..104].fill(0x00);
+        t4_long_run(&s).unwrap();
+        s[99] = 0x35;
+        assert_eq!(t4_long_run(&s), Err(Error::HealthTestFailed));
+    }
+}
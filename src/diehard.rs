@@ -0,0 +1,122 @@
+//! Diehard tests
+//!
+//! These consume a bit stream, packing bits into words with the first bit lowest such
+//! that [BitsFromRng](crate::helpers::BitsFromRng) reproduces the RNG's `next_u32` outputs.
+
+use crate::nist::igamma_upper;
+
+mod birthday_spacings;
+pub use birthday_spacings::{
+    birthday_spacings, birthday_spacings_result, birthday_spacings_with_alpha, BIRTHDAY_SPACINGS_M,
+    BIRTHDAY_SPACINGS_MAX_N, BIRTHDAY_SPACINGS_N,
+};
+
+mod count_the_ones;
+pub use count_the_ones::{
+    count_the_ones_stream, count_the_ones_stream_result, count_the_ones_stream_with_alpha,
+    COUNT_THE_ONES_MIN_BYTES,
+};
+
+mod craps;
+pub use craps::{craps, craps_result, craps_with_alpha, CRAPS_GAMES};
+
+mod gap;
+pub use gap::{gap_test, gap_test_result, gap_test_with_alpha, GAP_MAX_LEN};
+
+mod minimum_distance;
+pub use minimum_distance::{
+    minimum_distance, minimum_distance_result, minimum_distance_with_alpha, MINIMUM_DISTANCE_MAX_N,
+    MINIMUM_DISTANCE_MIN_TRIALS, MINIMUM_DISTANCE_N,
+};
+
+pub mod monkey;
+
+mod overlapping_permutations;
+pub use overlapping_permutations::{
+    overlapping_permutations, overlapping_permutations_result, overlapping_permutations_with_alpha,
+    OVERLAPPING_PERMUTATIONS_MIN_VALUES,
+};
+
+mod overlapping_sums;
+pub use overlapping_sums::{
+    overlapping_sums, overlapping_sums_result, overlapping_sums_with_alpha,
+    OVERLAPPING_SUMS_BLOCKS, OVERLAPPING_SUMS_M,
+};
+
+mod parking_lot;
+pub use parking_lot::{
+    parking_lot, parking_lot_result, parking_lot_with_alpha, PARKING_LOT_ATTEMPTS,
+};
+
+mod poker;
+pub use poker::{
+    poker_test, poker_test_result, poker_test_with_alpha, POKER_DIGITS, POKER_MAX_DIGITS,
+    POKER_RADIX,
+};
+
+mod runs_up_down;
+pub use runs_up_down::{
+    runs_up_down, runs_up_down_result, runs_up_down_with_alpha, RUNS_UP_DOWN_MIN_VALUES,
+};
+
+mod spheres;
+pub use spheres::{
+    spheres_3d, spheres_3d_result, spheres_3d_with_alpha, SPHERES_MIN_TRIALS, SPHERES_N,
+};
+
+mod squeeze;
+pub use squeeze::{squeeze, squeeze_result, squeeze_with_alpha, SQUEEZE_PROBS, SQUEEZE_TRIALS};
+
+/// Read a `bits` wide word (up to 32) from a bit stream with the first bit lowest,
+/// returning `None` where the stream ends mid-word
+fn next_word(data: &mut impl Iterator<Item = bool>, bits: usize) -> Option<u32> {
+    let mut v = 0;
+    for i in 0..bits {
+        v |= (data.next()? as u32) << i;
+    }
+    Some(v)
+}
+
+/// Convert a bit stream into uniform values over [0, 1)
+///
+/// Each value is read from a 32-bit word w with the first bit lowest, using the upper 24
+/// bits `(w >> 8) / 2^24` so that all values are exactly representable. This stops
+/// where the stream ends mid-word, and can be used with
+/// [BitsFromRng](crate::helpers::BitsFromRng) to test an RNG's `next_u32` outputs.
+/// See [UniformFloats](crate::helpers::UniformFloats) to consume only 24 bits per value.
+pub fn uniform_floats(mut data: impl Iterator<Item = bool>) -> impl Iterator<Item = f32> {
+    core::iter::from_fn(move || {
+        next_word(&mut data, 32).map(|w| (w >> 8) as f32 / (1 << 24) as f32)
+    })
+}
+
+/// Compute the upper tail P-value for a chi-square statistic with `dof` degrees of freedom
+fn chi_square_p(dof: usize, x2: f32) -> f32 {
+    igamma_upper(dof as f32 / 2.0, x2 / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn diehard_next_word() {
+        let buff = [0x78, 0x56, 0x34, 0x12, 0xff];
+        let mut bits = BitIter::new(&buff);
+
+        assert_eq!(next_word(&mut bits, 32), Some(0x1234_5678));
+        assert_eq!(next_word(&mut bits, 4), Some(0xf));
+        assert_eq!(next_word(&mut bits, 5), None);
+    }
+
+    #[test]
+    fn diehard_uniform_floats() {
+        let buff = [0x00, 0x00, 0x00, 0x80, 0xff, 0xff, 0xff, 0xff, 0x00];
+        let mut values = uniform_floats(BitIter::new(&buff));
+
+        assert_eq!(values.next(), Some(0.5));
+        assert_eq!(values.next(), Some(1.0 - 1.0 / (1 << 24) as f32));
+        assert_eq!(values.next(), None);
+    }
+}
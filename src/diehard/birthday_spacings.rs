@@ -0,0 +1,218 @@
+//! Diehard Birthday Spacings Test
+
+use super::{chi_square_p, next_word};
+use crate::math;
+use crate::{helpers::poisson_pmf, Error, TestResult};
+
+/// Default days per year (2^m) exponent for the birthday spacings test
+pub const BIRTHDAY_SPACINGS_M: usize = 24;
+
+/// Default birthdays per year (n) for the birthday spacings test
+pub const BIRTHDAY_SPACINGS_N: usize = 512;
+
+/// Maximum supported birthdays per year (n)
+pub const BIRTHDAY_SPACINGS_MAX_N: usize = 1024;
+
+/// Maximum tracked duplicate count, higher counts are merged
+const MAX_DUPLICATES: usize = 64;
+
+/// Minimum expected count for each chi-square category
+const MIN_EXPECTED: f64 = 5.0;
+
+/// Diehard Birthday Spacings Test over an iterator of N bits
+///
+/// Each trial draws n birthdays of m bits in a year of 2^m days, sorts these and counts
+/// the number of duplicated spacings between them, which should be Poisson distributed
+/// with λ = n^3 / (4 * 2^m). The distribution of duplicate counts over all complete
+/// trials is compared against this using x^2, with tail categories merged to give an
+/// expected count of at least 5 in each.
+///
+/// Diehard uses m = [BIRTHDAY_SPACINGS_M] and n = [BIRTHDAY_SPACINGS_N] (λ = 2) with 500
+/// trials, or ~6 Mbit. As this is `no_std` and does not allocate, `n` is limited to
+/// 2..=[BIRTHDAY_SPACINGS_MAX_N] with the birthdays (4 KiB) held on the stack, and `m` to
+/// 1..=32.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn birthday_spacings(
+    data: impl Iterator<Item = bool>,
+    m: usize,
+    n: usize,
+) -> Result<f32, Error> {
+    birthday_spacings_result(data, m, n)?.check()
+}
+
+/// Diehard Birthday Spacings Test at significance level `alpha`
+///
+/// See [birthday_spacings] for details
+pub fn birthday_spacings_with_alpha(
+    data: impl Iterator<Item = bool>,
+    m: usize,
+    n: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    birthday_spacings_result(data, m, n)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// Diehard Birthday Spacings Test returning the [TestResult], with statistic x^2
+///
+/// See [birthday_spacings] for details
+pub fn birthday_spacings_result(
+    mut data: impl Iterator<Item = bool>,
+    m: usize,
+    n: usize,
+) -> Result<TestResult, Error> {
+    // Check parameters are supported
+    if !(1..=32).contains(&m) || !(2..=BIRTHDAY_SPACINGS_MAX_N).contains(&n) {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut bits = 0;
+    let mut trials = 0;
+    let mut counts = [0usize; MAX_DUPLICATES + 1];
+    let mut days = [0u32; BIRTHDAY_SPACINGS_MAX_N];
+
+    'outer: loop {
+        // Draw birthdays, discarding incomplete trials
+        for d in days[..n].iter_mut() {
+            match next_word(&mut data, m) {
+                Some(v) => *d = v,
+                None => break 'outer,
+            }
+            bits += m;
+        }
+
+        let days = &mut days[..n];
+        days.sort_unstable();
+
+        // Compute spacings in place, from the last so each predecessor is intact
+        for i in (1..n).rev() {
+            days[i] -= days[i - 1];
+        }
+        days.sort_unstable();
+
+        // Count duplicated spacings
+        let duplicates = days.windows(2).filter(|w| w[0] == w[1]).count();
+        counts[duplicates.min(MAX_DUPLICATES)] += 1;
+
+        trials += 1;
+    }
+
+    // Compute Poisson probabilities
+    let lambda = math::pow(n as f64, 3.0) / (4.0 * math::pow(2.0, m as f64));
+    let probs: [f64; MAX_DUPLICATES + 1] =
+        core::array::from_fn(|j| poisson_pmf(j as u32, lambda as f32) as f64);
+
+    // Merge categories until each has an expected count of at least MIN_EXPECTED,
+    // with the remaining tail merged into the last category
+    let t = trials as f64;
+    let mut x2 = 0.0;
+    let mut categories = 0;
+
+    let mut observed = 0;
+    let mut prob = 0.0;
+    let mut cumulative = 0.0;
+
+    for (c, p) in counts.iter().zip(probs) {
+        observed += c;
+        prob += p;
+        cumulative += p;
+
+        if t * prob >= MIN_EXPECTED && t * (1.0 - cumulative) >= MIN_EXPECTED {
+            x2 += chi_square_term(observed, t * prob);
+            categories += 1;
+
+            observed = 0;
+            prob = 0.0;
+        }
+    }
+
+    // Check we have at least two categories, including the tail
+    if categories == 0 {
+        return Err(Error::InsufficientSampleSize(bits));
+    }
+    x2 += chi_square_term(observed, t * (1.0 - cumulative + prob));
+    categories += 1;
+
+    // Compute p
+    let x2 = x2 as f32;
+    let p = chi_square_p(categories - 1, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Compute (o - e)^2 / e
+fn chi_square_term(observed: usize, expected: f64) -> f64 {
+    let d = observed as f64 - expected;
+    d * d / expected
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    /// Bytes for Diehard's 500 trials
+    const LEN: usize = 500 * BIRTHDAY_SPACINGS_N * BIRTHDAY_SPACINGS_M / 8;
+
+    #[test]
+    fn birthday_spacings_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        birthday_spacings(
+            BitIter::new(&buff),
+            BIRTHDAY_SPACINGS_M,
+            BIRTHDAY_SPACINGS_N,
+        )
+        .expect("Birthday spacings test failed");
+    }
+
+    #[test]
+    fn birthday_spacings_fail() {
+        // Arithmetic progressions have equal spacings
+        let mut buff = vec![0u8; LEN];
+        for (i, c) in buff.chunks_exact_mut(3).enumerate() {
+            let v = (i as u32).wrapping_mul(7919) & 0xff_ffff;
+            c.copy_from_slice(&v.to_le_bytes()[..3]);
+        }
+
+        birthday_spacings(
+            BitIter::new(&buff),
+            BIRTHDAY_SPACINGS_M,
+            BIRTHDAY_SPACINGS_N,
+        )
+        .expect_err("Birthday spacings p > threshold");
+    }
+
+    #[test]
+    fn birthday_spacings_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            birthday_spacings(BitIter::new(&buff), 0, 512),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            birthday_spacings(BitIter::new(&buff), 33, 512),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            birthday_spacings(BitIter::new(&buff), 24, BIRTHDAY_SPACINGS_MAX_N + 1),
+            Err(Error::InvalidParameter)
+        );
+
+        // Too few trials for two categories
+        let buff = [0u8; 3 * 512 * 2];
+        assert_eq!(
+            birthday_spacings(BitIter::new(&buff), 24, 512),
+            Err(Error::InsufficientSampleSize(24 * 512 * 2))
+        );
+    }
+}
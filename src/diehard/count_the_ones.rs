@@ -0,0 +1,155 @@
+//! Diehard Count-the-1s Test
+
+use super::chi_square_p;
+use crate::{Error, TestResult};
+
+/// Minimum data length for the count-the-1s test, giving at least 5 expected
+/// occurrences of the least likely word
+pub const COUNT_THE_ONES_MIN_BYTES: usize = 80_000;
+
+/// Letter frequencies (out of 256) for bytes with popcount 0-2, 3, 4, 5, and 6-8
+const LETTER_COUNTS: [u32; 5] = [37, 56, 70, 56, 37];
+
+/// Diehard Count-the-1s Test (stream version) over a buffer of bytes
+///
+/// Maps each byte to one of five letters by its popcount (0-2, 3, 4, 5, 6-8), counting
+/// the overlapping 5-letter words and their 4-letter prefixes. The statistic
+/// Q5 - Q4 of the x^2 statistics for these counts follows x^2 with 5^5 - 5^4 = 2500
+/// degrees of freedom, detecting correlations between consecutive bytes not seen by
+/// the bit frequency tests.
+///
+/// Diehard uses 256,000 bytes, this requires at least [COUNT_THE_ONES_MIN_BYTES] returning
+/// [Error::InsufficientSampleSize] otherwise. The word counts (12.5 KiB) are held on the
+/// stack.
+pub fn count_the_ones_stream(data: &[u8]) -> Result<f32, Error> {
+    count_the_ones_stream_result(data)?.check()
+}
+
+/// Diehard Count-the-1s Test (stream version) at significance level `alpha`
+///
+/// See [count_the_ones_stream] for details
+pub fn count_the_ones_stream_with_alpha(data: &[u8], alpha: f32) -> Result<f32, Error> {
+    count_the_ones_stream_result(data)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard Count-the-1s Test (stream version) returning the [TestResult], with statistic
+/// Q5 - Q4
+///
+/// See [count_the_ones_stream] for details
+pub fn count_the_ones_stream_result(data: &[u8]) -> Result<TestResult, Error> {
+    // Check we have enough data for the chi-square approximation
+    if data.len() < COUNT_THE_ONES_MIN_BYTES {
+        return Err(Error::InsufficientSampleSize(data.len()));
+    }
+
+    // Count overlapping 5-letter words, with the first letter highest
+    let mut counts = [0u32; 3125];
+    let mut word = 0;
+
+    for (i, b) in data.iter().enumerate() {
+        word = (word * 5 + letter(*b)) % 3125;
+
+        if i >= 4 {
+            counts[word] += 1;
+        }
+    }
+
+    // Compute the x^2 statistics for 5-letter words and their 4-letter prefixes
+    let windows = (data.len() - 4) as f64;
+    let q5 = chi_square_words(counts.iter().copied(), 5, windows);
+    let q4 = chi_square_words(counts.chunks_exact(5).map(|c| c.iter().sum()), 4, windows);
+
+    // Compute p
+    let x2 = (q5 - q4) as f32;
+    let p = chi_square_p(2500, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Map a byte to a letter by popcount
+fn letter(b: u8) -> usize {
+    match b.count_ones() {
+        0..=2 => 0,
+        c @ 3..=5 => c as usize - 2,
+        _ => 4,
+    }
+}
+
+/// Compute x^2 for counts of each k-letter word in `windows` words
+fn chi_square_words(counts: impl Iterator<Item = u32>, k: usize, windows: f64) -> f64 {
+    counts
+        .enumerate()
+        .map(|(mut w, c)| {
+            // Expected count from letter probabilities
+            let mut e = windows;
+            for _ in 0..k {
+                e *= LETTER_COUNTS[w % 5] as f64 / 256.0;
+                w /= 5;
+            }
+
+            let d = c as f64 - e;
+            d * d / e
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn count_the_ones_ok() {
+        let mut buff = vec![0u8; 256_000];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        count_the_ones_stream(&buff).expect("Count-the-1s test failed");
+    }
+
+    #[test]
+    fn count_the_ones_vector() {
+        let mut buff = vec![0u8; 100_000];
+        lcg_fill(&mut buff, 13);
+
+        // Check statistic and p value match reference
+        let r = count_the_ones_stream_result(&buff).unwrap();
+        assert_approx_eq!(r.statistic, 2502.3819, 1e-2);
+        assert_approx_eq!(r.p_value, 0.482810, 1e-3);
+    }
+
+    #[test]
+    fn count_the_ones_fail() {
+        // Bytes alternating between high and low popcounts
+        let mut buff = vec![0u8; 100_000];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        for (i, b) in buff.iter_mut().enumerate() {
+            match i % 2 {
+                0 => *b |= 0x0f,
+                _ => *b &= 0x0f,
+            }
+        }
+
+        count_the_ones_stream(&buff).expect_err("Count-the-1s p > threshold");
+
+        assert_eq!(
+            count_the_ones_stream(&buff[..COUNT_THE_ONES_MIN_BYTES - 1]),
+            Err(Error::InsufficientSampleSize(COUNT_THE_ONES_MIN_BYTES - 1))
+        );
+    }
+
+    #[test]
+    fn count_the_ones_letters() {
+        // Letter frequencies match popcount distribution
+        let mut counts = [0; 5];
+        for b in 0..=255 {
+            counts[letter(b)] += 1;
+        }
+        assert_eq!(counts, LETTER_COUNTS);
+    }
+}
@@ -0,0 +1,249 @@
+//! Diehard Craps Test
+
+use super::{chi_square_p, next_word};
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Default number of games for the craps test
+pub const CRAPS_GAMES: usize = 200_000;
+
+/// Maximum tracked throws per game, longer games are merged
+const MAX_THROWS: usize = 21;
+
+/// Consecutive rejected die values at which the data is considered stuck
+const MAX_REJECTIONS: usize = 64;
+
+/// Throws in a single game at which the data is considered stuck
+const MAX_GAME_THROWS: usize = 1000;
+
+/// Probability of winning a game, 244 / 495
+const WIN_PROB: f64 = 244.0 / 495.0;
+
+/// Diehard Craps Test over an iterator of N bits with `n` games
+///
+/// Each game is played with a pair of dice, winning on a first throw of 7 or 11 and
+/// losing on 2, 3, or 12, otherwise the first throw sets the point and the dice are
+/// thrown until either the point (win) or 7 (lose). The number of wins over all games is
+/// compared against a normal approximation with p = 244 / 495, and the number of throws
+/// per game from 1 to >= 21 against the expected distribution using x^2. This returns
+/// both P-values `(p_wins, p_throws)`, failing where either is below the threshold.
+///
+/// Each die is read from a 3-bit word with the first bit lowest, rejecting values of 6
+/// or 7 such that each face 1 to 6 is equally likely, consuming on average 4 bits per
+/// die. Diehard uses [CRAPS_GAMES] games, or ~5 Mbit, returning
+/// [Error::InsufficientSampleSize] where the data ends before `n` games are complete.
+///
+/// Data that never completes a die roll or a game, such as all ones or a periodic
+/// stream, fails with [Error::BadPValue] (p = 0) once 64 consecutive values are rejected
+/// or a game reaches 1000 throws, which random data will not plausibly reach.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn craps(data: impl Iterator<Item = bool>, n: usize) -> Result<(f32, f32), Error> {
+    let (wins, throws) = craps_result(data, n)?;
+
+    Ok((wins.check()?, throws.check()?))
+}
+
+/// Diehard Craps Test at significance level `alpha`
+///
+/// See [craps] for details
+pub fn craps_with_alpha(
+    data: impl Iterator<Item = bool>,
+    n: usize,
+    alpha: f32,
+) -> Result<(f32, f32), Error> {
+    let (wins, throws) = craps_result(data, n)?;
+
+    Ok((
+        wins.at_alpha(alpha)?.check()?,
+        throws.at_alpha(alpha)?.check()?,
+    ))
+}
+
+/// Diehard Craps Test returning both [TestResult]s, with statistics z for the number of
+/// wins and x^2 for the throws per game
+///
+/// See [craps] for details
+pub fn craps_result(
+    mut data: impl Iterator<Item = bool>,
+    n: usize,
+) -> Result<(TestResult, TestResult), Error> {
+    // Check we have at least one game
+    if n == 0 {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut bits = 0;
+    let mut wins = 0;
+    let mut counts = [0usize; MAX_THROWS];
+
+    for _ in 0..n {
+        let (won, throws) = play(&mut data, &mut bits)?;
+
+        wins += won as usize;
+        counts[throws.min(MAX_THROWS) - 1] += 1;
+    }
+
+    // Compute z and p for the number of wins
+    let t = n as f64;
+    let z = (wins as f64 - t * WIN_PROB) / math::sqrt(t * WIN_PROB * (1.0 - WIN_PROB));
+    let p1 = math::erfc(math::fabs(z) / core::f64::consts::SQRT_2) as f32;
+
+    // Compute x^2 and p for the throws per game
+    let mut x2 = 0.0;
+    for (c, p) in counts.iter().zip(throw_probs()) {
+        let d = *c as f64 - t * p;
+        x2 += d * d / (t * p);
+    }
+    let x2 = x2 as f32;
+    let p2 = chi_square_p(MAX_THROWS - 1, x2);
+
+    Ok((TestResult::new(p1, z as f32), TestResult::new(p2, x2)))
+}
+
+/// Error for data that never completes a roll or game
+const STUCK: Error = Error::BadPValue {
+    p: 0.0,
+    statistic: f32::NAN,
+};
+
+/// Play a single game, returning whether it was won and the number of throws
+fn play(data: &mut impl Iterator<Item = bool>, bits: &mut usize) -> Result<(bool, usize), Error> {
+    let mut throw = || Ok::<_, Error>(roll(data, bits)? + roll(data, bits)?);
+
+    let point = throw()?;
+    match point {
+        7 | 11 => return Ok((true, 1)),
+        2 | 3 | 12 => return Ok((false, 1)),
+        _ => (),
+    }
+
+    // Throw until the point or 7
+    for throws in 2..=MAX_GAME_THROWS {
+        let v = throw()?;
+
+        if v == point {
+            return Ok((true, throws));
+        } else if v == 7 {
+            return Ok((false, throws));
+        }
+    }
+
+    Err(STUCK)
+}
+
+/// Roll a single die, rejecting 3-bit values outside 0..6
+fn roll(data: &mut impl Iterator<Item = bool>, bits: &mut usize) -> Result<u32, Error> {
+    for _ in 0..MAX_REJECTIONS {
+        let v = next_word(data, 3).ok_or(Error::InsufficientSampleSize(*bits))?;
+        *bits += 3;
+
+        if v < 6 {
+            return Ok(v + 1);
+        }
+    }
+
+    Err(STUCK)
+}
+
+/// Compute the probability of each number of throws per game, from 1 to >= 21
+fn throw_probs() -> [f64; MAX_THROWS] {
+    let mut probs = [0.0; MAX_THROWS];
+
+    // Games decided on the first throw, 12 / 36 ways
+    probs[0] = 1.0 / 3.0;
+
+    // Subsequent throws end the game with the point or 7
+    for (k, p) in probs.iter_mut().enumerate().take(MAX_THROWS - 1).skip(1) {
+        for ways in [3.0, 4.0, 5.0, 5.0, 4.0, 3.0] {
+            let end = (ways + 6.0) / 36.0;
+            *p += ways / 36.0 * math::pow(1.0 - end, (k - 1) as f64) * end;
+        }
+    }
+
+    // Merge the remaining tail
+    probs[MAX_THROWS - 1] = 1.0 - probs[..MAX_THROWS - 1].iter().sum::<f64>();
+
+    probs
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for Diehard's 200,000 games, with margin for long games
+    const LEN: usize = CRAPS_GAMES * 4;
+
+    #[test]
+    fn craps_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        craps(BitIter::new(&buff), CRAPS_GAMES).expect("Craps test failed");
+    }
+
+    #[test]
+    fn craps_vector() {
+        let mut buff = vec![0u8; 20_000 * 8];
+        lcg_fill(&mut buff, 29);
+
+        // Check statistics and p values match reference (9912 wins)
+        let (wins, throws) = craps_result(BitIter::new(&buff), 20_000).unwrap();
+        assert_approx_eq!(wins.statistic, 0.755466, 1e-4);
+        assert_approx_eq!(wins.p_value, 0.449970, 1e-4);
+        assert_approx_eq!(throws.statistic, 15.174674, 1e-3);
+        assert_approx_eq!(throws.p_value, 0.766327, 1e-4);
+    }
+
+    #[test]
+    fn craps_fail() {
+        // Bits set with probability 1/4, biasing the dice low
+        let mut rng = StdRng::seed_from_u64(2);
+        let (mut buff, mut mask) = (vec![0u8; LEN], vec![0u8; LEN]);
+        rng.fill_bytes(&mut buff);
+        rng.fill_bytes(&mut mask);
+        buff.iter_mut().zip(mask).for_each(|(b, m)| *b &= m);
+
+        craps(BitIter::new(&buff), 20_000).expect_err("Craps p > threshold");
+    }
+
+    #[test]
+    fn craps_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(craps(BitIter::new(&buff), 0), Err(Error::InvalidParameter));
+
+        // Zero bits always roll 1, losing each game on the first throw
+        assert_eq!(
+            craps(BitIter::new(&buff), 22),
+            Err(Error::InsufficientSampleSize(21 * 6))
+        );
+    }
+
+    #[test]
+    fn craps_stuck() {
+        // All ones never roll a die
+        let r = craps(core::iter::repeat(true), 1);
+        assert!(matches!(r, Err(Error::BadPValue { p: 0.0, .. })));
+
+        // A point of 4 (1, 3) followed by only 5s (2, 3) never ends the game
+        let die = |v: u32| (0..3).map(move |i| v & (1 << i) != 0);
+        let data = die(0).chain(die(2)).chain(die(1).chain(die(2)).cycle());
+        let r = craps(data, 1);
+        assert!(matches!(r, Err(Error::BadPValue { p: 0.0, .. })));
+    }
+
+    #[test]
+    fn craps_probs() {
+        let probs = throw_probs();
+        assert_approx_eq!(probs[1], 0.188272, 1e-6);
+        assert_approx_eq!(probs[MAX_THROWS - 1], 0.001436, 1e-6);
+    }
+}
@@ -0,0 +1,188 @@
+//! Knuth's Gap Test
+
+use super::chi_square_p;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum gap length counted individually, longer gaps are merged
+pub const GAP_MAX_LEN: usize = 64;
+
+/// Knuth's Gap Test over an iterator of N uniform values
+///
+/// This counts the lengths of gaps between successive values within `[lo, hi)`, from the
+/// start of the sequence, and compares these against the geometric distribution with
+/// p = hi - lo using a chi-square test (TAOCP Vol. 2, 3.3.2 D). Gap lengths of t or more
+/// are merged, with t selected (up to [GAP_MAX_LEN]) such that every category has an
+/// expected count of at least 5, and any trailing values after the last gap are
+/// discarded. This detects clustering of values within a range that frequency tests
+/// miss.
+///
+/// The range must satisfy 0 <= lo < hi <= 1 and not cover all of [0, 1), returning
+/// [Error::InvalidParameter] otherwise, and sequences too short for two categories
+/// return [Error::InsufficientSampleSize] with the number of values consumed.
+///
+/// Bit streams can be converted to values with [uniform_floats](super::uniform_floats):
+///
+/// ```
+/// use rand::{rngs::StdRng, RngCore, SeedableRng};
+/// use rngcheck::{diehard::{gap_test, uniform_floats}, helpers::BitIter};
+///
+/// let mut buff = [0u8; 4 * 10_000];
+/// StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+///
+/// gap_test(uniform_floats(BitIter::new(&buff)), 0.0, 0.5).unwrap();
+/// ```
+pub fn gap_test(values: impl Iterator<Item = f32>, lo: f32, hi: f32) -> Result<f32, Error> {
+    gap_test_result(values, lo, hi)?.check()
+}
+
+/// Knuth's Gap Test at significance level `alpha`
+///
+/// See [gap_test] for details
+pub fn gap_test_with_alpha(
+    values: impl Iterator<Item = f32>,
+    lo: f32,
+    hi: f32,
+    alpha: f32,
+) -> Result<f32, Error> {
+    gap_test_result(values, lo, hi)?.at_alpha(alpha)?.check()
+}
+
+/// Knuth's Gap Test returning the [TestResult], with statistic x^2
+///
+/// See [gap_test] for details
+pub fn gap_test_result(
+    values: impl Iterator<Item = f32>,
+    lo: f32,
+    hi: f32,
+) -> Result<TestResult, Error> {
+    // Check range is within [0, 1), inverted to reject NaN
+    if !(lo >= 0.0 && lo < hi && hi <= 1.0 && hi - lo < 1.0) {
+        return Err(Error::InvalidParameter);
+    }
+    let p = (hi - lo) as f64;
+
+    let mut n = 0;
+    let mut gaps = 0;
+    let mut counts = [0usize; GAP_MAX_LEN + 1];
+    let mut r = 0;
+
+    for v in values {
+        n += 1;
+
+        // Record the gap on each value within the range
+        match v >= lo && v < hi {
+            true => {
+                counts[r.min(GAP_MAX_LEN)] += 1;
+                gaps += 1;
+                r = 0;
+            }
+            false => r += 1,
+        }
+    }
+
+    // Select the largest t with an expected count of at least 5 in every category,
+    // where the smallest is either the last individual length or the merged tail
+    let smallest = |t: usize| gaps as f64 * math::pow(1.0 - p, (t - 1) as f64) * p.min(1.0 - p);
+    let t = match (1..=GAP_MAX_LEN).rev().find(|t| smallest(*t) >= 5.0) {
+        Some(t) => t,
+        None => return Err(Error::InsufficientSampleSize(n)),
+    };
+
+    // Compute x^2 against the geometric distribution
+    let mut x2 = 0.0;
+    let mut q = 1.0;
+    for c in &counts[..t] {
+        let e = gaps as f64 * p * q;
+        x2 += (*c as f64 - e) * (*c as f64 - e) / e;
+        q *= 1.0 - p;
+    }
+
+    let tail = counts[t..].iter().sum::<usize>() as f64;
+    let e = gaps as f64 * q;
+    x2 += (tail - e) * (tail - e) / e;
+
+    let x2 = x2 as f32;
+    let p = chi_square_p(t, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{diehard::uniform_floats, helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for 10,000 values
+    const LEN: usize = 10_000 * 4;
+
+    #[test]
+    fn gap_test_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        for (lo, hi) in [(0.0, 0.5), (0.5, 1.0), (0.25, 0.35)] {
+            gap_test(uniform_floats(BitIter::new(&buff)), lo, hi).expect("Gap test failed");
+        }
+    }
+
+    #[test]
+    fn gap_test_vector() {
+        let mut buff = vec![0u8; LEN];
+        lcg_fill(&mut buff, 29);
+
+        // Check statistics and p values match reference
+        let r = gap_test_result(uniform_floats(BitIter::new(&buff)), 0.0, 0.5).unwrap();
+        assert_approx_eq!(r.statistic, 11.283116, 1e-3);
+        assert_approx_eq!(r.p_value, 0.256801, 1e-4);
+
+        let r = gap_test_result(uniform_floats(BitIter::new(&buff)), 0.25, 0.35).unwrap();
+        assert_approx_eq!(r.statistic, 37.24703, 1e-3);
+        assert_approx_eq!(r.p_value, 0.170053, 1e-4);
+    }
+
+    #[test]
+    fn gap_test_fail() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        // Biased source, with values skewed towards zero
+        let values = uniform_floats(BitIter::new(&buff)).map(|v| v * v);
+        gap_test(values, 0.0, 0.5).expect_err("Gap test p > threshold");
+
+        // Clustered values, with the correct frequency in range but only in pairs
+        let values = (0..10_000).map(|i| match i % 4 {
+            0 | 1 => 0.25,
+            _ => 0.75,
+        });
+        gap_test(values, 0.0, 0.5).expect_err("Gap test p > threshold");
+    }
+
+    #[test]
+    fn gap_test_invalid() {
+        let values = || (0..1000).map(|i| (i % 10) as f32 / 10.0);
+
+        for (lo, hi) in [(0.5, 0.5), (0.6, 0.5), (-0.1, 0.5), (0.5, 1.1), (0.0, 1.0)] {
+            assert_eq!(gap_test(values(), lo, hi), Err(Error::InvalidParameter));
+        }
+        assert_eq!(
+            gap_test(values(), f32::NAN, 0.5),
+            Err(Error::InvalidParameter)
+        );
+
+        assert_eq!(
+            gap_test(core::iter::empty(), 0.0, 0.5),
+            Err(Error::InsufficientSampleSize(0))
+        );
+        assert_eq!(
+            gap_test((0..10).map(|i| i as f32 / 10.0), 0.0, 0.5),
+            Err(Error::InsufficientSampleSize(10))
+        );
+    }
+}
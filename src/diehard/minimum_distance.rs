@@ -0,0 +1,207 @@
+//! Diehard Minimum Distance Test
+
+use super::{chi_square_p, next_word};
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Default points per trial (n) for the minimum distance test
+pub const MINIMUM_DISTANCE_N: usize = 8000;
+
+/// Maximum supported points per trial (n)
+pub const MINIMUM_DISTANCE_MAX_N: usize = 8000;
+
+/// Minimum number of trials, giving an expected count of 5 in each interval
+pub const MINIMUM_DISTANCE_MIN_TRIALS: usize = 50;
+
+/// Side length of the square
+const SIDE: f64 = 10_000.0;
+
+/// Diehard Minimum Distance Test over an iterator of N bits with `n` points per trial
+///
+/// Each trial places n random points in a 10,000 x 10,000 square and finds the minimum
+/// distance d between any pair of points. For random points
+/// `1 - exp(-n(n - 1)πd^2 / (2 * area))` is uniform over [0, 1), with these values for
+/// all complete trials bucketed into ten equal intervals and compared against a uniform
+/// distribution using x^2.
+///
+/// Each coordinate is read from a 32-bit word w as `x = 10,000 * w / 2^32`, two words
+/// per point in (x, y) order, so each trial consumes 64 * n bits. Diehard uses
+/// n = [MINIMUM_DISTANCE_N] with 100 trials, this requires at least
+/// [MINIMUM_DISTANCE_MIN_TRIALS] trials returning [Error::InsufficientSampleSize]
+/// otherwise. As this is `no_std` and does not allocate, `n` is limited to
+/// 2..=[MINIMUM_DISTANCE_MAX_N] with the points (64 KiB) held on the stack.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn minimum_distance(data: impl Iterator<Item = bool>, n: usize) -> Result<f32, Error> {
+    minimum_distance_result(data, n)?.check()
+}
+
+/// Diehard Minimum Distance Test at significance level `alpha`
+///
+/// See [minimum_distance] for details
+pub fn minimum_distance_with_alpha(
+    data: impl Iterator<Item = bool>,
+    n: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    minimum_distance_result(data, n)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard Minimum Distance Test returning the [TestResult], with statistic x^2
+///
+/// See [minimum_distance] for details
+pub fn minimum_distance_result(
+    mut data: impl Iterator<Item = bool>,
+    n: usize,
+) -> Result<TestResult, Error> {
+    // Check number of points is supported
+    if !(2..=MINIMUM_DISTANCE_MAX_N).contains(&n) {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut trials = 0;
+    let mut counts = [0usize; 10];
+    let mut points = [[0u32; 2]; MINIMUM_DISTANCE_MAX_N];
+
+    // Scale from d^2 to the exponential rate, n(n - 1)π / (2 * area)
+    let rate = (n * (n - 1)) as f64 * core::f64::consts::PI / (2.0 * SIDE * SIDE);
+
+    'outer: loop {
+        // Place points, discarding incomplete trials
+        for p in points[..n].iter_mut() {
+            match (next_word(&mut data, 32), next_word(&mut data, 32)) {
+                (Some(x), Some(y)) => *p = [x, y],
+                _ => break 'outer,
+            }
+        }
+
+        // Bucket uniform value, with 1.0 in the last interval
+        let d2 = min_distance_sq(&mut points[..n], SIDE);
+        let u = 1.0 - math::exp(-rate * d2);
+        counts[((u * 10.0) as usize).min(9)] += 1;
+
+        trials += 1;
+    }
+
+    // Check we have enough trials
+    if trials < MINIMUM_DISTANCE_MIN_TRIALS {
+        return Err(Error::InsufficientSampleSize(trials * n * 64));
+    }
+
+    // Compute x^2 over intervals
+    let e = trials as f32 / 10.0;
+    let mut x2 = 0.0;
+    for v in counts {
+        x2 += math::powf(v as f32 - e, 2.0) / e;
+    }
+
+    // Compute p
+    let p = chi_square_p(9, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Compute the squared minimum distance between points with coordinates scaled from
+/// [0, 2^32) to [0, `side`), in square units
+///
+/// This sorts the points by the first coordinate and sweeps each against its following
+/// points until that separation alone exceeds the current minimum, so is shared with the
+/// 3D [spheres_3d](super::spheres_3d) test.
+pub(super) fn min_distance_sq<const D: usize>(points: &mut [[u32; D]], side: f64) -> f64 {
+    let scale = side / (1u64 << 32) as f64;
+    points.sort_unstable();
+
+    let mut min = f64::INFINITY;
+    for (i, a) in points.iter().enumerate() {
+        for b in &points[i + 1..] {
+            let dx = (b[0] - a[0]) as f64 * scale;
+            if dx * dx >= min {
+                break;
+            }
+
+            let d2 = a
+                .iter()
+                .zip(b)
+                .skip(1)
+                .map(|(a, b)| a.abs_diff(*b) as f64 * scale)
+                .fold(dx * dx, |d2, d| d2 + d * d);
+            min = min.min(d2);
+        }
+    }
+
+    min
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn minimum_distance_ok() {
+        let mut buff = vec![0u8; MINIMUM_DISTANCE_MIN_TRIALS * MINIMUM_DISTANCE_N * 8];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        minimum_distance(BitIter::new(&buff), MINIMUM_DISTANCE_N)
+            .expect("Minimum distance test failed");
+    }
+
+    #[test]
+    fn minimum_distance_vector() {
+        let mut buff = vec![0u8; 50 * 1000 * 8];
+        lcg_fill(&mut buff, 17);
+
+        // Check statistic and p value match reference (counts [4, 4, 4, 4, 5, 3, 5, 6, 7, 8])
+        let r = minimum_distance_result(BitIter::new(&buff), 1000).unwrap();
+        assert_approx_eq!(r.statistic, 4.4, 1e-4);
+        assert_approx_eq!(r.p_value, 0.883171, 1e-4);
+    }
+
+    #[test]
+    fn minimum_distance_fail() {
+        // Coordinates on a coarse lattice, with many coincident points
+        let mut buff = vec![0u8; 50 * 1000 * 8];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        for w in buff.chunks_exact_mut(4) {
+            w[..3].fill(0);
+        }
+
+        minimum_distance(BitIter::new(&buff), 1000).expect_err("Minimum distance p > threshold");
+    }
+
+    #[test]
+    fn minimum_distance_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            minimum_distance(BitIter::new(&buff), 1),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            minimum_distance(BitIter::new(&buff), MINIMUM_DISTANCE_MAX_N + 1),
+            Err(Error::InvalidParameter)
+        );
+
+        let buff = vec![0u8; 49 * 100 * 8];
+        assert_eq!(
+            minimum_distance(BitIter::new(&buff), 100),
+            Err(Error::InsufficientSampleSize(49 * 100 * 64))
+        );
+    }
+
+    #[test]
+    fn minimum_distance_points() {
+        // Closest pair is not adjacent in x
+        let s = (1u64 << 32) as f64 / SIDE;
+        let mut points = [(0.0, 0.0), (1.0, 100.0), (2.0, 0.0), (2.5, 1.0)]
+            .map(|(x, y)| [(x * s) as u32, (y * s) as u32]);
+
+        assert_approx_eq!(min_distance_sq(&mut points, SIDE), 1.25, 1e-5);
+    }
+}
@@ -0,0 +1,277 @@
+//! Diehard Monkey Tests (OPSO, OQSO, DNA)
+//!
+//! These treat letters taken from a fixed bit position of each 32-bit word as keystrokes
+//! typed by a monkey, counting the number of possible 20-bit words that never appear in
+//! [MONKEY_WORDS] overlapping words. For a random sequence the missing word count is
+//! approximately normal with mean `2^20 e^-2` (~141,909), and a standard deviation
+//! (from Diehard) depending on the letter size.
+//!
+//! Words are read from the bit stream with the first bit lowest, with letters taken
+//! from bits `offset..offset + letter bits` of each word and the first letter of each
+//! word highest. Diehard repeats each test over a range of offsets, from the lowest
+//! bits (offset 0) to the highest (offset `32 - letter bits`), each taking
+//! [MONKEY_WORDS] words plus one per extra letter (~8 MiB). As this is `no_std` and does
+//! not allocate, the caller provides a `scratch` buffer of at least [MONKEY_SCRATCH_LEN]
+//! bytes to track the words seen.
+
+use super::next_word;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Number of overlapping words counted by each monkey test
+pub const MONKEY_WORDS: usize = 1 << 21;
+
+/// Scratch buffer length (in bytes) required by the monkey tests, one bit per word
+pub const MONKEY_SCRATCH_LEN: usize = (1 << WORD_BITS) / 8;
+
+/// Bits per word for all monkey tests
+const WORD_BITS: usize = 20;
+
+/// Expected number of missing words, `2^20 (1 - 2^-20)^(2^21)`
+const MEAN: f64 = 141_909.19;
+
+/// Diehard OPSO (Overlapping Pairs Sparse Occupancy) Test over an iterator of N bits
+///
+/// Each word is two 10-bit letters, with a standard deviation of 290 missing words.
+/// See the [module](self) documentation for details.
+pub fn opso(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<f32, Error> {
+    opso_result(data, offset, scratch)?.check()
+}
+
+/// Diehard OPSO Test at significance level `alpha`
+///
+/// See [opso] for details
+pub fn opso_with_alpha(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+    alpha: f32,
+) -> Result<f32, Error> {
+    opso_result(data, offset, scratch)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard OPSO Test returning the [TestResult], with statistic z for the number of
+/// missing words
+///
+/// See [opso] for details
+pub fn opso_result(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<TestResult, Error> {
+    monkey_test(data, 2, 10, offset, 290.0, scratch)
+}
+
+/// Diehard OQSO (Overlapping Quadruples Sparse Occupancy) Test over an iterator of N bits
+///
+/// Each word is four 5-bit letters, with a standard deviation of 295 missing words.
+/// See the [module](self) documentation for details.
+pub fn oqso(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<f32, Error> {
+    oqso_result(data, offset, scratch)?.check()
+}
+
+/// Diehard OQSO Test at significance level `alpha`
+///
+/// See [oqso] for details
+pub fn oqso_with_alpha(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+    alpha: f32,
+) -> Result<f32, Error> {
+    oqso_result(data, offset, scratch)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard OQSO Test returning the [TestResult], with statistic z for the number of
+/// missing words
+///
+/// See [oqso] for details
+pub fn oqso_result(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<TestResult, Error> {
+    monkey_test(data, 4, 5, offset, 295.0, scratch)
+}
+
+/// Diehard DNA Test over an iterator of N bits
+///
+/// Each word is ten 2-bit letters (from the alphabet C, G, A, T), with a standard
+/// deviation of 339 missing words. See the [module](self) documentation for details.
+pub fn dna(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<f32, Error> {
+    dna_result(data, offset, scratch)?.check()
+}
+
+/// Diehard DNA Test at significance level `alpha`
+///
+/// See [dna] for details
+pub fn dna_with_alpha(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+    alpha: f32,
+) -> Result<f32, Error> {
+    dna_result(data, offset, scratch)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard DNA Test returning the [TestResult], with statistic z for the number of
+/// missing words
+///
+/// See [dna] for details
+pub fn dna_result(
+    data: impl Iterator<Item = bool>,
+    offset: usize,
+    scratch: &mut [u8],
+) -> Result<TestResult, Error> {
+    monkey_test(data, 10, 2, offset, 339.0, scratch)
+}
+
+/// Count the missing words of `letters` letters of `letter_bits` bits, comparing against
+/// the normal distribution with standard deviation `sigma`
+///
+/// Returns [Error::InvalidParameter] where the letters do not fit within a 32-bit word
+/// at `offset` or `scratch` is too short, and [Error::InsufficientSampleSize] where the
+/// data ends before [MONKEY_WORDS] words are complete.
+fn monkey_test(
+    mut data: impl Iterator<Item = bool>,
+    letters: usize,
+    letter_bits: usize,
+    offset: usize,
+    sigma: f64,
+    scratch: &mut [u8],
+) -> Result<TestResult, Error> {
+    debug_assert_eq!(letters * letter_bits, WORD_BITS);
+
+    // Check parameters are supported
+    if offset + letter_bits > 32 || scratch.len() < MONKEY_SCRATCH_LEN {
+        return Err(Error::InvalidParameter);
+    }
+
+    let seen = &mut scratch[..MONKEY_SCRATCH_LEN];
+    seen.fill(0);
+
+    let mask = (1 << WORD_BITS) - 1;
+    let mut word = 0usize;
+
+    // Type letters, marking each word once the first is complete
+    for i in 0..MONKEY_WORDS + letters - 1 {
+        let letter = match next_word(&mut data, 32) {
+            Some(v) => (v >> offset) as usize & ((1 << letter_bits) - 1),
+            None => return Err(Error::InsufficientSampleSize(i * 32)),
+        };
+
+        word = ((word << letter_bits) | letter) & mask;
+
+        if i >= letters - 1 {
+            seen[word / 8] |= 1 << (word % 8);
+        }
+    }
+
+    // Count missing words
+    let missing = (1 << WORD_BITS) - crate::popcount::popcount(seen);
+
+    // Compute z and p
+    let z = (missing as f64 - MEAN) / sigma;
+    let p = math::erfc(math::fabs(z) / core::f64::consts::SQRT_2) as f32;
+
+    Ok(TestResult::new(p, z as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::{vec, vec::Vec};
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes of data for each test
+    const LEN: usize = (MONKEY_WORDS + 9) * 4;
+
+    #[test]
+    fn monkey_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+
+        oqso(BitIter::new(&buff), 27, &mut scratch).expect("OQSO test failed");
+    }
+
+    #[test]
+    fn monkey_vector() {
+        let mut buff = vec![0u8; LEN];
+        lcg_fill(&mut buff, 37);
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+
+        // Check statistics and p values match reference
+        let r: Vec<_> = [
+            opso_result(BitIter::new(&buff), 22, &mut scratch),
+            oqso_result(BitIter::new(&buff), 3, &mut scratch),
+            dna_result(BitIter::new(&buff), 12, &mut scratch),
+        ]
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect();
+
+        let expected = [
+            (2.402793, 0.016270),
+            (0.060373, 0.951859),
+            (-1.313245, 0.189100),
+        ];
+        for (r, (z, p)) in r.iter().zip(expected) {
+            assert_approx_eq!(r.statistic, z, 1e-3);
+            assert_approx_eq!(r.p_value, p, 1e-4);
+        }
+    }
+
+    #[test]
+    fn monkey_fail() {
+        // Words repeating with a short period leave most words missing
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff[..4096]);
+        for i in 4096..LEN {
+            buff[i] = buff[i - 4096];
+        }
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+
+        opso(BitIter::new(&buff), 0, &mut scratch).expect_err("OPSO p > threshold");
+    }
+
+    #[test]
+    fn monkey_invalid() {
+        let buff = [0u8; 1024];
+        let mut scratch = vec![0u8; MONKEY_SCRATCH_LEN];
+
+        assert_eq!(
+            opso(BitIter::new(&buff), 23, &mut scratch),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            dna(
+                BitIter::new(&buff),
+                0,
+                &mut scratch[..MONKEY_SCRATCH_LEN - 1]
+            ),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            oqso(BitIter::new(&buff), 0, &mut scratch),
+            Err(Error::InsufficientSampleSize(1024 * 8))
+        );
+    }
+}
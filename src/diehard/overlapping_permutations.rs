@@ -0,0 +1,337 @@
+//! Diehard Overlapping 5-Permutations Test
+
+use super::chi_square_p;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Minimum number of values for the overlapping permutations test, giving an expected
+/// count of 100 for each ordering
+pub const OVERLAPPING_PERMUTATIONS_MIN_VALUES: usize = 12_000;
+
+/// Number of orderings of five values
+const PERMS: usize = 120;
+
+/// Degrees of freedom, the rank of the covariance matrix (5! - 4!)
+const DOF: usize = 96;
+
+/// Maximum conjugate gradient iterations, convergence typically takes ~30
+const MAX_ITERATIONS: usize = 200;
+
+/// Diehard Overlapping 5-Permutations Test over an iterator of N 32-bit values
+///
+/// Each window of five consecutive values (wrapping around at the end, so N values give
+/// N windows) is classified by the ordering of its values into one of 120 permutations.
+/// As overlapping windows are not independent the counts are compared against a uniform
+/// distribution using the quadratic form `x^2 = (c - e)' Σ^+ (c - e)`, with Σ^+ the
+/// pseudo-inverse of the covariance matrix (rank 96), giving x^2 with 96 degrees of
+/// freedom. Equal values are ordered by position.
+///
+/// Rather than hard-coding the 120 x 120 pseudo-inverse, the covariance is computed from
+/// the probability of each pair of orderings at offsets 1 to 4, solving Σ y = (c - e)
+/// by conjugate gradients, so this requires ~32 KiB of stack. Diehard uses 1,000,000
+/// values, this requires at least [OVERLAPPING_PERMUTATIONS_MIN_VALUES] values returning
+/// [Error::InsufficientSampleSize] otherwise.
+///
+/// See [WordsFromBits](crate::helpers::WordsFromBits) for use with bit streams
+pub fn overlapping_permutations(values: impl Iterator<Item = u32>) -> Result<f32, Error> {
+    overlapping_permutations_result(values)?.check()
+}
+
+/// Diehard Overlapping 5-Permutations Test at significance level `alpha`
+///
+/// See [overlapping_permutations] for details
+pub fn overlapping_permutations_with_alpha(
+    values: impl Iterator<Item = u32>,
+    alpha: f32,
+) -> Result<f32, Error> {
+    overlapping_permutations_result(values)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// Diehard Overlapping 5-Permutations Test returning the [TestResult], with statistic x^2
+///
+/// See [overlapping_permutations] for details
+pub fn overlapping_permutations_result(
+    values: impl Iterator<Item = u32>,
+) -> Result<TestResult, Error> {
+    let mut n = 0;
+    let mut first = [0u32; 4];
+    let mut window = [0u32; 5];
+    let mut counts = [0usize; PERMS];
+
+    // Count orderings of each complete window
+    for v in values {
+        if n < 4 {
+            first[n] = v;
+        }
+
+        window.copy_within(1.., 0);
+        window[4] = v;
+        n += 1;
+
+        if n >= 5 {
+            counts[perm_index(&window)] += 1;
+        }
+    }
+
+    // Check we have enough values
+    if n < OVERLAPPING_PERMUTATIONS_MIN_VALUES {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Wrap windows around to the first values
+    for v in first {
+        window.copy_within(1.., 0);
+        window[4] = v;
+        counts[perm_index(&window)] += 1;
+    }
+
+    // Compute scaled deviations from the expected count, with covariance n Σ
+    let e = n as f64 / PERMS as f64;
+    let scale = 1.0 / math::sqrt(n as f64);
+    let b: [f64; PERMS] = core::array::from_fn(|i| (counts[i] as f64 - e) * scale);
+
+    // Solve Σ y = b, with the deviations in the range of Σ as windows wrap
+    let cov = Covariance::new();
+    let y = cov.solve(&b);
+
+    let x2 = b.iter().zip(&y).map(|(b, y)| b * y).sum::<f64>() as f32;
+
+    // Compute p
+    let p = chi_square_p(DOF, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Compute the index (0..120) of the ordering of five values, by Lehmer code
+fn perm_index(v: &[u32; 5]) -> usize {
+    let mut index = 0;
+    for i in 0..5 {
+        let smaller = v[i + 1..].iter().filter(|w| **w < v[i]).count();
+        index = index * (5 - i) + smaller;
+    }
+    index
+}
+
+/// Covariance matrix of ordering counts for overlapping windows of five i.i.d. values
+///
+/// `Σ = diag(p) - p p' + Σ_d (J_d + J_d' - 2 p p')` for offsets d of 1 to 4, where
+/// `J_d[a][b]` is the probability of ordering a in one window and b in the window
+/// starting d values later. The joint terms are held as counts out of 9!.
+struct Covariance {
+    joint: [[u16; PERMS]; PERMS],
+}
+
+impl Covariance {
+    fn new() -> Self {
+        let ranks = ranks();
+
+        // Sum joint counts over each offset, scaling from (5 + d)! to 9!
+        let mut joint = [[0u16; PERMS]; PERMS];
+        for (a, row) in joint.iter_mut().enumerate() {
+            for (b, v) in row.iter_mut().enumerate() {
+                let mut scale = 9 * 8 * 7 * 6u32;
+                for d in 1..5 {
+                    scale /= 5 + d as u32;
+                    let c =
+                        joint_count(&ranks[a], &ranks[b], d) + joint_count(&ranks[b], &ranks[a], d);
+                    *v += (c * scale) as u16;
+                }
+            }
+        }
+
+        Self { joint }
+    }
+
+    /// Compute Σ v
+    fn mul(&self, v: &[f64; PERMS]) -> [f64; PERMS] {
+        let p = 1.0 / PERMS as f64;
+        let sum = v.iter().sum::<f64>();
+
+        core::array::from_fn(|a| {
+            let joint = self.joint[a]
+                .iter()
+                .zip(v)
+                .map(|(j, v)| *j as f64 * v)
+                .sum::<f64>();
+
+            p * v[a] - 9.0 * p * p * sum + joint / 362_880.0
+        })
+    }
+    /// Solve Σ y = b by conjugate gradients, giving y = Σ^+ b for b in the range of Σ
+    fn solve(&self, b: &[f64; PERMS]) -> [f64; PERMS] {
+        let dot = |x: &[f64; PERMS], y: &[f64; PERMS]| x.iter().zip(y).map(|(x, y)| x * y).sum();
+
+        let mut y = [0f64; PERMS];
+        let mut r = *b;
+        let mut d = *b;
+        let mut rr: f64 = dot(&r, &r);
+        let tol = rr * 1e-24;
+
+        for _ in 0..MAX_ITERATIONS {
+            if rr <= tol {
+                break;
+            }
+
+            let sd = self.mul(&d);
+            let alpha = rr / dot(&d, &sd);
+
+            for i in 0..PERMS {
+                y[i] += alpha * d[i];
+                r[i] -= alpha * sd[i];
+            }
+
+            let rr_next = dot(&r, &r);
+            let beta = rr_next / rr;
+            rr = rr_next;
+
+            for i in 0..PERMS {
+                d[i] = r[i] + beta * d[i];
+            }
+        }
+
+        y
+    }
+}
+
+/// Compute the rank of each value within each ordering, inverting the Lehmer code
+fn ranks() -> [[u8; 5]; PERMS] {
+    core::array::from_fn(|index| {
+        let mut r = [0u8; 5];
+        let mut rem = index;
+        let mut codes = [0usize; 5];
+        for i in (0..5).rev() {
+            codes[i] = rem % (5 - i);
+            rem /= 5 - i;
+        }
+
+        // Each code counts the smaller values to the right, selecting from unused ranks
+        let mut used = [false; 5];
+        for i in 0..5 {
+            let rank = (0..5).filter(|r| !used[*r]).nth(codes[i]).unwrap();
+            used[rank] = true;
+            r[i] = rank as u8;
+        }
+        r
+    })
+}
+
+/// Compute the number of orderings of 5 + d values with ranks a in the first five values
+/// and b in the five starting at d, out of (5 + d)!
+fn joint_count(a: &[u8; 5], b: &[u8; 5], d: usize) -> u32 {
+    let k = 5 - d;
+
+    // Overlapping values must be in the same order
+    for i in 0..k {
+        for j in i + 1..k {
+            if (a[d + i] < a[d + j]) != (b[i] < b[j]) {
+                return 0;
+            }
+        }
+    }
+
+    // Count values outside the overlap falling between each pair of overlapping values,
+    // which are ordered within each window and may interleave freely between them
+    let mut gaps_a = [0u32; 5];
+    let mut gaps_b = [0u32; 5];
+    for x in &a[..d] {
+        gaps_a[a[d..].iter().filter(|o| *o < x).count()] += 1;
+    }
+    for x in &b[k..] {
+        gaps_b[b[..k].iter().filter(|o| *o < x).count()] += 1;
+    }
+
+    gaps_a
+        .iter()
+        .zip(&gaps_b)
+        .map(|(&m, &n)| binomial(m + n, m))
+        .product()
+}
+
+/// Compute the binomial coefficient C(n, k) for small n
+fn binomial(n: u32, k: u32) -> u32 {
+    (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn overlapping_permutations_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let values = (0..50_000).map(|_| rng.next_u32());
+
+        overlapping_permutations(values).expect("Overlapping permutations test failed");
+    }
+
+    #[test]
+    fn overlapping_permutations_vector() {
+        let mut buff = vec![0u8; 20_000 * 4];
+        lcg_fill(&mut buff, 31);
+
+        // Check statistic and p value match reference
+        let values = buff
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
+        let r = overlapping_permutations_result(values).unwrap();
+        assert_approx_eq!(r.statistic, 114.39466, 1e-3);
+        assert_approx_eq!(r.p_value, 0.097055, 1e-4);
+    }
+
+    #[test]
+    fn overlapping_permutations_fail() {
+        // Slowly increasing values, dominated by ascending orderings
+        let mut rng = StdRng::seed_from_u64(2);
+        let values = (0..20_000u32).map(|i| i.wrapping_mul(1 << 16) + (rng.next_u32() >> 14));
+
+        overlapping_permutations(values).expect_err("Overlapping permutations p > threshold");
+    }
+
+    #[test]
+    fn overlapping_permutations_invalid() {
+        assert_eq!(
+            overlapping_permutations(0..11_999),
+            Err(Error::InsufficientSampleSize(11_999))
+        );
+    }
+
+    #[test]
+    fn overlapping_permutations_index() {
+        // Lehmer code indices, with ties ordered by position
+        assert_eq!(perm_index(&[1, 2, 3, 4, 5]), 0);
+        assert_eq!(perm_index(&[5, 4, 3, 2, 1]), 119);
+        assert_eq!(perm_index(&[7, 7, 7, 7, 7]), 0);
+
+        // Ranks round-trip through the index
+        for (i, r) in ranks().iter().enumerate() {
+            assert_eq!(perm_index(&r.map(|v| v as u32)), i);
+        }
+    }
+
+    #[test]
+    fn overlapping_permutations_joint() {
+        // Joint counts over each offset sum to (5 + d)!
+        let ranks = ranks();
+        for (d, total) in [(1, 720), (2, 5040), (3, 40320), (4, 362880)] {
+            let mut sum = 0;
+            for a in &ranks {
+                for b in &ranks {
+                    sum += joint_count(a, b, d);
+                }
+            }
+            assert_eq!(sum, total);
+        }
+
+        // Ascending then ascending at offset 1, 6 values in order
+        assert_eq!(joint_count(&ranks[0], &ranks[0], 1), 1);
+    }
+}
@@ -0,0 +1,184 @@
+//! Diehard Overlapping Sums Test
+
+use crate::helpers::{ks_statistic, ks_test, normal_cdf_f64};
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Number of values in each overlapping sum (m)
+pub const OVERLAPPING_SUMS_M: usize = 100;
+
+/// Number of blocks of m sums, each tested for normality
+pub const OVERLAPPING_SUMS_BLOCKS: usize = 10;
+
+/// Values consumed by each block of m overlapping sums
+const BLOCK_VALUES: usize = 2 * OVERLAPPING_SUMS_M - 1;
+
+/// Entries in the packed lower triangular Cholesky factor
+const FACTOR_LEN: usize = OVERLAPPING_SUMS_M * (OVERLAPPING_SUMS_M + 1) / 2;
+
+/// Diehard Overlapping Sums Test over an iterator of N uniform values
+///
+/// Each block forms the m = [OVERLAPPING_SUMS_M] overlapping sums
+/// `S_j = U_j + ... + U_{j + m - 1}` of 2m - 1 consecutive uniform values, which are
+/// approximately normal with mean m / 2 and covariance `(m - |j - k|) / 12`. The sums are
+/// decorrelated into independent standard normals using the Cholesky factor of this
+/// covariance, converted to uniforms with Φ, and tested using
+/// [ks_test](crate::helpers::ks_test). The P-values of [OVERLAPPING_SUMS_BLOCKS] blocks
+/// are then checked for uniformity with a second KS test.
+///
+/// This consumes `10 * (2m - 1)` = 1990 values, returning [Error::InsufficientSampleSize]
+/// with the number of values consumed where fewer are provided, and any further values
+/// are not consumed. As this is `no_std` and does not allocate, the Cholesky factor
+/// (40 KiB) is held on the stack.
+///
+/// Bit streams can be converted to values with [uniform_floats](super::uniform_floats).
+pub fn overlapping_sums(values: impl Iterator<Item = f32>) -> Result<f32, Error> {
+    overlapping_sums_result(values)?.check()
+}
+
+/// Diehard Overlapping Sums Test at significance level `alpha`
+///
+/// See [overlapping_sums] for details
+pub fn overlapping_sums_with_alpha(
+    values: impl Iterator<Item = f32>,
+    alpha: f32,
+) -> Result<f32, Error> {
+    overlapping_sums_result(values)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard Overlapping Sums Test returning the [TestResult], with statistic D from the KS
+/// test over block P-values
+///
+/// See [overlapping_sums] for details
+pub fn overlapping_sums_result(mut values: impl Iterator<Item = f32>) -> Result<TestResult, Error> {
+    const M: usize = OVERLAPPING_SUMS_M;
+
+    let factor = covariance_factor();
+
+    let mut n = 0;
+    let mut p_values = [0f32; OVERLAPPING_SUMS_BLOCKS];
+
+    for p in p_values.iter_mut() {
+        // Read the values for this block
+        let mut u = [0f64; BLOCK_VALUES];
+        for v in u.iter_mut() {
+            match values.next() {
+                Some(x) => *v = x as f64,
+                None => return Err(Error::InsufficientSampleSize(n)),
+            }
+            n += 1;
+        }
+
+        // Form centred overlapping sums
+        let mut s = [0f64; M];
+        s[0] = u[..M].iter().sum::<f64>();
+        for j in 1..M {
+            s[j] = s[j - 1] - u[j - 1] + u[j + M - 1];
+        }
+        s.iter_mut().for_each(|s| *s -= M as f64 / 2.0);
+
+        // Decorrelate by forward substitution, z = L^-1 s, and convert to uniforms
+        let mut z = [0f64; M];
+        let mut uniforms = [0f32; M];
+        for i in 0..M {
+            let row = &factor[i * (i + 1) / 2..][..i + 1];
+            let acc = row[..i].iter().zip(&z).map(|(l, z)| l * z).sum::<f64>();
+            z[i] = (s[i] - acc) / row[i];
+            uniforms[i] = normal_cdf_f64(z[i]) as f32;
+        }
+
+        *p = ks_test(&mut uniforms);
+    }
+
+    // Check block P-values are uniform
+    let (d, p) = ks_statistic(&mut p_values);
+
+    Ok(TestResult::new(p as f32, d as f32))
+}
+
+/// Compute the packed lower triangular Cholesky factor of the covariance of m
+/// overlapping sums, `(m - |j - k|) / 12`
+fn covariance_factor() -> [f64; FACTOR_LEN] {
+    const M: usize = OVERLAPPING_SUMS_M;
+
+    let mut l = [0f64; FACTOR_LEN];
+    let idx = |i: usize, j: usize| i * (i + 1) / 2 + j;
+
+    for i in 0..M {
+        for j in 0..=i {
+            let c = (M - (i - j)) as f64 / 12.0;
+            let s = c - (0..j).map(|k| l[idx(i, k)] * l[idx(j, k)]).sum::<f64>();
+
+            l[idx(i, j)] = match i == j {
+                true => math::sqrt(s),
+                false => s / l[idx(j, j)],
+            };
+        }
+    }
+
+    l
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{diehard::uniform_floats, helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for the values consumed by the test
+    const LEN: usize = OVERLAPPING_SUMS_BLOCKS * BLOCK_VALUES * 4;
+
+    #[test]
+    fn overlapping_sums_ok() {
+        let mut buff = [0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        overlapping_sums(uniform_floats(BitIter::new(&buff))).expect("Overlapping sums failed");
+    }
+
+    #[test]
+    fn overlapping_sums_vector() {
+        let mut buff = [0u8; LEN];
+        lcg_fill(&mut buff, 59);
+
+        // Check statistic and p value match reference
+        let r = overlapping_sums_result(uniform_floats(BitIter::new(&buff))).unwrap();
+        assert_approx_eq!(r.statistic, 0.282079, 1e-4);
+        assert_approx_eq!(r.p_value, 0.345394, 1e-4);
+    }
+
+    #[test]
+    fn overlapping_sums_fail() {
+        let mut buff = [0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        // Biased source, with values skewed towards zero
+        let values = uniform_floats(BitIter::new(&buff)).map(|v| math::powf(v, 1.2));
+        overlapping_sums(values).expect_err("Overlapping sums p > threshold");
+    }
+
+    #[test]
+    fn overlapping_sums_insufficient() {
+        let buff = [0u8; LEN - 4];
+        assert_eq!(
+            overlapping_sums(uniform_floats(BitIter::new(&buff))),
+            Err(Error::InsufficientSampleSize(
+                OVERLAPPING_SUMS_BLOCKS * BLOCK_VALUES - 1
+            ))
+        );
+    }
+
+    #[test]
+    fn covariance_factor_product() {
+        // L L^T reproduces the covariance
+        let l = covariance_factor();
+        let idx = |i: usize, j: usize| i * (i + 1) / 2 + j;
+
+        for (i, j) in [(0, 0), (1, 0), (50, 20), (99, 0), (99, 98), (99, 99)] {
+            let c = (0..=j).map(|k| l[idx(i, k)] * l[idx(j, k)]).sum::<f64>();
+            assert_approx_eq!(c, (OVERLAPPING_SUMS_M - (i - j)) as f64 / 12.0, 1e-9);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! Diehard Parking Lot Test
+
+use super::{chi_square_p, next_word};
+use crate::{Error, TestResult};
+
+/// Parking attempts per trial
+pub const PARKING_LOT_ATTEMPTS: usize = 12_000;
+
+/// Side length of the parking lot
+const SIDE: usize = 100;
+
+/// Expected mean and standard deviation of the number of cars parked per trial
+const MEAN: f64 = 3523.0;
+const SIGMA: f64 = 21.9;
+
+/// One unit in 16.16 fixed point coordinates
+const UNIT: u32 = 1 << 16;
+
+/// Diehard Parking Lot Test over an iterator of N bits with `n` trials
+///
+/// Each trial attempts to park [PARKING_LOT_ATTEMPTS] cars at random positions in a
+/// 100 x 100 square, where a car crashes (and is not parked) if it is within 1 of a
+/// parked car in both the x and y directions. The number of cars parked in each trial is
+/// approximately normal with mean 3523 and standard deviation 21.9, with the sum of the
+/// squared z-scores over trials compared against x^2 with `n` degrees of freedom.
+///
+/// Each coordinate is read from a 16-bit word w as `x = 100 * w / 2^16`, two words per
+/// attempt in (x, y) order, so each trial consumes 384,000 bits. Diehard uses 10 trials.
+/// As this is `no_std` and does not allocate, parked cars are held (~40 KiB) on the
+/// stack in a grid of unit cells, each of which can hold at most one car.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn parking_lot(data: impl Iterator<Item = bool>, n: usize) -> Result<f32, Error> {
+    parking_lot_result(data, n)?.check()
+}
+
+/// Diehard Parking Lot Test at significance level `alpha`
+///
+/// See [parking_lot] for details
+pub fn parking_lot_with_alpha(
+    data: impl Iterator<Item = bool>,
+    n: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    parking_lot_result(data, n)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard Parking Lot Test returning the [TestResult], with statistic x^2
+///
+/// See [parking_lot] for details
+pub fn parking_lot_result(
+    mut data: impl Iterator<Item = bool>,
+    n: usize,
+) -> Result<TestResult, Error> {
+    // Check we have at least one trial
+    if n == 0 {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut bits = 0;
+    let mut x2 = 0.0;
+
+    for _ in 0..n {
+        let parked = match park(&mut data) {
+            Some(v) => v,
+            None => return Err(Error::InsufficientSampleSize(bits)),
+        };
+        bits += PARKING_LOT_ATTEMPTS * 32;
+
+        // Accumulate squared z-score
+        let z = (parked as f64 - MEAN) / SIGMA;
+        x2 += z * z;
+    }
+
+    // Compute p
+    let x2 = x2 as f32;
+    let p = chi_square_p(n, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Run a single trial, returning the number of cars parked or `None` where the data ends
+fn park(data: &mut impl Iterator<Item = bool>) -> Option<usize> {
+    // Occupancy and fractional position of the car in each unit cell
+    let mut occupied = [0u64; (SIDE * SIDE).div_ceil(64)];
+    let mut offsets = [(0u16, 0u16); SIDE * SIDE];
+    let mut parked = 0;
+
+    for _ in 0..PARKING_LOT_ATTEMPTS {
+        // Read 16.16 fixed point coordinates
+        let x = next_word(data, 16)? * SIDE as u32;
+        let y = next_word(data, 16)? * SIDE as u32;
+        let (cx, cy) = ((x / UNIT) as usize, (y / UNIT) as usize);
+
+        // Check for crashes against cars in the neighbouring cells
+        let mut crashed = false;
+        'check: for i in cx.saturating_sub(1)..(cx + 2).min(SIDE) {
+            for j in cy.saturating_sub(1)..(cy + 2).min(SIDE) {
+                let c = i * SIDE + j;
+                if occupied[c / 64] & (1 << (c % 64)) == 0 {
+                    continue;
+                }
+
+                let (ox, oy) = offsets[c];
+                let px = i as u32 * UNIT + ox as u32;
+                let py = j as u32 * UNIT + oy as u32;
+
+                if x.abs_diff(px) <= UNIT && y.abs_diff(py) <= UNIT {
+                    crashed = true;
+                    break 'check;
+                }
+            }
+        }
+
+        if !crashed {
+            let c = cx * SIDE + cy;
+            occupied[c / 64] |= 1 << (c % 64);
+            offsets[c] = ((x % UNIT) as u16, (y % UNIT) as u16);
+            parked += 1;
+        }
+    }
+
+    Some(parked)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    /// Bytes for Diehard's 10 trials
+    const LEN: usize = 10 * PARKING_LOT_ATTEMPTS * 4;
+
+    #[test]
+    fn parking_lot_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        parking_lot(BitIter::new(&buff), 10).expect("Parking lot test failed");
+
+        // Cars parked are close to the expected mean
+        let parked = park(&mut BitIter::new(&buff)).unwrap();
+        assert!(parked.abs_diff(3523) < 100, "Unexpected count {parked}");
+    }
+
+    #[test]
+    fn parking_lot_fail() {
+        // Coordinates limited to the lower half of the lot
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        for b in buff.iter_mut().skip(1).step_by(2) {
+            *b &= 0x7f;
+        }
+
+        parking_lot(BitIter::new(&buff), 10).expect_err("Parking lot p > threshold");
+    }
+
+    #[test]
+    fn parking_lot_invalid() {
+        let buff = vec![0u8; LEN];
+
+        assert_eq!(
+            parking_lot(BitIter::new(&buff), 0),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            parking_lot(BitIter::new(&buff), 11),
+            Err(Error::InsufficientSampleSize(LEN * 8))
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! Poker (Partition) Test
+
+use super::chi_square_p;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Digits per hand for the standard poker test
+pub const POKER_DIGITS: usize = 5;
+
+/// Radix for the standard poker test
+pub const POKER_RADIX: usize = 10;
+
+/// Maximum supported digits per hand
+pub const POKER_MAX_DIGITS: usize = 16;
+
+/// Poker (Partition) Test over a buffer of bytes, with hands of `digits_per_hand`
+/// base-`radix` digits
+///
+/// This splits the stream into hands of k digits and counts hands by their number of
+/// distinct digits s, comparing these against the probabilities
+/// `r (r - 1) ... (r - s + 1) S(k, s) / r^k` with S the Stirling numbers of the second
+/// kind (TAOCP Vol. 2, 3.3.2 C). Categories are merged from the fewest distinct digits
+/// until each has an expected count of at least 5. The standard test uses
+/// [POKER_DIGITS] decimal ([POKER_RADIX]) digits.
+///
+/// Each byte gives one digit `b % r`, discarding bytes of at least `r * (256 / r)` so
+/// that digits are uniform, and any trailing digits that do not form a full hand are
+/// discarded. This requires 2 <= `radix` <= 256 and 2 <= `digits_per_hand` <=
+/// [POKER_MAX_DIGITS], returning [Error::InvalidParameter] otherwise, and sequences too
+/// short for two categories return [Error::InsufficientSampleSize] with the number of
+/// bytes.
+///
+/// Unlike [chi_square_bytes](crate::helpers::chi_square_bytes), which tests the
+/// frequency of each value, this tests the distribution of values within groups.
+pub fn poker_test(data: &[u8], digits_per_hand: usize, radix: usize) -> Result<f32, Error> {
+    poker_test_result(data, digits_per_hand, radix)?.check()
+}
+
+/// Poker (Partition) Test at significance level `alpha`
+///
+/// See [poker_test] for details
+pub fn poker_test_with_alpha(
+    data: &[u8],
+    digits_per_hand: usize,
+    radix: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    poker_test_result(data, digits_per_hand, radix)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// Poker (Partition) Test returning the [TestResult], with statistic x^2
+///
+/// See [poker_test] for details
+pub fn poker_test_result(
+    data: &[u8],
+    digits_per_hand: usize,
+    radix: usize,
+) -> Result<TestResult, Error> {
+    let k = digits_per_hand;
+
+    // Check hand size and radix are supported
+    if !(2..=POKER_MAX_DIGITS).contains(&k) || !(2..=256).contains(&radix) {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Count hands by the number of distinct digits, from uniform digits
+    let limit = radix * (256 / radix);
+    let mut digits = data
+        .iter()
+        .map(|b| *b as usize)
+        .filter(|b| *b < limit)
+        .map(|b| b % radix);
+
+    let mut n = 0;
+    let mut counts = [0usize; POKER_MAX_DIGITS + 1];
+
+    'outer: loop {
+        let mut seen = [0u64; 4];
+        for _ in 0..k {
+            match digits.next() {
+                Some(d) => seen[d / 64] |= 1 << (d % 64),
+                None => break 'outer,
+            }
+        }
+
+        let s = seen.iter().map(|w| w.count_ones()).sum::<u32>();
+        counts[s as usize] += 1;
+        n += 1;
+    }
+
+    // Merge categories from the fewest distinct digits, folding any remainder into the
+    // last category
+    let probs = poker_probs(k, radix);
+    let mut buckets = [(0usize, 0f64); POKER_MAX_DIGITS];
+    let mut num_buckets = 0;
+    let (mut c, mut p) = (0, 0.0);
+
+    for (count, prob) in counts
+        .iter()
+        .zip(probs.iter())
+        .take(k.min(radix) + 1)
+        .skip(1)
+    {
+        c += count;
+        p += prob;
+
+        if n as f64 * p >= 5.0 {
+            buckets[num_buckets] = (c, p);
+            num_buckets += 1;
+            (c, p) = (0, 0.0);
+        }
+    }
+
+    if num_buckets > 0 {
+        buckets[num_buckets - 1].0 += c;
+        buckets[num_buckets - 1].1 += p;
+    }
+
+    // Check sample size meets minimum requirements
+    if num_buckets < 2 {
+        return Err(Error::InsufficientSampleSize(data.len()));
+    }
+
+    // Compute x^2
+    let mut x2 = 0.0;
+    for (c, p) in &buckets[..num_buckets] {
+        let e = n as f64 * p;
+        x2 += (*c as f64 - e) * (*c as f64 - e) / e;
+    }
+
+    let x2 = x2 as f32;
+    let p = chi_square_p(num_buckets - 1, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Compute the probability of each number of distinct digits in a hand of k base-r digits
+fn poker_probs(k: usize, r: usize) -> [f64; POKER_MAX_DIGITS + 1] {
+    // Stirling numbers of the second kind S(k, s), by row
+    let mut stirling = [0f64; POKER_MAX_DIGITS + 1];
+    stirling[0] = 1.0;
+    for i in 1..=k {
+        for j in (1..=i).rev() {
+            stirling[j] = j as f64 * stirling[j] + stirling[j - 1];
+        }
+        stirling[0] = 0.0;
+    }
+
+    // Scale by the falling factorial r (r - 1) ... (r - s + 1) / r^k
+    let mut probs = [0f64; POKER_MAX_DIGITS + 1];
+    let mut falling = 1.0;
+    let total = math::pow(r as f64, k as f64);
+    for s in 1..=k.min(r) {
+        falling *= (r - s + 1) as f64;
+        probs[s] = stirling[s] * falling / total;
+    }
+
+    probs
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::{vec, vec::Vec};
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn poker_probs_sum() {
+        // Knuth's probabilities for five decimal digits
+        let p = poker_probs(5, 10);
+        for (a, b) in p[1..=5].iter().zip([0.0001, 0.0135, 0.18, 0.504, 0.3024]) {
+            assert_approx_eq!(*a, b, 1e-12);
+        }
+
+        for (k, r) in [(2, 2), (5, 10), (16, 3), (16, 256)] {
+            assert_approx_eq!(poker_probs(k, r).iter().sum::<f64>(), 1.0, 1e-9);
+        }
+    }
+
+    #[test]
+    fn poker_test_ok() {
+        let mut buff = vec![0u8; 20000];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        for (k, r) in [(POKER_DIGITS, POKER_RADIX), (4, 16), (3, 256)] {
+            poker_test(&buff, k, r).expect("Poker test failed");
+        }
+    }
+
+    #[test]
+    fn poker_test_vector() {
+        let mut buff = vec![0u8; 20000];
+        lcg_fill(&mut buff, 31);
+
+        // Check statistics and p values match reference
+        let r = poker_test_result(&buff, POKER_DIGITS, POKER_RADIX).unwrap();
+        assert_approx_eq!(r.statistic, 0.407268, 1e-3);
+        assert_approx_eq!(r.p_value, 0.938737, 1e-4);
+
+        let r = poker_test_result(&buff, 4, 16).unwrap();
+        assert_approx_eq!(r.statistic, 1.833867, 1e-3);
+        assert_approx_eq!(r.p_value, 0.399743, 1e-4);
+
+        let r = poker_test_result(&buff, 3, 256).unwrap();
+        assert_approx_eq!(r.statistic, 0.652115, 1e-3);
+        assert_approx_eq!(r.p_value, 0.419358, 1e-4);
+    }
+
+    #[test]
+    fn poker_test_fail() {
+        // Uniform digit frequencies, but repeating within each hand
+        let buff: Vec<u8> = (0..20000).map(|i| ((i / 2) % 10) as u8).collect();
+        poker_test(&buff, POKER_DIGITS, POKER_RADIX).expect_err("Poker test p > threshold");
+
+        // Uniform digit frequencies, but always distinct within each hand
+        let buff: Vec<u8> = (0..20000).map(|i| (i % 10) as u8).collect();
+        poker_test(&buff, POKER_DIGITS, POKER_RADIX).expect_err("Poker test p > threshold");
+    }
+
+    #[test]
+    fn poker_test_invalid() {
+        let buff = [0u8; 1000];
+
+        for (k, r) in [(1, 10), (17, 10), (5, 1), (5, 257)] {
+            assert_eq!(poker_test(&buff, k, r), Err(Error::InvalidParameter));
+        }
+
+        assert_eq!(
+            poker_test(&[], POKER_DIGITS, POKER_RADIX),
+            Err(Error::InsufficientSampleSize(0))
+        );
+        assert_eq!(
+            poker_test(&buff[..20], POKER_DIGITS, POKER_RADIX),
+            Err(Error::InsufficientSampleSize(20))
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Diehard Runs (Up and Down) Test
+
+use super::chi_square_p;
+use crate::{Error, TestResult};
+
+/// Minimum number of values for the runs up and down test
+pub const RUNS_UP_DOWN_MIN_VALUES: usize = 4000;
+
+/// Covariance weights for run lengths 1 to 5 and >= 6, from Knuth
+const A: [[f64; 6]; 6] = [
+    [4529.4, 9044.9, 13568.0, 18091.0, 22615.0, 27892.0],
+    [9044.9, 18097.0, 27139.0, 36187.0, 45234.0, 55789.0],
+    [13568.0, 27139.0, 40721.0, 54281.0, 67852.0, 83685.0],
+    [18091.0, 36187.0, 54281.0, 72414.0, 90470.0, 111580.0],
+    [22615.0, 45234.0, 67852.0, 90470.0, 113262.0, 139476.0],
+    [27892.0, 55789.0, 83685.0, 111580.0, 139476.0, 172860.0],
+];
+
+/// Expected runs per value for run lengths 1 to 5 and >= 6
+const B: [f64; 6] = [
+    1.0 / 6.0,
+    5.0 / 24.0,
+    11.0 / 120.0,
+    19.0 / 720.0,
+    29.0 / 5040.0,
+    1.0 / 840.0,
+];
+
+/// Diehard Runs (Up and Down) Test over an iterator of N uniform values
+///
+/// This counts the lengths of maximal ascending (up) and descending (down) runs, with
+/// lengths of 6 or more merged, and as adjacent runs are not independent applies the
+/// covariance matrix from Knuth (TAOCP Vol. 2, 3.3.2 G) to give a statistic V with 6
+/// degrees of freedom for each direction. This returns both P-values `(p_up, p_down)`,
+/// failing where either is below the threshold. Equal values end both runs.
+///
+/// Diehard uses 10,000 values per test, this requires at least
+/// [RUNS_UP_DOWN_MIN_VALUES] values returning [Error::InsufficientSampleSize] otherwise.
+///
+/// See [uniform_floats](super::uniform_floats) for use with bit streams
+pub fn runs_up_down(values: impl Iterator<Item = f32>) -> Result<(f32, f32), Error> {
+    let (up, down) = runs_up_down_result(values)?;
+
+    Ok((up.check()?, down.check()?))
+}
+
+/// Diehard Runs (Up and Down) Test at significance level `alpha`
+///
+/// See [runs_up_down] for details
+pub fn runs_up_down_with_alpha(
+    values: impl Iterator<Item = f32>,
+    alpha: f32,
+) -> Result<(f32, f32), Error> {
+    let (up, down) = runs_up_down_result(values)?;
+
+    Ok((up.at_alpha(alpha)?.check()?, down.at_alpha(alpha)?.check()?))
+}
+
+/// Diehard Runs (Up and Down) Test returning both [TestResult]s, with statistics V
+///
+/// See [runs_up_down] for details
+pub fn runs_up_down_result(
+    mut values: impl Iterator<Item = f32>,
+) -> Result<(TestResult, TestResult), Error> {
+    let mut prev = match values.next() {
+        Some(v) => v,
+        None => return Err(Error::InsufficientSampleSize(0)),
+    };
+
+    let mut n = 1;
+    let (mut up, mut down) = ([0usize; 6], [0usize; 6]);
+    let (mut up_len, mut down_len) = (1, 1);
+
+    for v in values {
+        // Extend or end the current runs
+        if v > prev {
+            up_len += 1;
+        } else {
+            up[up_len.min(6) - 1] += 1;
+            up_len = 1;
+        }
+
+        if v < prev {
+            down_len += 1;
+        } else {
+            down[down_len.min(6) - 1] += 1;
+            down_len = 1;
+        }
+
+        prev = v;
+        n += 1;
+    }
+
+    // Record the final runs
+    up[up_len.min(6) - 1] += 1;
+    down[down_len.min(6) - 1] += 1;
+
+    // Check sample size meets minimum requirements
+    if n < RUNS_UP_DOWN_MIN_VALUES {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    Ok((runs_result(&up, n), runs_result(&down, n)))
+}
+
+/// Compute the result for run length counts over n values
+fn runs_result(counts: &[usize; 6], n: usize) -> TestResult {
+    // Compute deviations from the expected counts
+    let mut d = [0.0; 6];
+    for ((d, c), b) in d.iter_mut().zip(counts).zip(B) {
+        *d = *c as f64 - n as f64 * b;
+    }
+
+    // Compute V
+    let mut v = 0.0;
+    for (i, row) in A.iter().enumerate() {
+        for (j, a) in row.iter().enumerate() {
+            v += d[i] * d[j] * a;
+        }
+    }
+    let v = (v / (n - 6) as f64) as f32;
+
+    // Compute p
+    let p = chi_square_p(6, v);
+
+    TestResult::new(p, v)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{diehard::uniform_floats, helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for Diehard's 10,000 values
+    const LEN: usize = 10_000 * 4;
+
+    #[test]
+    fn runs_up_down_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        runs_up_down(uniform_floats(BitIter::new(&buff))).expect("Runs up and down test failed");
+    }
+
+    #[test]
+    fn runs_up_down_vector() {
+        let mut buff = vec![0u8; LEN];
+        lcg_fill(&mut buff, 23);
+
+        // Check statistics and p values match reference
+        let (up, down) = runs_up_down_result(uniform_floats(BitIter::new(&buff))).unwrap();
+        assert_approx_eq!(up.statistic, 5.073735, 1e-3);
+        assert_approx_eq!(up.p_value, 0.534392, 1e-4);
+        assert_approx_eq!(down.statistic, 5.493883, 1e-3);
+        assert_approx_eq!(down.p_value, 0.482196, 1e-4);
+    }
+
+    #[test]
+    fn runs_up_down_fail() {
+        // Sawtooth values, ascending runs of length 4
+        let values = (0..10_000).map(|i| (i % 4) as f32 / 4.0);
+        runs_up_down(values).expect_err("Runs up and down p > threshold");
+
+        // Alternating values, with only runs of length 2 in either direction
+        let values = (0..10_000).map(|i| (i % 2) as f32);
+        runs_up_down(values).expect_err("Runs up and down p > threshold");
+    }
+
+    #[test]
+    fn runs_up_down_invalid() {
+        assert_eq!(
+            runs_up_down(core::iter::empty()),
+            Err(Error::InsufficientSampleSize(0))
+        );
+        assert_eq!(
+            runs_up_down((0..3999).map(|i| i as f32)),
+            Err(Error::InsufficientSampleSize(3999))
+        );
+    }
+}
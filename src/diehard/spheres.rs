@@ -0,0 +1,162 @@
+//! Diehard 3D Spheres Test
+
+use super::minimum_distance::min_distance_sq;
+use super::{chi_square_p, next_word};
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Points per trial for the 3D spheres test
+pub const SPHERES_N: usize = 4000;
+
+/// Minimum number of trials, giving an expected count of 5 in each interval
+pub const SPHERES_MIN_TRIALS: usize = 50;
+
+/// Side length of the cube
+const SIDE: f64 = 1000.0;
+
+/// Diehard 3D Spheres Test over an iterator of N bits
+///
+/// Each trial places [SPHERES_N] random points in a 1000 x 1000 x 1000 cube and finds
+/// the minimum distance r between any pair of points. For random points r^3 is close to
+/// exponential with mean `3 * volume / (2π n (n - 1))` (about 30), so
+/// `1 - exp(-r^3 / mean)` is uniform over [0, 1), with these values for all complete
+/// trials bucketed into ten equal intervals and compared against a uniform distribution
+/// using x^2 as for [minimum_distance](super::minimum_distance).
+///
+/// Each coordinate is read from a 32-bit word w as `x = 1000 * w / 2^32`, three words
+/// per point in (x, y, z) order, so each trial consumes 96 * [SPHERES_N] bits. Diehard
+/// uses 20 trials, this requires at least [SPHERES_MIN_TRIALS] trials returning
+/// [Error::InsufficientSampleSize] otherwise. As this is `no_std` and does not allocate,
+/// the points (48 KiB) are held on the stack.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn spheres_3d(data: impl Iterator<Item = bool>) -> Result<f32, Error> {
+    spheres_3d_result(data)?.check()
+}
+
+/// Diehard 3D Spheres Test at significance level `alpha`
+///
+/// See [spheres_3d] for details
+pub fn spheres_3d_with_alpha(data: impl Iterator<Item = bool>, alpha: f32) -> Result<f32, Error> {
+    spheres_3d_result(data)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard 3D Spheres Test returning the [TestResult], with statistic x^2
+///
+/// See [spheres_3d] for details
+pub fn spheres_3d_result(mut data: impl Iterator<Item = bool>) -> Result<TestResult, Error> {
+    let n = SPHERES_N;
+
+    let mut trials = 0;
+    let mut counts = [0usize; 10];
+    let mut points = [[0u32; 3]; SPHERES_N];
+
+    // Scale from r^3 to the exponential rate, n(n - 1) (4/3)π / (2 * volume)
+    let rate = (n * (n - 1)) as f64 * 2.0 * core::f64::consts::PI / (3.0 * SIDE * SIDE * SIDE);
+
+    'outer: loop {
+        // Place points, discarding incomplete trials
+        for p in points.iter_mut() {
+            for c in p.iter_mut() {
+                match next_word(&mut data, 32) {
+                    Some(v) => *c = v,
+                    None => break 'outer,
+                }
+            }
+        }
+
+        // Bucket uniform value, with 1.0 in the last interval
+        let r2 = min_distance_sq(&mut points, SIDE);
+        let u = 1.0 - math::exp(-rate * r2 * math::sqrt(r2));
+        counts[((u * 10.0) as usize).min(9)] += 1;
+
+        trials += 1;
+    }
+
+    // Check we have enough trials
+    if trials < SPHERES_MIN_TRIALS {
+        return Err(Error::InsufficientSampleSize(trials * n * 96));
+    }
+
+    // Compute x^2 over intervals
+    let e = trials as f32 / 10.0;
+    let mut x2 = 0.0;
+    for v in counts {
+        x2 += math::powf(v as f32 - e, 2.0) / e;
+    }
+
+    // Compute p
+    let p = chi_square_p(9, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for the minimum number of trials
+    const LEN: usize = SPHERES_MIN_TRIALS * SPHERES_N * 12;
+
+    #[test]
+    fn spheres_3d_ok() {
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        spheres_3d(BitIter::new(&buff)).expect("3D spheres test failed");
+    }
+
+    #[test]
+    fn spheres_3d_vector() {
+        let mut buff = vec![0u8; LEN];
+        lcg_fill(&mut buff, 53);
+
+        // Check statistic and p value match reference (counts [3, 6, 5, 1, 3, 8, 7, 4, 8, 5])
+        let r = spheres_3d_result(BitIter::new(&buff)).unwrap();
+        assert_approx_eq!(r.statistic, 9.6, 1e-4);
+        assert_approx_eq!(r.p_value, 0.383827, 1e-4);
+    }
+
+    #[test]
+    fn spheres_3d_fail() {
+        // Coordinates on a coarse lattice, with many coincident points
+        let mut buff = vec![0u8; LEN];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        for w in buff.chunks_exact_mut(4) {
+            w[..3].fill(0);
+        }
+
+        spheres_3d(BitIter::new(&buff)).expect_err("3D spheres p > threshold");
+    }
+
+    #[test]
+    fn spheres_3d_insufficient() {
+        let buff = vec![0u8; LEN - 1];
+        assert_eq!(
+            spheres_3d(BitIter::new(&buff)),
+            Err(Error::InsufficientSampleSize(49 * SPHERES_N * 96))
+        );
+    }
+
+    #[test]
+    fn spheres_3d_points() {
+        // Closest pair differs in all three coordinates
+        let s = (1u64 << 32) as f64 / SIDE;
+        let mut points = [
+            (0.0, 0.0, 0.0),
+            (1.0, 5.0, 0.0),
+            (2.0, 1.0, 1.0),
+            (2.5, 1.5, 2.0),
+        ]
+        .map(|(x, y, z)| [(x * s) as u32, (y * s) as u32, (z * s) as u32]);
+
+        assert_approx_eq!(min_distance_sq(&mut points, SIDE), 1.5, 1e-5);
+    }
+}
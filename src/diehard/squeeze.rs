@@ -0,0 +1,171 @@
+//! Diehard Squeeze Test
+
+use super::{chi_square_p, next_word};
+use crate::{Error, TestResult};
+
+/// Default number of trials for the squeeze test
+pub const SQUEEZE_TRIALS: usize = 100_000;
+
+/// Starting value of k
+const START: u64 = 1 << 31;
+
+/// Iterations in the first (merged) category
+const MIN_ITERATIONS: usize = 6;
+
+/// Iterations in the last (merged) category, at which a trial is stopped
+const MAX_ITERATIONS: usize = 48;
+
+/// Probability of each iteration count from <= 6 to >= 48
+pub const SQUEEZE_PROBS: [f64; MAX_ITERATIONS - MIN_ITERATIONS + 1] = [
+    0.00002103, 0.00005779, 0.00017554, 0.00046732, 0.00110783, 0.00236784, 0.00460945, 0.00824117,
+    0.01362782, 0.02096850, 0.03017613, 0.04080198, 0.05204204, 0.06283829, 0.07205638, 0.07869451,
+    0.08206756, 0.08191935, 0.07844008, 0.07219411, 0.06398678, 0.05470930, 0.04519851, 0.03613660,
+    0.02800027, 0.02105567, 0.01538652, 0.01094020, 0.00757796, 0.00511956, 0.00337726, 0.00217786,
+    0.00137439, 0.00084970, 0.00051518, 0.00030666, 0.00017939, 0.00010324, 0.00005851, 0.00003269,
+    0.00001803, 0.00000982, 0.00001121,
+];
+
+/// Diehard Squeeze Test over an iterator of N bits with `n` trials
+///
+/// Each trial starts from k = 2^31 and repeatedly sets k = ceil(k * U) for uniform U over
+/// (0, 1] until k reaches 1, counting the number of iterations j. Iteration counts over
+/// all trials are placed into categories from j <= 6 to j >= 48 and compared against
+/// [SQUEEZE_PROBS] using x^2. Trials are stopped at 48 iterations, so degenerate data
+/// cannot stall the test.
+///
+/// Each U is read from a 32-bit word w as `(w + 1) / 2^32`, so each trial consumes a
+/// variable number of bits, on average ~23 words. Diehard uses [SQUEEZE_TRIALS] trials
+/// or ~9 MiB, returning [Error::InsufficientSampleSize] where the data ends before `n`
+/// trials are complete.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers, or
+/// [BitsFromRng](crate::helpers::BitsFromRng) to test an RNG directly
+pub fn squeeze(data: impl Iterator<Item = bool>, n: usize) -> Result<f32, Error> {
+    squeeze_result(data, n)?.check()
+}
+
+/// Diehard Squeeze Test at significance level `alpha`
+///
+/// See [squeeze] for details
+pub fn squeeze_with_alpha(
+    data: impl Iterator<Item = bool>,
+    n: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    squeeze_result(data, n)?.at_alpha(alpha)?.check()
+}
+
+/// Diehard Squeeze Test returning the [TestResult], with statistic x^2
+///
+/// See [squeeze] for details
+pub fn squeeze_result(mut data: impl Iterator<Item = bool>, n: usize) -> Result<TestResult, Error> {
+    // Check we have at least one trial
+    if n == 0 {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut bits = 0;
+    let mut counts = [0usize; SQUEEZE_PROBS.len()];
+
+    for _ in 0..n {
+        // Squeeze k towards 1, with ceil(k * (w + 1) / 2^32) in fixed point
+        let mut k = START;
+        let mut j = 0;
+        while k != 1 && j < MAX_ITERATIONS {
+            let w = match next_word(&mut data, 32) {
+                Some(w) => w as u64,
+                None => return Err(Error::InsufficientSampleSize(bits)),
+            };
+            bits += 32;
+
+            k = (k * (w + 1)).div_ceil(1 << 32);
+            j += 1;
+        }
+
+        counts[j.max(MIN_ITERATIONS) - MIN_ITERATIONS] += 1;
+    }
+
+    // Compute x^2 over categories
+    let t = n as f64;
+    let mut x2 = 0.0;
+    for (c, p) in counts.iter().zip(SQUEEZE_PROBS) {
+        let d = *c as f64 - t * p;
+        x2 += d * d / (t * p);
+    }
+
+    // Compute p
+    let x2 = x2 as f32;
+    let p = chi_square_p(SQUEEZE_PROBS.len() - 1, x2);
+
+    Ok(TestResult::new(p, x2))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    /// Bytes for n trials at the maximum number of iterations
+    const fn len(n: usize) -> usize {
+        n * MAX_ITERATIONS * 4
+    }
+
+    #[test]
+    fn squeeze_ok() {
+        let mut buff = vec![0u8; len(20_000)];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        squeeze(BitIter::new(&buff), 20_000).expect("Squeeze test failed");
+    }
+
+    #[test]
+    fn squeeze_vector() {
+        let mut buff = vec![0u8; len(10_000)];
+        lcg_fill(&mut buff, 19);
+
+        // Check statistic and p value match reference
+        let r = squeeze_result(BitIter::new(&buff), 10_000).unwrap();
+        assert_approx_eq!(r.statistic, 48.77054, 1e-3);
+        assert_approx_eq!(r.p_value, 0.219381, 1e-4);
+    }
+
+    #[test]
+    fn squeeze_fail() {
+        // Uniforms limited to (0, 0.5], squeezing k too quickly
+        let mut buff = vec![0u8; len(10_000)];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        for b in buff.iter_mut().skip(3).step_by(4) {
+            *b &= 0x7f;
+        }
+
+        squeeze(BitIter::new(&buff), 10_000).expect_err("Squeeze p > threshold");
+    }
+
+    #[test]
+    fn squeeze_invalid() {
+        let buff = [0xffu8; 64];
+
+        assert_eq!(
+            squeeze(BitIter::new(&buff), 0),
+            Err(Error::InvalidParameter)
+        );
+
+        // U = 1 leaves k unchanged, requiring the maximum iterations
+        assert_eq!(
+            squeeze(BitIter::new(&buff), 1),
+            Err(Error::InsufficientSampleSize(16 * 32))
+        );
+    }
+
+    #[test]
+    fn squeeze_probs() {
+        let sum: f64 = SQUEEZE_PROBS.iter().sum();
+        assert_approx_eq!(sum, 1.0, 1e-6);
+    }
+}
@@ -0,0 +1,106 @@
+//! FIPS 140-2 tests
+
+use crate::Error;
+
+/// FIPS 140-2 §4.9.2 continuous random number generator test over `N` byte blocks,
+/// defaulting to 32-bit blocks
+///
+/// This is a stateful online check, the first block is retained for comparison (and must
+/// be discarded by the caller) and each subsequent block must differ from its immediate
+/// predecessor. As blocks are stored internally `N` is specified in bytes.
+pub struct ContinuousRngTest<const N: usize = 4> {
+    prev: [u8; N],
+    primed: bool,
+}
+
+impl<const N: usize> Default for ContinuousRngTest<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ContinuousRngTest<N> {
+    /// Create a new continuous test, with no previous block
+    pub const fn new() -> Self {
+        Self {
+            prev: [0; N],
+            primed: false,
+        }
+    }
+
+    /// Check a block against the previous block, returning [Error::RepeatedBlock] on
+    /// a match or [Error::InvalidParameter] where `block` is not `N` bytes long
+    ///
+    /// The first block after creation or [ContinuousRngTest::reset] is always accepted.
+    pub fn check_block(&mut self, block: &[u8]) -> Result<(), Error> {
+        // Check block length
+        if block.len() != N {
+            return Err(Error::InvalidParameter);
+        }
+
+        // Compare against the previous block where available
+        let repeated = self.primed && self.prev[..] == block[..];
+
+        self.prev.copy_from_slice(block);
+        self.primed = true;
+
+        match repeated {
+            true => Err(Error::RepeatedBlock),
+            false => Ok(()),
+        }
+    }
+
+    /// Reset the test, discarding the previous block
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn continuous_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut t = ContinuousRngTest::<16>::new();
+
+        for _ in 0..1000 {
+            let mut block = [0u8; 16];
+            rng.fill_bytes(&mut block);
+
+            t.check_block(&block).expect("Continuous test failed");
+        }
+    }
+
+    #[test]
+    fn continuous_repeated() {
+        let mut t: ContinuousRngTest = ContinuousRngTest::new();
+
+        // First block is always accepted
+        assert_eq!(t.check_block(&[1, 2, 3, 4]), Ok(()));
+        assert_eq!(t.check_block(&[1, 2, 3, 5]), Ok(()));
+        assert_eq!(t.check_block(&[1, 2, 3, 5]), Err(Error::RepeatedBlock));
+
+        // Non-consecutive repeats are accepted
+        assert_eq!(t.check_block(&[1, 2, 3, 4]), Ok(()));
+        assert_eq!(t.check_block(&[1, 2, 3, 5]), Ok(()));
+
+        // Reset discards the previous block
+        t.reset();
+        assert_eq!(t.check_block(&[1, 2, 3, 5]), Ok(()));
+    }
+
+    #[test]
+    fn continuous_invalid() {
+        let mut t: ContinuousRngTest = ContinuousRngTest::new();
+
+        assert_eq!(t.check_block(&[1, 2, 3]), Err(Error::InvalidParameter));
+        assert_eq!(
+            t.check_block(&[1, 2, 3, 4, 5]),
+            Err(Error::InvalidParameter)
+        );
+    }
+}
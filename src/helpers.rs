@@ -1,14 +1,128 @@
+use crate::math;
+use core::marker::PhantomData;
+
+use crate::{nist::igamma_upper, Error, TestResult};
+
+/// Order of bits within each byte for [BitIter]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum BitOrder {
+    /// Least significant bit first
+    #[default]
+    Lsb,
+    /// Most significant bit first, as used by SP 800-22 test vectors
+    Msb,
+}
+
+/// Order of bytes within each word for [WordsFromBits]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ByteOrder {
+    /// First byte lowest
+    #[default]
+    Little,
+    /// First byte highest
+    Big,
+}
+
+/// Packing of bits into words for [WordsFromBits]
+///
+/// Words are assembled from bytes of 8 consecutive bits (the last possibly shorter), with
+/// the first bit of each byte lowest or highest per `bit_order`, and the first byte of
+/// each word lowest or highest per `byte_order`.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct BitPacking {
+    /// Order of bits within each byte
+    pub bit_order: BitOrder,
+    /// Order of bytes within each word
+    pub byte_order: ByteOrder,
+}
+
+impl BitPacking {
+    /// Create a new packing from bit and byte orders
+    pub const fn new(bit_order: BitOrder, byte_order: ByteOrder) -> Self {
+        Self {
+            bit_order,
+            byte_order,
+        }
+    }
+}
+
+impl From<BitOrder> for BitPacking {
+    /// Pack with the first bit lowest ([BitOrder::Lsb], [ByteOrder::Little]) or highest
+    /// ([BitOrder::Msb], [ByteOrder::Big])
+    fn from(order: BitOrder) -> Self {
+        match order {
+            BitOrder::Lsb => Self::new(BitOrder::Lsb, ByteOrder::Little),
+            BitOrder::Msb => Self::new(BitOrder::Msb, ByteOrder::Big),
+        }
+    }
+}
+
 /// Helper for bit-wise iteration through slices
+///
+/// This iterates from the front at byte `i` bit `j`, and from the back (see
+/// [DoubleEndedIterator]) before byte `k` bit `l`.
 pub struct BitIter<B: AsRef<[u8]>> {
     buff: B,
     i: usize,
     j: usize,
+    k: usize,
+    l: usize,
+    order: BitOrder,
 }
 
 impl<B: AsRef<[u8]>> BitIter<B> {
-    /// Create a new [BitIter] over the provided buffer
+    /// Create a new [BitIter] over the provided buffer, LSB first
     pub fn new(buff: B) -> Self {
-        Self { buff, i: 0, j: 0 }
+        Self::with_order(buff, BitOrder::Lsb)
+    }
+
+    /// Create a new [BitIter] over the provided buffer, MSB first
+    pub fn new_msb(buff: B) -> Self {
+        Self::with_order(buff, BitOrder::Msb)
+    }
+
+    /// Create a new [BitIter] over the provided buffer with the specified [BitOrder]
+    pub fn with_order(buff: B, order: BitOrder) -> Self {
+        let k = buff.as_ref().len();
+
+        Self {
+            buff,
+            i: 0,
+            j: 0,
+            k,
+            l: 0,
+            order,
+        }
+    }
+
+    /// Create a new [BitIter] over the provided buffer, LSB first, starting `offset` bits
+    /// in
+    ///
+    /// This skips headers without copying or realigning the buffer, offsets past the end
+    /// of the buffer give an empty iterator.
+    pub fn new_with_offset(buff: B, offset: usize) -> Self {
+        let mut s = Self::new(buff);
+
+        let offset = offset.min(s.k * 8);
+        s.i = offset / 8;
+        s.j = offset % 8;
+
+        s
+    }
+
+    /// Fetch the bit at byte `i` bit `j` using the configured order
+    fn bit(&self, i: usize, j: usize) -> bool {
+        let shift = match self.order {
+            BitOrder::Lsb => j,
+            BitOrder::Msb => 7 - j,
+        };
+
+        self.buff.as_ref()[i] & (1 << shift) != 0
+    }
+
+    /// Check whether the front and back positions have met
+    fn done(&self) -> bool {
+        self.i * 8 + self.j >= self.k * 8 + self.l
     }
 }
 
@@ -24,15 +138,13 @@ impl<B: AsRef<[u8]>> Iterator for BitIter<B> {
     type Item = bool;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let d = self.buff.as_ref();
-
         // Check for remaining data
-        if self.i >= d.len() {
+        if self.done() {
             return None;
         }
 
         // Fetch current bit value
-        let v = d[self.i] & (1 << self.j) != 0;
+        let v = self.bit(self.i, self.j);
 
         // Increment index
         if self.j < 7 {
@@ -45,61 +157,1739 @@ impl<B: AsRef<[u8]>> Iterator for BitIter<B> {
         // Return value
         Some(v)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rem = self.len();
+        (rem, Some(rem))
+    }
+}
+
+/// Exact remaining length for [BitIter], as required by some tests
+impl<B: AsRef<[u8]>> ExactSizeIterator for BitIter<B> {
+    fn len(&self) -> usize {
+        (self.k * 8 + self.l).saturating_sub(self.i * 8 + self.j)
+    }
+}
+
+/// Reverse bit-wise iteration for [BitIter], without buffering
+impl<B: AsRef<[u8]>> DoubleEndedIterator for BitIter<B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // Check for remaining data
+        if self.done() {
+            return None;
+        }
+
+        // Decrement index
+        if self.l > 0 {
+            self.l -= 1;
+        } else {
+            self.k -= 1;
+            self.l = 7;
+        }
+
+        // Return value
+        Some(self.bit(self.k, self.l))
+    }
+}
+
+/// Words accepted by [BitIterWords]
+pub trait Word: Copy {
+    /// Width of the word in bits
+    const BITS: usize;
+
+    /// Fetch bit `i`, where bit 0 is the least significant
+    fn bit(self, i: usize) -> bool;
+}
+
+macro_rules! impl_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                const BITS: usize = <$t>::BITS as usize;
+
+                fn bit(self, i: usize) -> bool {
+                    self & (1 << i) != 0
+                }
+            }
+        )*
+    };
+}
+
+impl_word!(u32, u64);
+
+/// Helper for bit-wise iteration through slices of `u32` or `u64` words
+///
+/// This reads bits directly from each word in the specified [BitOrder], without first
+/// converting to bytes. With [BitOrder::Lsb] bit 0 (the least significant) of each word
+/// is first, matching [BitIter::new] over the little-endian bytes of each word, and with
+/// [BitOrder::Msb] the most significant bit is first, matching [BitIter::new_msb] over the
+/// big-endian bytes.
+pub struct BitIterWords<B: AsRef<[W]>, W: Word> {
+    buff: B,
+    front: usize,
+    back: usize,
+    order: BitOrder,
+    _word: PhantomData<W>,
+}
+
+impl<B: AsRef<[W]>, W: Word> BitIterWords<B, W> {
+    /// Create a new [BitIterWords] over the provided words, LSB first
+    pub fn new(buff: B) -> Self {
+        Self::with_order(buff, BitOrder::Lsb)
+    }
+
+    /// Create a new [BitIterWords] over the provided words, MSB first
+    pub fn new_msb(buff: B) -> Self {
+        Self::with_order(buff, BitOrder::Msb)
+    }
+
+    /// Create a new [BitIterWords] over the provided words with the specified [BitOrder]
+    pub fn with_order(buff: B, order: BitOrder) -> Self {
+        let back = buff.as_ref().len() * W::BITS;
+
+        Self {
+            buff,
+            front: 0,
+            back,
+            order,
+            _word: PhantomData,
+        }
+    }
+
+    /// Fetch the bit at (absolute) position `n` using the configured order
+    fn bit(&self, n: usize) -> bool {
+        let (i, j) = (n / W::BITS, n % W::BITS);
+        let shift = match self.order {
+            BitOrder::Lsb => j,
+            BitOrder::Msb => W::BITS - 1 - j,
+        };
+
+        self.buff.as_ref()[i].bit(shift)
+    }
+}
+
+impl<B: AsRef<[W]>, W: Word> From<B> for BitIterWords<B, W> {
+    /// Convert a type `B: AsRef<[W]>` into a `BitIterWords<B, W>`
+    fn from(value: B) -> Self {
+        BitIterWords::new(value)
+    }
+}
+
+/// Bit-wise [Iterator] implementation for [BitIterWords]
+impl<B: AsRef<[W]>, W: Word> Iterator for BitIterWords<B, W> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        let v = self.bit(self.front);
+        self.front += 1;
+
+        Some(v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rem = self.len();
+        (rem, Some(rem))
+    }
+}
+
+/// Exact remaining length for [BitIterWords], as required by some tests
+impl<B: AsRef<[W]>, W: Word> ExactSizeIterator for BitIterWords<B, W> {
+    fn len(&self) -> usize {
+        self.back - self.front
+    }
+}
+
+/// Reverse bit-wise iteration for [BitIterWords]
+impl<B: AsRef<[W]>, W: Word> DoubleEndedIterator for BitIterWords<B, W> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front >= self.back {
+            return None;
+        }
+
+        self.back -= 1;
+
+        Some(self.bit(self.back))
+    }
+}
+
+/// Sources of bits accepted by the `nist_*` tests
+///
+/// This is implemented for any [IntoIterator] over [IntoBit] items, covering iterators
+/// of `bool` (passed through unchanged), `&[bool]` and `[bool; N]`, and with the
+/// `bitvec` feature `&BitSlice` and `&BitVec`.
+pub trait AsBits {
+    /// Iterator over the bits
+    type Bits: Iterator<Item = bool>;
+
+    /// Convert into an iterator over the bits
+    fn into_bits(self) -> Self::Bits;
+}
+
+impl<I: IntoIterator> AsBits for I
+where
+    I::Item: IntoBit,
+{
+    type Bits = Bits<I::IntoIter>;
+
+    fn into_bits(self) -> Self::Bits {
+        Bits(self.into_iter())
+    }
+}
+
+/// Single bit values, see [AsBits]
+pub trait IntoBit {
+    /// Fetch the value of the bit
+    fn into_bit(self) -> bool;
+}
+
+impl IntoBit for bool {
+    #[inline]
+    fn into_bit(self) -> bool {
+        self
+    }
+}
+
+impl IntoBit for &bool {
+    #[inline]
+    fn into_bit(self) -> bool {
+        *self
+    }
+}
+
+#[cfg(feature = "bitvec")]
+impl<M, T, O> IntoBit for bitvec::ptr::BitRef<'_, M, T, O>
+where
+    M: bitvec::ptr::Mutability,
+    T: bitvec::store::BitStore,
+    O: bitvec::order::BitOrder,
+{
+    #[inline]
+    fn into_bit(self) -> bool {
+        *self
+    }
+}
+
+/// Iterator over the bits of an [AsBits] source
+#[derive(Clone, Debug)]
+pub struct Bits<I>(I);
+
+impl<I: Iterator> Iterator for Bits<I>
+where
+    I::Item: IntoBit,
+{
+    type Item = bool;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(IntoBit::into_bit)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for Bits<I> where I::Item: IntoBit {}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Bits<I>
+where
+    I::Item: IntoBit,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(IntoBit::into_bit)
+    }
+}
+
+/// Fixed capacity buffer for collecting bits, such as from an interrupt-driven source,
+/// before running tests without allocating
+///
+/// Bits are stored first bit lowest (see [BitOrder::Lsb]) in `N` bytes, specified in bytes
+/// as array lengths cannot be computed from const generics. Use
+/// [BitBuffer::with_capacity] to collect a number of bits that is not a multiple of 8.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BitBuffer<const N: usize> {
+    buff: [u8; N],
+    len: usize,
+    capacity: usize,
+}
+
+impl<const N: usize> Default for BitBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> BitBuffer<N> {
+    /// Create a new, empty, buffer holding `N * 8` bits
+    pub const fn new() -> Self {
+        Self {
+            buff: [0; N],
+            len: 0,
+            capacity: N * 8,
+        }
+    }
+
+    /// Create a new, empty, buffer holding `bits` bits, returning
+    /// [Error::InvalidParameter] where this exceeds `N * 8`
+    pub const fn with_capacity(bits: usize) -> Result<Self, Error> {
+        if bits > N * 8 {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self {
+            buff: [0; N],
+            len: 0,
+            capacity: bits,
+        })
+    }
+
+    /// Push a single bit, returning `false` (and discarding the bit) where the buffer is
+    /// full
+    pub fn push(&mut self, bit: bool) -> bool {
+        if self.is_full() {
+            return false;
+        }
+
+        let (i, j) = (self.len / 8, self.len % 8);
+        match bit {
+            true => self.buff[i] |= 1 << j,
+            false => self.buff[i] &= !(1 << j),
+        }
+        self.len += 1;
+
+        true
+    }
+
+    /// Number of bits collected
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether no bits have been collected
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check whether the buffer has reached capacity
+    pub fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    /// Capacity of the buffer in bits
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Fetch the bytes holding the collected bits, with any unused bits of the last byte
+    /// zeroed
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buff[..self.len.div_ceil(8)]
+    }
+
+    /// Iterate over the collected bits, in the order pushed
+    pub fn iter(&self) -> BitIter<&[u8]> {
+        BitIter {
+            buff: &self.buff[..],
+            i: 0,
+            j: 0,
+            k: self.len / 8,
+            l: self.len % 8,
+            order: BitOrder::Lsb,
+        }
+    }
+
+    /// Clear the buffer, retaining the capacity
+    pub fn clear(&mut self) {
+        self.buff.fill(0);
+        self.len = 0;
+    }
+}
+
+/// Fixed capacity ring buffer holding the most recent bits, for sliding window and
+/// continuous tests without allocating
+///
+/// Once full each push overwrites the oldest bit. As with [BitBuffer] the storage is
+/// specified as `N` bytes, use [BitRing::with_capacity] for a window that is not a
+/// multiple of 8 bits.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BitRing<const N: usize> {
+    buff: [u8; N],
+    head: usize,
+    len: usize,
+    capacity: usize,
+}
+
+impl<const N: usize> Default for BitRing<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> BitRing<N> {
+    /// Create a new, empty, ring holding `N * 8` bits
+    pub const fn new() -> Self {
+        Self {
+            buff: [0; N],
+            head: 0,
+            len: 0,
+            capacity: N * 8,
+        }
+    }
+
+    /// Create a new, empty, ring holding `bits` bits, returning
+    /// [Error::InvalidParameter] where this is zero or exceeds `N * 8`
+    pub const fn with_capacity(bits: usize) -> Result<Self, Error> {
+        if bits == 0 || bits > N * 8 {
+            return Err(Error::InvalidParameter);
+        }
+
+        Ok(Self {
+            buff: [0; N],
+            head: 0,
+            len: 0,
+            capacity: bits,
+        })
+    }
+
+    /// Push a single bit, returning the oldest bit where this is overwritten
+    pub fn push(&mut self, bit: bool) -> Option<bool> {
+        if self.capacity == 0 {
+            return Some(bit);
+        }
+
+        let (i, j) = (self.head / 8, self.head % 8);
+
+        let old = match self.is_full() {
+            true => Some(self.buff[i] & (1 << j) != 0),
+            false => {
+                self.len += 1;
+                None
+            }
+        };
+
+        match bit {
+            true => self.buff[i] |= 1 << j,
+            false => self.buff[i] &= !(1 << j),
+        }
+        self.head = (self.head + 1) % self.capacity;
+
+        old
+    }
+
+    /// Number of bits held
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether no bits have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check whether the ring has reached capacity, so further pushes overwrite the
+    /// oldest bit
+    pub fn is_full(&self) -> bool {
+        self.len >= self.capacity
+    }
+
+    /// Capacity of the ring in bits
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Iterate over the bits held, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        let start = self.head + self.capacity - self.len;
+
+        (0..self.len).map(move |k| {
+            let b = (start + k) % self.capacity;
+            self.buff[b / 8] & (1 << (b % 8)) != 0
+        })
+    }
+
+    /// Clear the ring, retaining the capacity
+    pub fn clear(&mut self) {
+        self.buff.fill(0);
+        self.head = 0;
+        self.len = 0;
+    }
+}
+
+/// Define a bit-wise RNG iterator over a specific `rand_core` version
+#[allow(unused_macros)]
+macro_rules! bits_from_rng {
+    ($(#[$meta:meta])* $name:ident, $rng:path, $buffer:ty, $next:ident) => {
+        $(#[$meta])*
+        pub struct $name<'a, R: $rng> {
+            rng: &'a mut R,
+            remaining: usize,
+            buffer: $buffer,
+            buffered: u8,
+        }
+
+        impl<'a, R: $rng> $name<'a, R> {
+            /// Create a new iterator producing `items` bits from the provided RNG
+            pub fn new(rng: &'a mut R, items: usize) -> Self {
+                Self {
+                    rng,
+                    remaining: items,
+                    buffer: 0,
+                    buffered: 0,
+                }
+            }
+        }
+
+        impl<'a, R: $rng> Iterator for $name<'a, R> {
+            type Item = bool;
+
+            fn next(&mut self) -> Option<bool> {
+                if self.remaining == 0 {
+                    return None;
+                }
+
+                self.remaining -= 1;
+
+                if self.buffered == 0 {
+                    self.buffer = self.rng.$next();
+                    self.buffered = <$buffer>::BITS as u8;
+                }
+                let result = self.buffer & 1 != 0;
+                self.buffer >>= 1;
+                self.buffered -= 1;
+                Some(result)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "rand_core_0_6")]
+bits_from_rng!(
+    /// Helper for bit-wise iteration from a `rand_core` 0.6 RNG
+    ///
+    /// This pulls random data out of the RNG in chunks of 32 bits, and produces them one by one for
+    /// testing.
+    BitsFromRng,
+    rand_core_0_6::RngCore,
+    u32,
+    next_u32
+);
+
+#[cfg(feature = "rand_core_0_6")]
+bits_from_rng!(
+    /// Helper for bit-wise iteration from a `rand_core` 0.6 RNG, 64 bits at a time
+    ///
+    /// This halves the number of RNG calls compared to [BitsFromRng] for 64-bit RNGs, note
+    /// the produced bits may differ where `next_u32` is not the low half of `next_u64`.
+    BitsFromRng64,
+    rand_core_0_6::RngCore,
+    u64,
+    next_u64
+);
+
+#[cfg(feature = "rand_core_0_9")]
+bits_from_rng!(
+    /// Helper for bit-wise iteration from a `rand_core` 0.9 RNG
+    ///
+    /// This pulls random data out of the RNG in chunks of 32 bits, and produces them one by one for
+    /// testing, see `BitsFromRng` for `rand_core` 0.6.
+    BitsFromRng09,
+    rand_core_0_9::RngCore,
+    u32,
+    next_u32
+);
+
+#[cfg(feature = "rand_core_0_9")]
+bits_from_rng!(
+    /// Helper for bit-wise iteration from a `rand_core` 0.9 RNG, 64 bits at a time
+    ///
+    /// See `BitsFromRng64` for details.
+    BitsFromRng64_09,
+    rand_core_0_9::RngCore,
+    u64,
+    next_u64
+);
+
+/// Define a fallible bit-wise RNG iterator, where `$fetch` reads the next `u32` from `$r`
+#[allow(unused_macros)]
+macro_rules! try_bits_from_rng {
+    ($(#[$meta:meta])* $name:ident, $rng:path, |$r:ident| $fetch:expr) => {
+        $(#[$meta])*
+        pub struct $name<'a, R: $rng> {
+            rng: &'a mut R,
+            remaining: usize,
+            buffer: u32,
+            buffered: u8,
+        }
+
+        impl<'a, R: $rng> $name<'a, R> {
+            /// Create a new iterator producing `items` bits from the provided RNG
+            pub fn new(rng: &'a mut R, items: usize) -> Self {
+                Self {
+                    rng,
+                    remaining: items,
+                    buffer: 0,
+                    buffered: 0,
+                }
+            }
+        }
+
+        impl<'a, R: $rng> Iterator for $name<'a, R> {
+            type Item = Result<bool, Error>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.remaining == 0 {
+                    return None;
+                }
+
+                if self.buffered == 0 {
+                    let $r = &mut *self.rng;
+                    match $fetch {
+                        Ok(v) => self.buffer = v,
+                        Err(_) => {
+                            // Stop on failure
+                            self.remaining = 0;
+                            return Some(Err(Error::RngFailed));
+                        }
+                    }
+                    self.buffered = u32::BITS as u8;
+                }
+
+                self.remaining -= 1;
+
+                let result = self.buffer & 1 != 0;
+                self.buffer >>= 1;
+                self.buffered -= 1;
+                Some(Ok(result))
+            }
+        }
+    };
 }
 
-/// Helper for bit-wise iteration from an RNG
+#[cfg(feature = "rand_core_0_6")]
+try_bits_from_rng!(
+    /// Helper for fallible bit-wise iteration from a `rand_core` 0.6 RNG
+    ///
+    /// This pulls random data out of the RNG with `try_fill_bytes` in chunks of 32 bits,
+    /// yielding [Error::RngFailed] and ending iteration if the RNG fails. See [try_bits]
+    /// for use with tests.
+    TryBitsFromRng,
+    rand_core_0_6::RngCore,
+    |rng| {
+        let mut b = [0u8; 4];
+        rng.try_fill_bytes(&mut b).map(|_| u32::from_le_bytes(b))
+    }
+);
+
+#[cfg(feature = "rand_core_0_9")]
+try_bits_from_rng!(
+    /// Helper for fallible bit-wise iteration from a `rand_core` 0.9
+    /// [TryRngCore](rand_core_0_9::TryRngCore)
+    ///
+    /// See `TryBitsFromRng` for details.
+    TryBitsFromRng09,
+    rand_core_0_9::TryRngCore,
+    |rng| rng.try_next_u32()
+);
+
+/// Helper for fallible bit-wise iteration over a source of bytes, LSB first
 ///
-/// This pulls random data out of the RNG in chunks of 32 bits, and produces them one by one for
-/// testing.
-pub struct BitsFromRng<'a, R: rand_core_0_6::RngCore> {
-    rng: &'a mut R,
-    remaining: usize,
-    buffer: u32,
+/// This yields the bits of each byte as for [BitIter], passing through the source error
+/// and ending iteration on the first failure. See [try_bits_from_bytes] for use with
+/// tests.
+pub struct TryBitsFromBytes<I> {
+    iter: I,
+    buffer: u8,
     buffered: u8,
+    done: bool,
 }
 
-impl<'a, R: rand_core_0_6::RngCore> BitsFromRng<'a, R> {
-    pub fn new(rng: &'a mut R, items: usize) -> Self {
+impl<I, E> TryBitsFromBytes<I>
+where
+    I: Iterator<Item = Result<u8, E>>,
+{
+    /// Create a new iterator over the bits of the provided byte source
+    pub fn new(iter: I) -> Self {
         Self {
-            rng,
-            remaining: items,
+            iter,
             buffer: 0,
             buffered: 0,
+            done: false,
         }
     }
 }
-impl<'a, R: rand_core_0_6::RngCore> Iterator for BitsFromRng<'a, R> {
-    type Item = bool;
-    fn next(&mut self) -> Option<bool> {
-        if self.remaining == 0 {
+
+impl<I, E> Iterator for TryBitsFromBytes<I>
+where
+    I: Iterator<Item = Result<u8, E>>,
+{
+    type Item = Result<bool, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
             return None;
         }
 
-        self.remaining -= 1;
-
         if self.buffered == 0 {
-            self.buffer = self.rng.next_u32();
-            self.buffered = 32;
+            match self.iter.next()? {
+                Ok(v) => self.buffer = v,
+                Err(e) => {
+                    // Stop on failure
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+            self.buffered = u8::BITS as u8;
         }
+
         let result = self.buffer & 1 != 0;
         self.buffer >>= 1;
         self.buffered -= 1;
-        Some(result)
+        Some(Ok(result))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    extern crate alloc;
-    use alloc::vec::Vec;
+/// Adapter over fallible bits, yielding values until the first error
+///
+/// See [try_bits] and [try_bits_from_bytes] for use with tests.
+pub struct TryBits<I: Iterator<Item = Result<bool, E>>, E = Error> {
+    iter: I,
+    err: Option<E>,
+}
 
-    use super::*;
+impl<I: Iterator<Item = Result<bool, E>>, E> TryBits<I, E> {
+    /// Create a new adapter over fallible bits
+    pub fn new(iter: I) -> Self {
+        Self { iter, err: None }
+    }
 
-    #[test]
-    fn bit_iter() {
-        let tests = &[(
-            &[0b0000_0001, 0b0000_0010, 0b0100_0000, 0b1000_0000],
-            &[
+    /// Fetch the error (if any) that ended iteration
+    pub fn error(&self) -> Option<&E> {
+        self.err.as_ref()
+    }
+}
+
+impl<I: Iterator<Item = Result<bool, E>>, E> Iterator for TryBits<I, E> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.err.is_some() {
+            return None;
+        }
+
+        match self.iter.next()? {
+            Ok(v) => Some(v),
+            Err(e) => {
+                self.err = Some(e);
+                None
+            }
+        }
+    }
+}
+
+/// Run a test over fallible bits, returning the first bit error (such as
+/// [Error::RngFailed]) in place of the test result
+///
+/// For example, `try_bits(TryBitsFromRng::new(&mut rng, 1024), |b| nist_freq_monobit(b))`.
+pub fn try_bits<I, T>(
+    iter: I,
+    test: impl FnOnce(&mut TryBits<I>) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    I: Iterator<Item = Result<bool, Error>>,
+{
+    let mut bits = TryBits::new(iter);
+    let r = test(&mut bits);
+
+    match bits.err {
+        Some(e) => Err(e),
+        None => r,
+    }
+}
+
+/// Run a test over a fallible source of bytes, returning [Error::RngFailed] in place of
+/// the test result if the source fails
+///
+/// This allows hardware sources to be tested while propagating faults, for example
+/// `try_bits_from_bytes(source.map(|_| read_byte()), |b| nist_runs(b))`. The source error
+/// is available from [TryBits::error] within the test, and bits are read as for
+/// [TryBitsFromBytes].
+pub fn try_bits_from_bytes<I, E, T>(
+    iter: I,
+    test: impl FnOnce(&mut TryBits<TryBitsFromBytes<I>, E>) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    I: Iterator<Item = Result<u8, E>>,
+{
+    let mut bits = TryBits::new(TryBitsFromBytes::new(iter));
+    let r = test(&mut bits);
+
+    match bits.err {
+        Some(_) => Err(Error::RngFailed),
+        None => r,
+    }
+}
+
+/// Deterministic xorshift64* RNG for reproducible examples and regression tests
+///
+/// This is **not** suitable for any security purpose, and exists only to exercise the
+/// tests deterministically. Seeds are expanded with SplitMix64 so any value (including
+/// zero) is valid, and `next_u32` returns the high half of `next_u64`.
+#[cfg(any(feature = "rand_core_0_6", feature = "rand_core_0_9"))]
+#[derive(Clone, PartialEq, Debug)]
+pub struct TestRng {
+    state: u64,
+}
+
+#[cfg(any(feature = "rand_core_0_6", feature = "rand_core_0_9"))]
+impl TestRng {
+    /// Create a new RNG from a seed
+    pub const fn seed(seed: u64) -> Self {
+        // SplitMix64, avoiding the all-zero xorshift state
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+
+        Self {
+            state: match z {
+                0 => 1,
+                _ => z,
+            },
+        }
+    }
+
+    fn step(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+#[cfg(feature = "rand_core_0_6")]
+impl rand_core_0_6::RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.step() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.step()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core_0_6::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core_0_6::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand_core_0_9")]
+impl rand_core_0_9::RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.step() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.step()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core_0_9::impls::fill_bytes_via_next(self, dest)
+    }
+}
+
+/// Adapter from bits to fixed-width words
+///
+/// Words are packed with the first bit lowest ([BitOrder::Lsb]) or highest
+/// ([BitOrder::Msb]), such that a [BitIter] with the same order round-trips to the
+/// original bytes in little or big endian order respectively. Other combinations of bit
+/// and byte order are supported via [BitPacking], for example to match the word assembly
+/// of an external tool. Reads return `None` where the underlying iterator ends mid-word,
+/// discarding the partial word.
+pub struct WordsFromBits<I: Iterator<Item = bool>> {
+    iter: I,
+    packing: BitPacking,
+}
+
+impl<I: Iterator<Item = bool>> WordsFromBits<I> {
+    /// Create a new adapter, first bit lowest
+    pub fn new(iter: I) -> Self {
+        Self::with_order(iter, BitOrder::Lsb)
+    }
+
+    /// Create a new adapter, first bit highest
+    pub fn new_msb(iter: I) -> Self {
+        Self::with_order(iter, BitOrder::Msb)
+    }
+
+    /// Create a new adapter with the specified [BitOrder], see [BitPacking::from]
+    pub fn with_order(iter: I, order: BitOrder) -> Self {
+        Self::with_packing(iter, order.into())
+    }
+
+    /// Create a new adapter with the specified [BitPacking]
+    pub fn with_packing(iter: I, packing: BitPacking) -> Self {
+        Self { iter, packing }
+    }
+
+    /// Read a `bits` wide word (up to 64)
+    pub fn next_bits(&mut self, bits: usize) -> Option<u64> {
+        let mut v = 0;
+        for start in (0..bits).step_by(8) {
+            let len = (bits - start).min(8);
+
+            // Assemble each byte in bit order
+            let mut byte = 0;
+            for i in 0..len {
+                let b = self.iter.next()? as u64;
+                match self.packing.bit_order {
+                    BitOrder::Lsb => byte |= b << i,
+                    BitOrder::Msb => byte = (byte << 1) | b,
+                }
+            }
+
+            // Then place bytes in byte order
+            match self.packing.byte_order {
+                ByteOrder::Little => v |= byte << start,
+                ByteOrder::Big => v = (v << len) | byte,
+            }
+        }
+        Some(v)
+    }
+
+    /// Read the next `u32`
+    pub fn next_u32(&mut self) -> Option<u32> {
+        self.next_bits(32).map(|v| v as u32)
+    }
+
+    /// Read the next `u64`
+    pub fn next_u64(&mut self) -> Option<u64> {
+        self.next_bits(64)
+    }
+
+    /// Fetch the underlying bit iterator
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+/// Adapter from bits to uniform floats over [0, 1)
+///
+/// Each value packs the next 24 (`f32`) or 53 (`f64`) bits into the mantissa with the
+/// first bit lowest, so all values are exactly representable, yielding `None` where the
+/// underlying iterator ends mid-value.
+pub struct UniformFloats<I: Iterator<Item = bool>, F = f32> {
+    words: WordsFromBits<I>,
+    _float: PhantomData<F>,
+}
+
+impl<I: Iterator<Item = bool>> UniformFloats<I, f32> {
+    /// Create a new adapter yielding `f32` values from 24 bits each
+    pub fn new(iter: I) -> Self {
+        Self {
+            words: WordsFromBits::new(iter),
+            _float: PhantomData,
+        }
+    }
+}
+
+impl<I: Iterator<Item = bool>> UniformFloats<I, f64> {
+    /// Create a new adapter yielding `f64` values from 53 bits each
+    pub fn new_f64(iter: I) -> Self {
+        Self {
+            words: WordsFromBits::new(iter),
+            _float: PhantomData,
+        }
+    }
+}
+
+impl<I: Iterator<Item = bool>> Iterator for UniformFloats<I, f32> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let v = self.words.next_bits(f32::MANTISSA_DIGITS as usize)?;
+        Some(v as f32 / (1u64 << f32::MANTISSA_DIGITS) as f32)
+    }
+}
+
+impl<I: Iterator<Item = bool>> Iterator for UniformFloats<I, f64> {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        let v = self.words.next_bits(f64::MANTISSA_DIGITS as usize)?;
+        Some(v as f64 / (1u64 << f64::MANTISSA_DIGITS) as f64)
+    }
+}
+
+/// Minimum data length for [chi_square_bytes], giving at least 10 expected occurrences of
+/// each byte value
+pub const CHI_SQUARE_MIN_BYTES: usize = 2560;
+
+/// Maximum alphabet size (k) for tests and estimates over generic symbols, see
+/// [chi_square_symbols]
+pub const MAX_ALPHABET_SIZE: usize = 256;
+
+/// Chi-square goodness-of-fit test of byte frequencies against a uniform distribution
+///
+/// Tallies the occurrences of each of the 256 byte values, computing x^2 against the
+/// expected n / 256 with 255 degrees of freedom. This requires at least
+/// [CHI_SQUARE_MIN_BYTES] bytes, returning [Error::InsufficientSampleSize] otherwise.
+pub fn chi_square_bytes(data: &[u8]) -> Result<f32, Error> {
+    chi_square_bytes_result(data)?.check()
+}
+
+/// Chi-square goodness-of-fit test of byte frequencies at significance level `alpha`
+///
+/// See [chi_square_bytes] for details
+pub fn chi_square_bytes_with_alpha(data: &[u8], alpha: f32) -> Result<f32, Error> {
+    chi_square_bytes_result(data)?.at_alpha(alpha)?.check()
+}
+
+/// Chi-square goodness-of-fit test of byte frequencies returning the [TestResult], with
+/// statistic x^2
+///
+/// See [chi_square_bytes] for details
+pub fn chi_square_bytes_result(data: &[u8]) -> Result<TestResult, Error> {
+    chi_square_symbols_result(data.iter().copied(), MAX_ALPHABET_SIZE)
+}
+
+/// Chi-square goodness-of-fit test of symbol frequencies from an alphabet of `k` symbols
+/// against a uniform distribution
+///
+/// As [chi_square_bytes] with k - 1 degrees of freedom, for sources where the natural
+/// unit is not a byte. This requires 2 <= k <= [MAX_ALPHABET_SIZE] and each sample less
+/// than k, returning [Error::InvalidParameter] otherwise, and at least 10 k samples.
+pub fn chi_square_symbols<S: Into<usize>>(
+    samples: impl Iterator<Item = S>,
+    k: usize,
+) -> Result<f32, Error> {
+    chi_square_symbols_result(samples, k)?.check()
+}
+
+/// Chi-square goodness-of-fit test of symbol frequencies at significance level `alpha`
+///
+/// See [chi_square_symbols] for details
+pub fn chi_square_symbols_with_alpha<S: Into<usize>>(
+    samples: impl Iterator<Item = S>,
+    k: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    chi_square_symbols_result(samples, k)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// Chi-square goodness-of-fit test of symbol frequencies returning the [TestResult],
+/// with statistic x^2
+///
+/// See [chi_square_symbols] for details
+pub fn chi_square_symbols_result<S: Into<usize>>(
+    samples: impl Iterator<Item = S>,
+    k: usize,
+) -> Result<TestResult, Error> {
+    if !(2..=MAX_ALPHABET_SIZE).contains(&k) {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Tally symbol frequencies
+    let mut counts = [0usize; MAX_ALPHABET_SIZE];
+    let mut n = 0;
+    for s in samples {
+        let s = s.into();
+        if s >= k {
+            return Err(Error::InvalidParameter);
+        }
+
+        counts[s] += 1;
+        n += 1;
+    }
+
+    // Check we have enough data for the chi-square approximation
+    if n < 10 * k {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute x^2 against the expected frequency
+    let e = n as f64 / k as f64;
+    let x2 = counts[..k]
+        .iter()
+        .map(|c| (*c as f64 - e) * (*c as f64 - e) / e)
+        .sum::<f64>() as f32;
+
+    // Compute p
+    let p = igamma_upper((k - 1) as f32 / 2.0, x2 / 2.0);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Shannon entropy estimate of byte frequencies, in bits per byte
+///
+/// Computes `-Σ p_i log2(p_i)` over the observed frequencies of the 256 byte values,
+/// giving a value in [0, 8] with empty data returning 0. This is a diagnostic rather
+/// than a test, small samples underestimate the entropy and structured data (such as a
+/// counter) may still score 8.
+pub fn shannon_entropy_bytes(data: &[u8]) -> f32 {
+    let n = data.len();
+    if n == 0 {
+        return 0.0;
+    }
+
+    // Tally byte frequencies
+    let mut counts = [0usize; 256];
+    for b in data {
+        counts[*b as usize] += 1;
+    }
+
+    // Sum -p log2(p) over observed values
+    let h = counts
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / n as f64;
+            -p * math::log2(p)
+        })
+        .sum::<f64>();
+
+    h as f32
+}
+
+/// Lag-1 serial correlation coefficient of a sequence of values
+///
+/// Computes Knuth's coefficient (TAOCP Vol. 2, 3.3.2 K)
+/// `C = (n Σ x_i x_{i+1} - (Σ x_i)^2) / (n Σ x_i^2 - (Σ x_i)^2)` in a single pass, with
+/// the final term wrapping to the first value. This gives a value in [-1, 1], expected
+/// within about `±2 / sqrt(n)` of zero for independent values, with fewer than two
+/// values or constant sequences returning 0. As with [shannon_entropy_bytes] this is a
+/// diagnostic rather than a test.
+pub fn serial_correlation(mut values: impl Iterator<Item = f32>) -> f32 {
+    let first = match values.next() {
+        Some(v) => v as f64,
+        None => return 0.0,
+    };
+
+    let mut n = 1;
+    let (mut sum, mut sum_sq, mut sum_lag) = (first, first * first, 0.0);
+    let mut prev = first;
+
+    for v in values {
+        let v = v as f64;
+
+        sum += v;
+        sum_sq += v * v;
+        sum_lag += prev * v;
+
+        prev = v;
+        n += 1;
+    }
+
+    // Wrap from the last value to the first
+    sum_lag += prev * first;
+
+    let n = n as f64;
+    // Treat variance lost to rounding as constant
+    let den = n * sum_sq - sum * sum;
+    if n < 2.0 || den <= n * sum_sq * f64::EPSILON {
+        return 0.0;
+    }
+
+    ((n * sum_lag - sum * sum) / den) as f32
+}
+
+/// One-sample Kolmogorov-Smirnov test of values against U(0, 1), returning the P-value
+///
+/// Sorts `values` in place (without allocating) to compute the statistic
+/// `D = max_i max(i / n - x_i, x_i - (i - 1) / n)`, with the asymptotic P-value from the
+/// Kolmogorov distribution using Stephens' correction
+/// `λ = (sqrt(n) + 0.12 + 0.11 / sqrt(n)) D`. Values are clamped to [0, 1], and empty
+/// inputs return 1. This turns any quantity that should be uniform (such as P-values or
+/// [UniformFloats]) into a P-value, for comparison with a significance level.
+pub fn ks_test(values: &mut [f32]) -> f32 {
+    ks_statistic(values).1 as f32
+}
+
+/// Compute the Kolmogorov-Smirnov statistic D and P-value for [ks_test]
+pub(crate) fn ks_statistic(values: &mut [f32]) -> (f64, f64) {
+    let n = values.len();
+    if n == 0 {
+        return (0.0, 1.0);
+    }
+
+    values.sort_unstable_by(f32::total_cmp);
+
+    // Largest distance between the empirical and uniform CDFs, either side of each step
+    let mut d = 0f64;
+    for (i, x) in values.iter().enumerate() {
+        let x = x.clamp(0.0, 1.0) as f64;
+        d = d
+            .max((i + 1) as f64 / n as f64 - x)
+            .max(x - i as f64 / n as f64);
+    }
+
+    let sn = math::sqrt(n as f64);
+    (d, kolmogorov_q((sn + 0.12 + 0.11 / sn) * d))
+}
+
+/// Complementary Kolmogorov distribution Q(λ) = P(K > λ)
+fn kolmogorov_q(l: f64) -> f64 {
+    use core::f64::consts::PI;
+
+    if l < 1e-3 {
+        return 1.0;
+    }
+
+    // Use the series converging fastest for each side, 5 terms are within f64 precision
+    match l < 1.18 {
+        true => {
+            let s = (1..=5)
+                .map(|j| (2 * j - 1) as f64)
+                .map(|k| math::exp(-k * k * PI * PI / (8.0 * l * l)))
+                .sum::<f64>();
+            1.0 - math::sqrt(2.0 * PI) / l * s
+        }
+        false => {
+            let s = (1..=5)
+                .map(|j| {
+                    let t = math::exp(-2.0 * (j * j) as f64 * l * l);
+                    if j % 2 == 1 {
+                        t
+                    } else {
+                        -t
+                    }
+                })
+                .sum::<f64>();
+            2.0 * s
+        }
+    }
+}
+
+/// Window length for [lz_compression_ratio]
+pub const LZ_WINDOW: usize = 4096;
+
+/// Minimum and maximum match lengths for [lz_compression_ratio]
+const LZ_MIN_MATCH: usize = 3;
+const LZ_MAX_MATCH: usize = 258;
+
+/// Maximum candidates checked per position for [lz_compression_ratio]
+const LZ_MAX_CHAIN: usize = 64;
+
+/// LZ77 compression ratio estimate, in tokens per byte
+///
+/// Greedily parses the data into literal and match tokens, finding matches of
+/// 3..=258 bytes within the preceding [LZ_WINDOW] bytes, and returns the number of
+/// tokens over the data length with empty data returning 0. Random data should give a
+/// ratio close to 1.0, with lower values indicating repeated structure.
+///
+/// As this is `no_std` and does not allocate the match finder uses fixed-size hash
+/// chains (~32 KiB) on the stack, and checks at most 64 candidates per position so may
+/// miss some matches. This is a diagnostic rather than a test.
+pub fn lz_compression_ratio(data: &[u8]) -> f32 {
+    const NONE: u32 = u32::MAX;
+    let n = data.len();
+    if n == 0 {
+        return 0.0;
+    }
+
+    // Most recent position for each hash, and previous position with the same hash
+    // for each position in the window
+    let mut head = [NONE; LZ_WINDOW];
+    let mut prev = [NONE; LZ_WINDOW];
+
+    let hash = |i: usize| {
+        let v = u32::from_le_bytes([data[i], data[i + 1], data[i + 2], 0]);
+        (v.wrapping_mul(2654435761) >> 20) as usize
+    };
+
+    let mut tokens = 0;
+    let mut i = 0;
+
+    while i < n {
+        // Search candidates within the window for the longest match
+        let mut best = 0;
+        if i + LZ_MIN_MATCH <= n {
+            let max = (n - i).min(LZ_MAX_MATCH);
+            let mut c = head[hash(i)];
+
+            for _ in 0..LZ_MAX_CHAIN {
+                if c == NONE || i - c as usize >= LZ_WINDOW {
+                    break;
+                }
+
+                let c_i = c as usize;
+                let l = (0..max)
+                    .take_while(|k| data[c_i + k] == data[i + k])
+                    .count();
+                best = best.max(l);
+
+                c = prev[c_i % LZ_WINDOW];
+            }
+        }
+
+        // Emit a match or a literal
+        let len = match best >= LZ_MIN_MATCH {
+            true => best,
+            false => 1,
+        };
+        tokens += 1;
+
+        // Insert consumed positions into the hash chains
+        for p in i..(i + len).min(n.saturating_sub(LZ_MIN_MATCH - 1)) {
+            let h = hash(p);
+            prev[p % LZ_WINDOW] = head[h];
+            head[h] = p as u32;
+        }
+
+        i += len;
+    }
+
+    tokens as f32 / n as f32
+}
+
+/// Structural summary from [stuck_bits] and [stuck_bits_words]
+///
+/// Bit positions are numbered within each word as `8 * byte + bit`, with bit 0 the least
+/// significant bit of each byte, so for single byte words bit i of each mask corresponds
+/// to `1 << i` in the data.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StuckBitReport {
+    /// Word width in bits
+    pub word_bits: usize,
+    /// Mask of positions that are zero in every complete word
+    pub stuck_low: u64,
+    /// Mask of positions that are one in every complete word
+    pub stuck_high: u64,
+    /// Longest run of consecutive `0x00` bytes
+    pub longest_zero_run: usize,
+    /// Longest run of consecutive `0xff` bytes
+    pub longest_ones_run: usize,
+}
+
+impl StuckBitReport {
+    /// Mask of positions that are constant in every complete word
+    pub fn stuck(&self) -> u64 {
+        self.stuck_low | self.stuck_high
+    }
+
+    /// Check whether any position is constant
+    pub fn any_stuck(&self) -> bool {
+        self.stuck() != 0
+    }
+}
+
+/// Find bit positions (mod 8) that are constant across the whole buffer, along with the
+/// longest runs of all-zero and all-one bytes
+///
+/// This is a cheap structural guard to run before the statistical tests, catching faults
+/// such as a dead or shorted data line with a single pass over the data rather than
+/// waiting for these to show in the monobit test. As with [shannon_entropy_bytes] this is
+/// a diagnostic rather than a test, so returns a [StuckBitReport] for the caller to act
+/// on. Random data of more than a few dozen bytes is very unlikely to have any stuck
+/// positions, with empty data reporting none. See [stuck_bits_words] for wider words, and
+/// [per_position_bias] for positions that are biased rather than stuck.
+pub fn stuck_bits(data: &[u8]) -> StuckBitReport {
+    stuck_bits_report(data, 1)
+}
+
+/// Find bit positions (mod the word width) that are constant across the whole buffer
+///
+/// As for [stuck_bits] with `data` split into words of `word_bytes` bytes, for sources
+/// producing wider words where a single fault affects one bit of each word. Trailing
+/// bytes beyond a whole number of words are included in the byte runs but not the stuck
+/// masks. Returns [Error::InvalidParameter] unless `word_bytes` is in 1..=8.
+pub fn stuck_bits_words(data: &[u8], word_bytes: usize) -> Result<StuckBitReport, Error> {
+    if !(1..=8).contains(&word_bytes) {
+        return Err(Error::InvalidParameter);
+    }
+
+    Ok(stuck_bits_report(data, word_bytes))
+}
+
+/// Compute the [StuckBitReport] for validated word widths
+fn stuck_bits_report(data: &[u8], word_bytes: usize) -> StuckBitReport {
+    let word_bits = word_bytes * 8;
+
+    // Accumulate positions seen set and cleared
+    let mut any_high = 0u64;
+    let mut any_low = 0u64;
+    let mut words = 0;
+    for w in data.chunks_exact(word_bytes) {
+        let mut b = [0u8; 8];
+        b[..word_bytes].copy_from_slice(w);
+        let v = u64::from_le_bytes(b);
+
+        any_high |= v;
+        any_low |= !v;
+        words += 1;
+    }
+
+    // Find longest runs of constant bytes
+    let (mut zeros, mut ones) = (0, 0);
+    let (mut longest_zero_run, mut longest_ones_run) = (0, 0);
+    for b in data {
+        zeros = if *b == 0x00 { zeros + 1 } else { 0 };
+        ones = if *b == 0xff { ones + 1 } else { 0 };
+        longest_zero_run = longest_zero_run.max(zeros);
+        longest_ones_run = longest_ones_run.max(ones);
+    }
+
+    // Report nothing stuck without any complete words
+    let mask = match (words, word_bits) {
+        (0, _) => 0,
+        (_, 64) => u64::MAX,
+        (_, w) => (1 << w) - 1,
+    };
+
+    StuckBitReport {
+        word_bits,
+        stuck_low: !any_high & mask,
+        stuck_high: !any_low & mask,
+        longest_zero_run,
+        longest_ones_run,
+    }
+}
+
+/// Fraction of ones at each bit position across all bytes
+///
+/// Entry i is the fraction of bytes with bit `1 << i` set, so each is expected near 0.5
+/// with a standard deviation of `0.5 / sqrt(n)` for n random bytes. A single global
+/// monobit test averages over all positions and may hide a bias on one of these, as
+/// from a faulty line of an 8-bit parallel source. As with [stuck_bits], which this
+/// complements, this is a diagnostic rather than a test, with empty data returning NaN
+/// at each position.
+pub fn per_position_bias(data: &[u8]) -> [f32; 8] {
+    let mut counts = [0usize; 8];
+    for b in data {
+        for (i, c) in counts.iter_mut().enumerate() {
+            *c += (b >> i & 1) as usize;
+        }
+    }
+
+    counts.map(|c| (c as f64 / data.len() as f64) as f32)
+}
+
+/// Poisson probability mass function, P(X = k) for rate `lambda`
+///
+/// Computed in log space as `exp(k ln(λ) - λ - ln(k!))` for stability with large `k`,
+/// returning NaN for negative or NaN `lambda`.
+pub fn poisson_pmf(k: u32, lambda: f32) -> f32 {
+    if lambda.is_nan() || lambda < 0.0 {
+        return f32::NAN;
+    }
+
+    // Handle λ = 0 where ln(λ) is undefined
+    if lambda == 0.0 {
+        return if k == 0 { 1.0 } else { 0.0 };
+    }
+
+    let k = k as f32;
+    math::expf(k * math::logf(lambda) - lambda - math::lgammaf(k + 1.0))
+}
+
+/// Poisson cumulative distribution function, P(X <= k) for rate `lambda`
+///
+/// Computed from the regularized upper incomplete gamma function Q(k + 1, λ), returning
+/// NaN for negative or NaN `lambda`.
+pub fn poisson_cdf(k: u32, lambda: f32) -> f32 {
+    if lambda.is_nan() || lambda < 0.0 {
+        return f32::NAN;
+    }
+
+    if lambda == 0.0 {
+        return 1.0;
+    }
+
+    igamma_upper(k as f32 + 1.0, lambda)
+}
+
+/// Standard normal cumulative distribution function, Φ(x)
+pub fn normal_cdf(x: f32) -> f32 {
+    0.5 * (1.0 + math::erff(x / core::f32::consts::SQRT_2))
+}
+
+/// Standard normal cumulative distribution function, Φ(x), using [erfc_f64] for
+/// precision in the tails
+pub fn normal_cdf_f64(x: f64) -> f64 {
+    0.5 * erfc_f64(-x / core::f64::consts::SQRT_2)
+}
+
+/// Standard normal quantile function, Φ^-1(p)
+///
+/// See [normal_quantile_f64] for details
+pub fn normal_quantile(p: f32) -> f32 {
+    normal_quantile_f64(p as f64) as f32
+}
+
+/// Standard normal quantile function, Φ^-1(p), using the rational approximation from
+/// P. J. Acklam, "An algorithm for computing the inverse normal cumulative distribution
+/// function" (2003)
+///
+/// The approximation (relative error below 1.2e-9) is refined with a single Halley step
+/// using [erfc_f64], giving close to full precision. This returns -∞ and ∞ for p of 0 and
+/// 1 respectively, and NaN outside [0, 1].
+pub fn normal_quantile_f64(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p.is_nan() || !(0.0..=1.0).contains(&p) {
+        return f64::NAN;
+    }
+    if p == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p == 1.0 {
+        return f64::INFINITY;
+    }
+
+    let tail = |q: f64| {
+        let num = ((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5];
+        let den = (((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0;
+        num / den
+    };
+
+    // Rational approximation over the lower tail, central region, and upper tail
+    let x = if p < P_LOW {
+        tail(math::sqrt(-2.0 * math::log(p)))
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        let num = (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q;
+        let den = ((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0;
+        num / den
+    } else {
+        -tail(math::sqrt(-2.0 * math::log(1.0 - p)))
+    };
+
+    // Refine with a Halley step
+    let e = normal_cdf_f64(x) - p;
+    let u = e * math::sqrt(2.0 * core::f64::consts::PI) * math::exp(x * x / 2.0);
+
+    x - u / (1.0 + x * u / 2.0)
+}
+
+/// Complementary error function, using the rational Chebyshev approximations from
+/// W. J. Cody, "Rational Chebyshev approximations for the error function" (1969)
+///
+/// This has a relative error below 1e-15 over the representable range, including the
+/// tail where `1 - erf(x)` loses precision.
+pub fn erfc_f64(x: f64) -> f64 {
+    // Coefficients for |x| <= 0.46875, erf(x) = x * A(x^2) / B(x^2)
+    const A: [f64; 5] = [
+        3.1611237438705655,
+        113.86415415105016,
+        377.485237685302,
+        3209.3775891384694,
+        0.18577770618460315,
+    ];
+    const B: [f64; 4] = [
+        23.601290952344122,
+        244.02463793444417,
+        1282.6165260773723,
+        2844.236833439171,
+    ];
+
+    // Coefficients for 0.46875 < |x| <= 4, erfc(x) = e^-x^2 * C(x) / D(x)
+    const C: [f64; 9] = [
+        0.5641884969886701,
+        8.883149794388377,
+        66.11919063714163,
+        298.6351381974001,
+        881.952221241769,
+        1712.0476126340707,
+        2051.0783778260716,
+        1230.3393547979972,
+        2.1531153547440383e-08,
+    ];
+    const D: [f64; 8] = [
+        15.744926110709835,
+        117.6939508913125,
+        537.1811018620099,
+        1621.3895745666903,
+        3290.7992357334597,
+        4362.619090143247,
+        3439.3676741437216,
+        1230.3393548037495,
+    ];
+
+    // Coefficients for |x| > 4, erfc(x) = e^-x^2 / x * (1 / sqrt(pi) + P(1/x^2) / Q(1/x^2))
+    const P: [f64; 6] = [
+        0.30532663496123236,
+        0.36034489994980445,
+        0.12578172611122926,
+        0.016083785148742275,
+        0.0006587491615298378,
+        0.016315387137302097,
+    ];
+    const Q: [f64; 5] = [
+        2.568520192289822,
+        1.8729528499234673,
+        0.5279051029514285,
+        0.06051834131244132,
+        0.0023352049762686918,
+    ];
+
+    // Beyond this erfc(x) underflows
+    const X_BIG: f64 = 26.543;
+
+    let y = math::fabs(x);
+
+    // Small arguments, via erf
+    if y <= 0.46875 {
+        let y_sq = match y > f64::EPSILON / 2.0 {
+            true => y * y,
+            false => 0.0,
+        };
+
+        let mut num = A[4] * y_sq;
+        let mut den = y_sq;
+        for i in 0..3 {
+            num = (num + A[i]) * y_sq;
+            den = (den + B[i]) * y_sq;
+        }
+
+        return 1.0 - x * (num + A[3]) / (den + B[3]);
+    }
+
+    let r = if y >= X_BIG {
+        0.0
+    } else {
+        let r = if y <= 4.0 {
+            let mut num = C[8] * y;
+            let mut den = y;
+            for i in 0..7 {
+                num = (num + C[i]) * y;
+                den = (den + D[i]) * y;
+            }
+
+            (num + C[7]) / (den + D[7])
+        } else {
+            let z = 1.0 / (y * y);
+
+            let mut num = P[5] * z;
+            let mut den = z;
+            for i in 0..4 {
+                num = (num + P[i]) * z;
+                den = (den + Q[i]) * z;
+            }
+
+            let r = z * (num + P[4]) / (den + Q[4]);
+            (core::f64::consts::FRAC_2_SQRT_PI / 2.0 - r) / y
+        };
+
+        // Compute e^-y^2 in two parts to limit the error from squaring y
+        let y_sq = math::trunc(y * 16.0) / 16.0;
+        let del = (y - y_sq) * (y + y_sq);
+        math::exp(-y_sq * y_sq) * math::exp(-del) * r
+    };
+
+    match x < 0.0 {
+        true => 2.0 - r,
+        false => r,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+    use alloc::vec::Vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn bit_iter_words() {
+        // LSB first starts from bit 0 of each word, MSB first from the top bit
+        let words = [0x8000_0001u32, 0x0000_0002];
+        let lsb: Vec<_> = BitIterWords::new(&words).collect();
+        assert_eq!(lsb.len(), 64);
+        assert!(lsb[0] && lsb[31] && lsb[33]);
+        assert_eq!(lsb.iter().filter(|b| **b).count(), 3);
+
+        let msb: Vec<_> = BitIterWords::new_msb(&words).collect();
+        assert!(msb[0] && msb[31] && msb[62]);
+        assert_eq!(msb.iter().filter(|b| **b).count(), 3);
+
+        // Orders match byte iteration over little and big-endian words, for both widths
+        let mut buff = [0u8; 64];
+        lcg_fill(&mut buff, 67);
+        let w32: Vec<u32> = buff
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        let w64: Vec<u64> = buff
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+
+        assert!(BitIterWords::new(&w32).eq(BitIter::new(&buff)));
+        assert!(BitIterWords::new(&w64).eq(BitIter::new(&buff)));
+
+        let be32: Vec<u8> = w32.iter().flat_map(|w| w.to_be_bytes()).collect();
+        let be64: Vec<u8> = w64.iter().flat_map(|w| w.to_be_bytes()).collect();
+        assert!(BitIterWords::new_msb(&w32).eq(BitIter::new_msb(&be32)));
+        assert!(BitIterWords::new_msb(&w64).eq(BitIter::new_msb(&be64)));
+
+        // Reverse iteration, meeting in the middle
+        assert!(BitIterWords::new(&w64).rev().eq(BitIter::new(&buff).rev()));
+        let mut i = BitIterWords::new(&words[..1]);
+        assert_eq!(i.len(), 32);
+        assert_eq!((i.next(), i.next_back()), (Some(lsb[0]), Some(lsb[31])));
+        assert_eq!(i.by_ref().count(), 30);
+        assert_eq!((i.next(), i.next_back(), i.len()), (None, None, 0));
+
+        // Feeds the tests directly
+        assert_eq!(
+            crate::nist::nist_freq_monobit(BitIterWords::new(&w32)),
+            crate::nist::nist_freq_monobit(BitIter::new(&buff))
+        );
+    }
+
+    #[test]
+    fn bit_iter() {
+        let tests = &[(
+            &[0b0000_0001, 0b0000_0010, 0b0100_0000, 0b1000_0000],
+            &[
                 true, false, false, false, false, false, false, false, false, true, false, false,
                 false, false, false, false, false, false, false, false, false, false, true, false,
                 false, false, false, false, false, false, false, true,
@@ -114,8 +1904,882 @@ mod tests {
     }
 
     #[test]
+    fn bit_iter_rev() {
+        let buff = [0b0000_0001, 0b0110_0010, 0b0100_0000, 0b1000_0011];
+
+        for order in [BitOrder::Lsb, BitOrder::Msb] {
+            let mut v: Vec<bool> = BitIter::with_order(&buff, order).collect();
+            v.reverse();
+
+            let r: Vec<bool> = BitIter::with_order(&buff, order).rev().collect();
+            assert_eq!(v, r);
+        }
+
+        // Iteration from both ends meets in the middle
+        let mut i = BitIter::new(&buff[..1]);
+        assert_eq!(i.next(), Some(true));
+        assert_eq!(i.next_back(), Some(false));
+        assert_eq!(i.by_ref().count(), 6);
+        assert_eq!(i.next_back(), None);
+    }
+
+    #[test]
+    fn bit_iter_len() {
+        let buff = [0u8; 3];
+        let mut i = BitIter::new(&buff);
+
+        for n in (0..24).rev() {
+            i.next().unwrap();
+            assert_eq!(i.len(), n);
+            assert_eq!(i.size_hint(), (n, Some(n)));
+        }
+        assert_eq!(i.next(), None);
+        assert_eq!(i.len(), 0);
+
+        // Length includes reverse iteration
+        let mut i = BitIter::new(&buff);
+        i.next_back();
+        i.next();
+        assert_eq!(i.len(), 22);
+    }
+
+    #[test]
+    fn bit_iter_offset() {
+        let buff = [0x5a, 0b1011_0001, 0xc3];
+
+        // Byte offsets match slicing
+        assert!(BitIter::new_with_offset(&buff, 8).eq(BitIter::new(&buff[1..])));
+        assert!(BitIter::new_with_offset(&buff, 0).eq(BitIter::new(&buff)));
+
+        // Bit offsets skip leading bits, with the length updated
+        for offset in [1, 3, 7, 11, 23] {
+            let v: Vec<bool> = BitIter::new_with_offset(&buff, offset).collect();
+            let e: Vec<bool> = BitIter::new(&buff).skip(offset).collect();
+            assert_eq!(v, e);
+            assert_eq!(BitIter::new_with_offset(&buff, offset).len(), 24 - offset);
+
+            let v: Vec<bool> = BitIter::new_with_offset(&buff, offset).rev().collect();
+            let e: Vec<bool> = BitIter::new(&buff).skip(offset).rev().collect();
+            assert_eq!(v, e);
+        }
+
+        assert_eq!(BitIter::new_with_offset(&buff, 24).next(), None);
+        assert_eq!(BitIter::new_with_offset(&buff, 100).len(), 0);
+    }
+
+    #[test]
+    fn bit_iter_msb() {
+        let buff = [0b1000_0000, 0b0000_0011];
+        let v: Vec<bool> = BitIter::new_msb(&buff).collect();
+
+        assert_eq!(
+            &v,
+            &[
+                true, false, false, false, false, false, false, false, false, false, false, false,
+                false, false, true, true,
+            ]
+        );
+
+        // Spec example 0110011010 for the block frequency test
+        let buff = [0b0110_0110, 0b1000_0000];
+        let v: Vec<bool> = BitIter::new_msb(&buff).take(10).collect();
+        assert_eq!(
+            &v,
+            &[false, true, true, false, false, true, true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn chi_square_bytes_ok() {
+        let mut buff = [0u8; 4096];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        chi_square_bytes(&buff).expect("Chi-square test failed");
+    }
+
+    #[test]
+    fn chi_square_bytes_vector() {
+        let mut buff = [0u8; 4096];
+        lcg_fill(&mut buff, 11);
+
+        // Check statistic and p value match reference
+        let r = chi_square_bytes_result(&buff).unwrap();
+        assert_approx_eq!(r.statistic, 245.75, 1e-3);
+        assert_approx_eq!(r.p_value, 0.649896, 1e-4);
+
+        let r = chi_square_bytes_result(&buff[..CHI_SQUARE_MIN_BYTES]).unwrap();
+        assert_approx_eq!(r.statistic, 242.0, 1e-3);
+        assert_approx_eq!(r.p_value, 0.710922, 1e-4);
+    }
+
+    #[test]
+    fn chi_square_bytes_fail() {
+        // Uniform bits but only 16 distinct bytes
+        let mut buff = [0u8; 4096];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = [0x0f, 0xf0, 0x33, 0xcc, 0x55, 0xaa, 0x69, 0x96][i % 8];
+        }
+        chi_square_bytes(&buff).expect_err("Chi-square p > threshold");
+
+        assert_eq!(
+            chi_square_bytes(&buff[..CHI_SQUARE_MIN_BYTES - 1]),
+            Err(Error::InsufficientSampleSize(CHI_SQUARE_MIN_BYTES - 1))
+        );
+    }
+
+    #[test]
+    fn chi_square_nibbles() {
+        let mut buff = [0u8; 4096];
+        lcg_fill(&mut buff, 11);
+        let nibbles = || buff.iter().flat_map(|b| [b & 0x0f, b >> 4]);
+
+        // Check statistic and p value match reference, with 15 degrees of freedom
+        let r = chi_square_symbols_result(nibbles(), 16).unwrap();
+        assert_approx_eq!(r.statistic, 10.25, 1e-3);
+        assert_approx_eq!(r.p_value, 0.803726, 1e-4);
+
+        // Bytes match the byte-wise test
+        assert_eq!(
+            chi_square_symbols_result(buff.iter().copied(), 256),
+            chi_square_bytes_result(&buff)
+        );
+
+        // Samples must be within the alphabet, with 10 per symbol
+        assert_eq!(
+            chi_square_symbols(nibbles(), 4),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            chi_square_symbols(nibbles().take(159), 16),
+            Err(Error::InsufficientSampleSize(159))
+        );
+        chi_square_symbols(nibbles().map(|v| v & 0x03), 16).expect_err("Chi-square p > threshold");
+    }
+
+    #[test]
+    fn shannon_entropy() {
+        // Identical bytes carry no information
+        assert_eq!(shannon_entropy_bytes(&[0xa5; 1024]), 0.0);
+        assert_eq!(shannon_entropy_bytes(&[]), 0.0);
+
+        // Uniform histogram gives the maximum of 8 bits per byte
+        let mut buff = [0u8; 1024];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        assert_approx_eq!(shannon_entropy_bytes(&buff), 8.0, 1e-6);
+
+        // Two equally likely values give 1 bit per byte
+        assert_approx_eq!(shannon_entropy_bytes(&[0x00, 0xff, 0xff, 0x00]), 1.0, 1e-6);
+    }
+
+    #[test]
+    fn serial_correlation_coefficient() {
+        // Ramps are almost perfectly correlated, aside from the wrap
+        let ramp = (0..10_000).map(|i| i as f32 / 10_000.0);
+        assert_approx_eq!(serial_correlation(ramp), 1.0, 1e-3);
+
+        // Alternating values are perfectly anti-correlated
+        assert_approx_eq!(
+            serial_correlation((0..100).map(|i| (i % 2) as f32)),
+            -1.0,
+            1e-6
+        );
+
+        // Independent values give a coefficient near zero
+        let mut buff = [0u8; 4 * 3 * 10_000];
+        lcg_fill(&mut buff, 17);
+        let c = serial_correlation(UniformFloats::<_, f32>::new(BitIter::new(&buff)));
+        assert!(c.abs() < 0.02, "noise correlation {c}");
+
+        // Degenerate sequences
+        assert_eq!(serial_correlation(core::iter::empty()), 0.0);
+        assert_eq!(serial_correlation([0.5].into_iter()), 0.0);
+        assert_eq!(serial_correlation([0.5; 100].into_iter()), 0.0);
+        assert_eq!(serial_correlation([0.1; 100].into_iter()), 0.0);
+    }
+
+    #[test]
+    fn ks_uniform() {
+        // Reference sample with D = 0.25
+        let mut values = [0.9, 0.05, 0.6, 0.1, 0.35, 0.15, 0.7, 0.2, 0.45, 0.3];
+        assert_approx_eq!(ks_test(&mut values), 0.497342, 1e-5);
+        assert_eq!(values[0], 0.05);
+
+        // Uniform values pass
+        let mut buff = [0u8; 4 * 10_000];
+        lcg_fill(&mut buff, 19);
+        let mut values: Vec<f32> = UniformFloats::<_, f32>::new(BitIter::new(&buff)).collect();
+        let p = ks_test(&mut values);
+        assert!(p > 0.01, "uniform p {p}");
+
+        // Skewed values fail, with D = 0.26
+        let mut values: Vec<f32> = (0..100).map(|i| (i as f32 / 100.0).powi(2)).collect();
+        assert_approx_eq!(ks_test(&mut values), 1.881583e-6, 1e-8);
+
+        // Q(λ) is continuous across the change of series
+        assert_approx_eq!(kolmogorov_q(1.18 - 1e-9), kolmogorov_q(1.18), 1e-9);
+
+        assert_eq!(ks_test(&mut []), 1.0);
+    }
+
+    #[test]
+    fn lz_compression() {
+        // Random data is incompressible
+        let mut buff = [0u8; 16384];
+        rand::rngs::OsRng.fill_bytes(&mut buff);
+        let r = lz_compression_ratio(&buff);
+        assert!(r > 0.99, "random ratio {r}");
+
+        // Repeated bytes and patterns compress to few tokens
+        let r = lz_compression_ratio(&[0xa5; 16384]);
+        assert!(r < 0.01, "repeated ratio {r}");
+
+        let mut pattern = [0u8; 100];
+        rand::rngs::OsRng.fill_bytes(&mut pattern);
+        for c in buff.chunks_mut(100) {
+            c.copy_from_slice(&pattern[..c.len()]);
+        }
+        let r = lz_compression_ratio(&buff);
+        assert!(r < 0.02, "pattern ratio {r}");
+
+        // Repeats are found only within the window
+        rand::rngs::OsRng.fill_bytes(&mut buff);
+        buff.copy_within(..LZ_WINDOW / 2, LZ_WINDOW / 2);
+        let r = lz_compression_ratio(&buff[..LZ_WINDOW]);
+        assert!(r < 0.51, "window ratio {r}");
+
+        rand::rngs::OsRng.fill_bytes(&mut buff);
+        buff.copy_within(..LZ_WINDOW, LZ_WINDOW);
+        let r = lz_compression_ratio(&buff[..LZ_WINDOW * 2]);
+        assert!(r > 0.99, "outside window ratio {r}");
+
+        assert_eq!(lz_compression_ratio(&[]), 0.0);
+        assert_eq!(lz_compression_ratio(&[1, 2]), 1.0);
+    }
+
+    #[test]
+    fn per_position_bias_injected() {
+        let mut buff = [0u8; 4096];
+        lcg_fill(&mut buff, 71);
+
+        // Bit 5 forced high in three of four bytes, giving 0.75 + 0.25 * 0.5
+        buff.iter_mut()
+            .enumerate()
+            .filter(|(i, _)| i % 4 != 0)
+            .for_each(|(_, b)| *b |= 1 << 5);
+
+        let bias = per_position_bias(&buff);
+        assert_approx_eq!(bias[5], 0.875, 0.01);
+
+        // Other positions are unaffected, within 4 standard deviations (0.5 / 64) of 0.5
+        for (i, f) in bias.iter().enumerate().filter(|(i, _)| *i != 5) {
+            assert!((f - 0.5).abs() < 0.032, "bit {i}: {f}");
+        }
+
+        assert_eq!(
+            per_position_bias(&[0x01, 0x03, 0x80, 0x81]),
+            [0.75, 0.25, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5]
+        );
+        assert!(per_position_bias(&[]).iter().all(|f| f.is_nan()));
+    }
+
+    #[test]
+    fn stuck_bit_detection() {
+        let mut buff = [0u8; 4096];
+        lcg_fill(&mut buff, 61);
+
+        // Random data has no stuck positions or long byte runs
+        let r = stuck_bits(&buff);
+        assert!(!r.any_stuck(), "{r:?}");
+        assert!(r.longest_zero_run < 3 && r.longest_ones_run < 3, "{r:?}");
+
+        // Bit 3 stuck low, then high
+        let mut low = buff;
+        low.iter_mut().for_each(|b| *b &= !(1 << 3));
+        assert_eq!(stuck_bits(&low).stuck_low, 1 << 3);
+        assert_eq!(stuck_bits(&low).stuck_high, 0);
+
+        let mut high = buff;
+        high.iter_mut().for_each(|b| *b |= 1 << 3);
+        assert_eq!(stuck_bits(&high).stuck_high, 1 << 3);
+        assert_eq!(stuck_bits(&high).stuck(), 1 << 3);
+
+        // Bit 3 of 32-bit words is only stuck in the first byte of each word
+        let mut words = buff;
+        words.iter_mut().step_by(4).for_each(|b| *b |= 1 << 3);
+        assert!(!stuck_bits(&words).any_stuck());
+        let r = stuck_bits_words(&words, 4).unwrap();
+        assert_eq!((r.word_bits, r.stuck_high, r.stuck_low), (32, 1 << 3, 0));
+
+        // Longest constant byte runs, with partial words excluded from the masks
+        let mut runs = buff;
+        runs[100..110].fill(0x00);
+        runs[200..205].fill(0xff);
+        let r = stuck_bits_words(&runs[..4095], 8).unwrap();
+        assert_eq!((r.longest_zero_run, r.longest_ones_run), (10, 5));
+        assert!(!r.any_stuck());
+
+        let r = stuck_bits_words(&[0xff; 7], 8).unwrap();
+        assert_eq!((r.stuck(), r.longest_ones_run), (0, 7));
+        assert_eq!(
+            stuck_bits(&[]),
+            StuckBitReport {
+                word_bits: 8,
+                ..Default::default()
+            }
+        );
+        assert_eq!(stuck_bits_words(&buff, 0), Err(Error::InvalidParameter));
+        assert_eq!(stuck_bits_words(&buff, 9), Err(Error::InvalidParameter));
+    }
+
+    #[test]
+    fn poisson_values() {
+        // Reference values for λ = 4
+        assert_approx_eq!(poisson_pmf(0, 4.0), 0.018316, 1e-6);
+        assert_approx_eq!(poisson_pmf(4, 4.0), 0.195367, 1e-6);
+        assert_approx_eq!(poisson_pmf(10, 4.0), 0.005292, 1e-6);
+
+        assert_approx_eq!(poisson_cdf(2, 4.0), 0.238103, 1e-5);
+        assert_approx_eq!(poisson_cdf(4, 4.0), 0.628837, 1e-5);
+        assert_approx_eq!(poisson_cdf(10, 4.0), 0.997160, 1e-5);
+
+        // CDF matches the sum of the PMF
+        let sum = (0..=6).map(|k| poisson_pmf(k, 2.5)).sum::<f32>();
+        assert_approx_eq!(poisson_cdf(6, 2.5), sum, 1e-5);
+
+        assert_eq!(poisson_pmf(0, 0.0), 1.0);
+        assert_eq!(poisson_pmf(1, 0.0), 0.0);
+        assert_eq!(poisson_cdf(0, 0.0), 1.0);
+        assert!(poisson_pmf(1, -1.0).is_nan());
+        assert!(poisson_cdf(1, f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn normal_cdf_values() {
+        let tests = &[
+            (0.0, 0.5),
+            (1.0, 0.841_344_7),
+            (-1.0, 0.158_655_25),
+            (1.96, 0.975_002_1),
+            (-3.0, 0.001_349_9),
+        ];
+
+        for (x, p) in tests {
+            assert_approx_eq::assert_approx_eq!(normal_cdf(*x), *p, 1e-6);
+        }
+    }
+
+    #[test]
+    fn normal_quantile_values() {
+        assert_approx_eq!(normal_cdf(1.96), 0.975, 1e-4);
+        assert_approx_eq!(normal_quantile(0.975), 1.96, 1e-4);
+        assert_approx_eq!(normal_quantile(0.995), 2.575_829, 1e-5);
+        assert_approx_eq!(normal_quantile(0.5), 0.0, 1e-7);
+
+        // Round trips through the CDF, including the tails
+        for x in [-8.0, -3.0, -1.0, -0.1, 0.5, 2.0] {
+            assert_approx_eq!(normal_quantile_f64(normal_cdf_f64(x)), x, 1e-9);
+        }
+        assert_approx_eq!(normal_quantile_f64(normal_cdf_f64(6.0)), 6.0, 1e-6);
+
+        assert_eq!(normal_quantile(0.0), f32::NEG_INFINITY);
+        assert_eq!(normal_quantile(1.0), f32::INFINITY);
+        assert!(normal_quantile(1.5).is_nan());
+        assert!(normal_quantile(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn erfc_f64_values() {
+        let tests = &[
+            (0.0, 1.0),
+            (0.25, 0.7236736098317631),
+            (1.0, 0.15729920705028513),
+            (3.0, 2.2090496998585438e-05),
+            (4.0, 1.541725790028002e-08),
+            (5.0, 1.5374597944280351e-12),
+            (10.0, 2.088487583762545e-45),
+            (-1.0, 1.842700792949715),
+        ];
+
+        for (x, e) in tests {
+            let v = erfc_f64(*x);
+            assert!(
+                math::fabs(v - e) <= 1e-12 * e,
+                "erfc({x}) = {v}, expected {e}"
+            );
+        }
+
+        assert_eq!(erfc_f64(30.0), 0.0);
+        assert_eq!(erfc_f64(-30.0), 2.0);
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
     fn from_rng() {
         let bits: Vec<_> = BitsFromRng::new(&mut rand::rngs::OsRng, 123).collect();
         assert_eq!(bits.len(), 123);
     }
+
+    /// RNG counting calls to `next_u32` and `next_u64`
+    #[cfg(feature = "rand_core_0_6")]
+    #[derive(Default)]
+    struct CallCounter {
+        u32_calls: usize,
+        u64_calls: usize,
+    }
+
+    #[cfg(feature = "rand_core_0_6")]
+    impl rand_core_0_6::RngCore for CallCounter {
+        fn next_u32(&mut self) -> u32 {
+            self.u32_calls += 1;
+            0xa5a5_a5a5
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.u64_calls += 1;
+            0xa5a5_a5a5_a5a5_a5a5
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            rand_core_0_6::impls::fill_bytes_via_next(self, dst)
+        }
+
+        fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), rand_core_0_6::Error> {
+            self.fill_bytes(dst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
+    fn from_rng_64() {
+        // 64-bit buffering produces the same bits with half the calls
+        let mut a = CallCounter::default();
+        let mut b = CallCounter::default();
+
+        let bits_32: Vec<_> = BitsFromRng::new(&mut a, 1000).collect();
+        let bits_64: Vec<_> = BitsFromRng64::new(&mut b, 1000).collect();
+
+        assert_eq!(bits_32, bits_64);
+        assert_eq!((a.u32_calls, a.u64_calls), (32, 0));
+        assert_eq!((b.u32_calls, b.u64_calls), (0, 16));
+    }
+
+    /// RNG failing after a number of successful calls
+    #[cfg(feature = "rand_core_0_6")]
+    struct FailAfter(usize);
+
+    #[cfg(feature = "rand_core_0_6")]
+    impl rand_core_0_6::RngCore for FailAfter {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(0)
+        }
+
+        fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), rand_core_0_6::Error> {
+            if self.0 == 0 {
+                return Err(
+                    core::num::NonZeroU32::new(rand_core_0_6::Error::CUSTOM_START)
+                        .unwrap()
+                        .into(),
+                );
+            }
+            self.0 -= 1;
+
+            dst.fill(0xa5);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
+    fn try_from_rng() {
+        // Bits match the infallible iterator
+        let mut rng = FailAfter(10);
+        let bits: Result<Vec<_>, _> = TryBitsFromRng::new(&mut rng, 64).collect();
+        assert_eq!(
+            bits.unwrap(),
+            [true, false, true, false, false, true, false, true].repeat(8)
+        );
+
+        // Failures end iteration
+        let mut rng = FailAfter(2);
+        let bits: Vec<_> = TryBitsFromRng::new(&mut rng, 100).collect();
+        assert_eq!(bits.len(), 65);
+        assert_eq!(bits[64], Err(Error::RngFailed));
+    }
+
+    #[test]
+    fn try_from_bytes() {
+        #[derive(Debug, PartialEq)]
+        struct HwError;
+
+        // Bits match the infallible iterator
+        let buff = [0xa5u8, 0x3c, 0x0f];
+        let bits: Result<Vec<_>, HwError> =
+            TryBitsFromBytes::new(buff.iter().map(|b| Ok(*b))).collect();
+        assert_eq!(bits.unwrap(), BitIter::new(&buff).collect::<Vec<_>>());
+
+        // Source errors are passed through and end iteration
+        let source = [Ok(0xff), Err(HwError), Ok(0x00)].into_iter();
+        let bits: Vec<_> = TryBitsFromBytes::new(source).collect();
+        assert_eq!(bits.len(), 9);
+        assert_eq!(bits[8], Err(HwError));
+    }
+
+    #[test]
+    fn try_bits_from_bytes_test() {
+        use crate::nist::nist_runs;
+
+        #[derive(Debug, PartialEq)]
+        struct HwError;
+
+        let mut buff = [0u8; 1024];
+        lcg_fill(&mut buff, 1);
+
+        // A mid-stream fault aborts the test
+        let source = buff
+            .iter()
+            .enumerate()
+            .map(|(i, b)| if i == 500 { Err(HwError) } else { Ok(*b) });
+        assert_eq!(
+            try_bits_from_bytes(source, |b| {
+                let r = nist_runs(&mut *b);
+                assert_eq!(b.error(), Some(&HwError));
+                r
+            }),
+            Err(Error::RngFailed)
+        );
+
+        // Otherwise results are passed through
+        let source = buff.iter().map(|b| Ok::<_, HwError>(*b));
+        assert_eq!(
+            try_bits_from_bytes(source, |b| nist_runs(b)),
+            nist_runs(BitIter::new(&buff))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
+    fn try_bits_test() {
+        use crate::nist::nist_freq_monobit;
+
+        // Errors take precedence over test results
+        let mut rng = FailAfter(2);
+        assert_eq!(
+            try_bits(TryBitsFromRng::new(&mut rng, 1024), |b| nist_freq_monobit(
+                b
+            )),
+            Err(Error::RngFailed)
+        );
+
+        // Otherwise results are passed through
+        let mut rng = rand::rngs::OsRng;
+        let bits: Vec<_> = BitsFromRng::new(&mut rng, 1024).collect();
+        let p = try_bits(bits.iter().map(|b| Ok(*b)), |b| nist_freq_monobit(b));
+        assert_eq!(p, nist_freq_monobit(bits.iter().copied()));
+    }
+
+    #[test]
+    fn bit_buffer() {
+        let mut buff = [0u8; 16];
+        lcg_fill(&mut buff, 9);
+
+        // Filling from a BitIter round-trips the bytes and bit order
+        let mut b = BitBuffer::<16>::new();
+        for v in BitIter::new(&buff) {
+            assert!(!b.is_full());
+            assert!(b.push(v));
+        }
+        assert!(b.is_full());
+        assert!(!b.push(true));
+
+        assert_eq!(b.as_bytes(), &buff);
+        assert!(b.iter().eq(BitIter::new(&buff)));
+        assert_eq!(b.iter().len(), 128);
+
+        // Partial bytes iterate only the collected bits, in either direction
+        let mut b = BitBuffer::<16>::with_capacity(13).unwrap();
+        BitIter::new(&buff).take(20).for_each(|v| {
+            b.push(v);
+        });
+        assert_eq!(b.len(), 13);
+        assert!(b.iter().eq(BitIter::new(&buff).take(13)));
+        assert!(b.iter().rev().eq(BitIter::new(&buff).take(13).rev()));
+        assert_eq!(b.as_bytes(), &[buff[0], buff[1] & 0x1f]);
+
+        b.clear();
+        assert!(b.is_empty());
+        assert_eq!(b.iter().next(), None);
+
+        assert_eq!(
+            BitBuffer::<16>::with_capacity(129),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn as_bits() {
+        let mut buff = [0u8; 32];
+        lcg_fill(&mut buff, 5);
+
+        let mut bools = [false; 256];
+        bools
+            .iter_mut()
+            .zip(BitIter::new(&buff))
+            .for_each(|(b, v)| *b = v);
+
+        // Iterators pass through, with slices and arrays yielding their values
+        assert!(BitIter::new(&buff).into_bits().eq(BitIter::new(&buff)));
+        assert!(bools[..].into_bits().eq(BitIter::new(&buff)));
+        assert!(bools.into_bits().rev().eq(BitIter::new(&buff).rev()));
+        assert_eq!(bools[..].into_bits().len(), 256);
+
+        // Each may be passed directly to the tests
+        let r = crate::nist::nist_freq_monobit_result(BitIter::new(&buff));
+        assert_eq!(crate::nist::nist_freq_monobit_result(&bools[..]), r);
+        assert_eq!(crate::nist::nist_freq_monobit_result(bools), r);
+    }
+
+    #[test]
+    #[cfg(feature = "bitvec")]
+    fn as_bits_bitvec() {
+        use bitvec::prelude::*;
+
+        let mut buff = [0u8; 32];
+        lcg_fill(&mut buff, 5);
+
+        let slice = buff.view_bits::<Lsb0>();
+        let vec = slice.to_bitvec();
+        assert!(slice.into_bits().eq(BitIter::new(&buff)));
+        assert!((&vec).into_bits().eq(BitIter::new(&buff)));
+
+        let r = crate::nist::nist_freq_monobit_result(BitIter::new(&buff));
+        assert_eq!(crate::nist::nist_freq_monobit_result(slice), r);
+        assert_eq!(crate::nist::nist_freq_monobit_result(&vec), r);
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
+    fn test_rng() {
+        use rand_core_0_6::RngCore;
+
+        // Reference values from xorshift64* seeded via SplitMix64
+        let mut rng = TestRng::seed(0);
+        assert_eq!(rng.next_u64(), 0x7bbc_b40d_5506_82d0);
+        assert_eq!(rng.next_u64(), 0xde7f_e413_d00c_c9fd);
+        assert_eq!(rng.next_u32(), 0xb3c6_3835);
+
+        // Identical seeds give identical streams
+        let (mut a, mut b) = (TestRng::seed(42), TestRng::seed(42));
+        let (mut x, mut y) = ([0u8; 37], [0u8; 37]);
+        a.fill_bytes(&mut x);
+        b.fill_bytes(&mut y);
+        assert_eq!(x, y);
+        assert_ne!(TestRng::seed(43).next_u64(), TestRng::seed(42).next_u64());
+
+        // Output passes the tests it is used to exercise
+        let mut rng = TestRng::seed(1);
+        crate::nist::nist_freq_monobit(BitsFromRng::new(&mut rng, 100_000)).unwrap();
+        crate::nist::nist_runs(BitsFromRng::new(&mut rng, 100_000)).unwrap();
+    }
+
+    #[test]
+    fn bit_ring() {
+        let mut buff = [0u8; 8];
+        lcg_fill(&mut buff, 9);
+        let bits = || BitIter::new(&buff);
+
+        // Partially filled rings hold every bit pushed
+        let mut r = BitRing::<2>::with_capacity(13).unwrap();
+        for v in bits().take(5) {
+            assert_eq!(r.push(v), None);
+        }
+        assert_eq!(r.len(), 5);
+        assert!(r.iter().eq(bits().take(5)));
+
+        // Once full pushes overwrite the oldest bit, with the most recent bits iterated
+        // oldest first across the wrap
+        for (k, v) in bits().enumerate().skip(5) {
+            let old = r.push(v);
+            match k < 13 {
+                true => assert_eq!(old, None),
+                false => assert_eq!(old, bits().nth(k - 13)),
+            }
+
+            let recent = || bits().take(k + 1).skip((k + 1).saturating_sub(13));
+            assert!(r.iter().eq(recent()));
+            assert!(r.iter().rev().eq(recent().rev()));
+        }
+        assert!(r.is_full());
+        assert_eq!(r.iter().len(), 13);
+
+        r.clear();
+        assert!(r.is_empty());
+        assert_eq!(r.iter().next(), None);
+
+        assert_eq!(BitRing::<2>::new().capacity(), 16);
+        assert_eq!(
+            BitRing::<2>::with_capacity(17),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(BitRing::<2>::with_capacity(0), Err(Error::InvalidParameter));
+    }
+
+    #[test]
+    fn uniform_floats() {
+        let mut buff = [0u8; 3 * 4096];
+        lcg_fill(&mut buff, 3);
+
+        // Values lie in [0, 1) with mean ~0.5
+        let values: Vec<f32> = UniformFloats::new(BitIter::new(&buff)).collect();
+        assert_eq!(values.len(), 4096);
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        assert_approx_eq!(mean, 0.5, 0.02);
+
+        // As for f64, with the partial value at the end discarded
+        let values: Vec<f64> = UniformFloats::new_f64(BitIter::new(&buff)).collect();
+        assert_eq!(values.len(), 3 * 4096 * 8 / 53);
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        assert_approx_eq!(mean, 0.5, 0.02);
+
+        // Bits are packed first bit lowest
+        let mut values = UniformFloats::new(BitIter::new([0x00, 0x00, 0x80, 0xff, 0xff, 0xff]));
+        assert_eq!(values.next(), Some(0.5));
+        assert_eq!(values.next(), Some(1.0 - f32::EPSILON / 2.0));
+        assert_eq!(values.next(), None);
+    }
+
+    #[test]
+    fn words_from_bits() {
+        let mut buff = [0u8; 96];
+        lcg_fill(&mut buff, 5);
+
+        // LSB first round-trips as little endian
+        let mut words = WordsFromBits::new(BitIter::new(&buff));
+        let mut out = Vec::new();
+        for _ in 0..8 {
+            out.extend_from_slice(&words.next_u32().unwrap().to_le_bytes());
+            out.extend_from_slice(&words.next_u64().unwrap().to_le_bytes());
+        }
+        assert_eq!(out, buff);
+        assert_eq!(words.next_u32(), None);
+
+        // MSB first round-trips as big endian
+        let mut words = WordsFromBits::new_msb(BitIter::new_msb(&buff));
+        let mut out = Vec::new();
+        while let Some(w) = words.next_u64() {
+            out.extend_from_slice(&w.to_be_bytes());
+        }
+        assert_eq!(out, buff);
+
+        // Partial words are discarded
+        let mut words = WordsFromBits::new(BitIter::new([0x12, 0x34, 0x56]));
+        assert_eq!(words.next_bits(12), Some(0x412));
+        assert_eq!(words.next_u32(), None);
+        assert_eq!(words.into_inner().next(), None);
+    }
+
+    #[test]
+    fn words_from_bits_packing() {
+        let buff = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+        let reversed = buff.map(u8::reverse_bits);
+
+        // Words from LSB first bits under each packing
+        let cases = [
+            (BitOrder::Lsb, ByteOrder::Little, [0x6745_2301, 0xefcd_ab89]),
+            (BitOrder::Lsb, ByteOrder::Big, [0x0123_4567, 0x89ab_cdef]),
+            (BitOrder::Msb, ByteOrder::Little, [0xe6a2_c480, 0xf7b3_d591]),
+            (BitOrder::Msb, ByteOrder::Big, [0x80c4_a2e6, 0x91d5_b3f7]),
+        ];
+        for (bit_order, byte_order, expected) in cases {
+            let packing = BitPacking::new(bit_order, byte_order);
+            let mut words = WordsFromBits::with_packing(BitIter::new(&buff), packing);
+            assert_eq!([words.next_u32(), words.next_u32()], expected.map(Some));
+        }
+
+        // Matching the equivalent conversions from bytes
+        let words = |packing| {
+            let mut words = WordsFromBits::with_packing(BitIter::new(&buff), packing);
+            words.next_u64().unwrap()
+        };
+        let packing = BitPacking::new;
+        assert_eq!(
+            words(packing(BitOrder::Lsb, ByteOrder::Little)),
+            u64::from_le_bytes(buff)
+        );
+        assert_eq!(
+            words(packing(BitOrder::Lsb, ByteOrder::Big)),
+            u64::from_be_bytes(buff)
+        );
+        assert_eq!(
+            words(packing(BitOrder::Msb, ByteOrder::Little)),
+            u64::from_le_bytes(reversed)
+        );
+        assert_eq!(
+            words(packing(BitOrder::Msb, ByteOrder::Big)),
+            u64::from_be_bytes(reversed)
+        );
+
+        // Defaults and bit orders match the existing constructors
+        assert_eq!(BitPacking::default(), BitOrder::Lsb.into());
+        let mut words = WordsFromBits::with_packing(BitIter::new(&buff), BitOrder::Msb.into());
+        let mut msb = WordsFromBits::new_msb(BitIter::new(&buff));
+        assert_eq!(words.next_bits(12), msb.next_bits(12));
+        assert_eq!(words.next_bits(20), msb.next_bits(20));
+    }
+
+    /// Counting RNG for `rand_core` 0.9, as `rand` 0.8 provides 0.6 RNGs
+    #[cfg(feature = "rand_core_0_9")]
+    struct Counter09(u32);
+
+    #[cfg(feature = "rand_core_0_9")]
+    impl rand_core_0_9::RngCore for Counter09 {
+        fn next_u32(&mut self) -> u32 {
+            self.0 += 1;
+            self.0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core_0_9::impls::next_u64_via_u32(self)
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            rand_core_0_9::impls::fill_bytes_via_next(self, dst)
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_9")]
+    fn from_rng_0_9() {
+        let mut rng = Counter09(0);
+        let bits: Vec<_> = BitsFromRng09::new(&mut rng, 40).collect();
+        assert_eq!(bits.len(), 40);
+
+        // 1, then 2 LSB first
+        assert_eq!(&bits[..3], &[true, false, false]);
+        assert_eq!(&bits[32..35], &[false, true, false]);
+
+        // 64-bit buffering combines these as (2 << 32) | 1
+        let mut rng = Counter09(0);
+        let bits_64: Vec<_> = BitsFromRng64_09::new(&mut rng, 40).collect();
+        assert_eq!(bits, bits_64);
+
+        // Infallible RNGs also provide TryRngCore
+        let mut rng = Counter09(0);
+        let bits_try: Result<Vec<_>, _> = TryBitsFromRng09::new(&mut rng, 40).collect();
+        assert_eq!(bits, bits_try.unwrap());
+    }
 }
@@ -1,25 +1,390 @@
 //! A helper crate for testing the operation of cryptographic random number generators
 //!
 //! This provides a subset of tests from NIST 800-22 and the Diehard / Dieharder suites
-//! suitable for runtime testing of RNGs.
+//! suitable for runtime testing of RNGs, as well as the BSI AIS 31 procedure A tests in
+//! [ais31].
 //!
 //! NOTE: This is a an incomplete and broadly untested implementation, to be extended as is useful / required.
 //! If we're missing a test that would be useful, please feel free to open an issue or PR!
+//!
+//! ## Features
+//!
+//! The default build is `no_std` without `alloc`, depending only on `libm` and `rand_core`.
+//!
+//! - `rand_core_0_6` (default) / `rand_core_0_9` provide bit iterators over RNGs, a
+//!   deterministic [helpers::TestRng] for reproducible examples, and the SP 800-90B IID
+//!   permutation test shuffled with an RNG
+//! - `double` computes the incomplete gamma function, monobit, block frequency, and cusum
+//!   statistics and P-values in f64, still returning f32 results
+//! - `fixed` provides integer pass / fail variants of the monobit, runs, and
+//!   autocorrelation tests for targets without an FPU, such as
+//!   `nist::nist_freq_monobit_passfail`
+//! - `special-gamma` uses the `special` crate for the incomplete gamma function
+//! - `bitvec` accepts `bitvec` slices and vectors as inputs to the NIST tests, see
+//!   [helpers::AsBits]
+//! - `serde` / `defmt` derive serialisation and formatting for errors and results
+//! - `error_in_core` implements `core::error::Error` for [Error]
+//! - `rayon` provides a parallel suite runner, requiring `std`
+//! - `simd` uses AVX2 where detected at runtime to count ones, requiring `std`
+//! - `std-math` uses the `std` floating point functions in place of `libm` where these
+//!   exist, which may be faster on hosted targets, requiring `std`. Results agree with
+//!   the default to within rounding.
+//!
+//! No feature requires `alloc` without `std`.
 
 #![no_std]
+#![cfg_attr(not(feature = "simd"), forbid(unsafe_code))]
+#![cfg_attr(feature = "simd", deny(unsafe_code))]
 
+#[cfg(any(feature = "rayon", feature = "simd", feature = "std-math"))]
+extern crate std;
+
+pub mod ais31;
+pub mod diehard;
+pub mod fips;
 pub mod helpers;
 pub mod nist;
+pub mod sp800_90b;
+
+mod math;
+mod popcount;
 
 /// Test errors
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Error {
     /// RNG Failed
     RngFailed,
 
-    /// Insufficient sample size
+    /// Insufficient sample size, with the amount of input (in bits or samples) consumed
+    ///
+    /// All tests return `InsufficientSampleSize(0)` for empty input, once any parameters
+    /// have been validated.
     InsufficientSampleSize(usize),
 
-    /// P-value outside required bounds
-    BadPValue(f32),
+    /// P-value outside required bounds, with the test statistic from which it was computed
+    ///
+    /// The statistic distinguishes marginal from gross failures, and is NaN where none is
+    /// computed (such as for invalid P-values passed to [nist::aggregate]).
+    BadPValue {
+        /// P-value
+        p: f32,
+
+        /// Test statistic, see [TestResult::statistic]
+        statistic: f32,
+    },
+
+    /// Invalid test parameter
+    InvalidParameter,
+
+    /// Significance level outside (0, 1)
+    InvalidAlpha(f32),
+
+    /// Block matches a previous block, see [fips::ContinuousRngTest] and
+    /// [ais31::t0_disjointness]
+    RepeatedBlock,
+
+    /// Online health test failure, see [sp800_90b] and [ais31]
+    HealthTestFailed,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::RngFailed => write!(f, "RNG failed"),
+            Error::InsufficientSampleSize(n) => write!(f, "insufficient sample size ({n})"),
+            Error::BadPValue { p, statistic } => {
+                write!(
+                    f,
+                    "P-value {p} outside required bounds (statistic {statistic})"
+                )
+            }
+            Error::InvalidParameter => write!(f, "invalid test parameter"),
+            Error::InvalidAlpha(a) => write!(f, "significance level {a} outside (0, 1)"),
+            Error::RepeatedBlock => write!(f, "block matches the previous block"),
+            Error::HealthTestFailed => write!(f, "online health test failure"),
+        }
+    }
+}
+
+/// [core::error::Error] requires Rust 1.81, so is gated to retain support for older
+/// toolchains
+#[cfg(feature = "error_in_core")]
+impl core::error::Error for Error {}
+
+/// Default significance level (α) for tests
+pub const DEFAULT_ALPHA: f32 = 0.01;
+
+/// Test result, containing the P-value and test statistic
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TestResult {
+    /// P-value for the test
+    pub p_value: f32,
+
+    /// Test statistic from which the P-value is computed
+    pub statistic: f32,
+
+    /// Whether the P-value meets the significance level, [DEFAULT_ALPHA] unless otherwise
+    /// specified
+    pub passed: bool,
+}
+
+impl TestResult {
+    /// Create a new result from a P-value and test statistic at [DEFAULT_ALPHA]
+    ///
+    /// NaN P-values do not compare as greater than the threshold so are rejected rather than passed
+    pub(crate) fn new(p_value: f32, statistic: f32) -> Self {
+        Self {
+            p_value,
+            statistic,
+            passed: p_value >= DEFAULT_ALPHA,
+        }
+    }
+
+    /// Re-evaluate the result at significance level `alpha`, which must be within (0, 1)
+    pub fn at_alpha(self, alpha: f32) -> Result<Self, Error> {
+        // Inverted to reject NaN
+        if !(alpha > 0.0 && alpha < 1.0) {
+            return Err(Error::InvalidAlpha(alpha));
+        }
+
+        Ok(Self {
+            passed: self.p_value >= alpha,
+            ..self
+        })
+    }
+
+    /// Fetch the P-value for a passed test, returning [Error::BadPValue] with the P-value and
+    /// statistic for failures
+    pub fn check(&self) -> Result<f32, Error> {
+        match self.passed {
+            true => Ok(self.p_value),
+            false => Err(Error::BadPValue {
+                p: self.p_value,
+                statistic: self.statistic,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_result_check() {
+        let r = TestResult::new(0.5, 1.0);
+        assert!(r.passed);
+        assert_eq!(r.check(), Ok(0.5));
+
+        let r = TestResult::new(0.001, 1.0);
+        assert!(!r.passed);
+        assert_eq!(
+            r.check(),
+            Err(Error::BadPValue {
+                p: 0.001,
+                statistic: 1.0
+            })
+        );
+
+        // NaN P-values are rejected
+        let r = TestResult::new(f32::NAN, 1.0);
+        assert!(!r.passed);
+        assert!(r.check().is_err());
+    }
+
+    #[test]
+    fn test_result_alpha() {
+        let r = TestResult::new(0.005, 1.0);
+        assert!(!r.passed);
+
+        assert!(r.at_alpha(0.001).unwrap().passed);
+        assert!(!r.at_alpha(0.05).unwrap().passed);
+
+        for alpha in [0.0, 1.0, -0.5, 1.5, f32::NAN] {
+            assert!(matches!(r.at_alpha(alpha), Err(Error::InvalidAlpha(_))));
+        }
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            Error::BadPValue {
+                p: 0.004,
+                statistic: 12.5
+            }
+            .to_string(),
+            "P-value 0.004 outside required bounds (statistic 12.5)"
+        );
+        assert_eq!(
+            Error::InsufficientSampleSize(100).to_string(),
+            "insufficient sample size (100)"
+        );
+    }
+
+    /// Every public test should be covered here, see [Error::InsufficientSampleSize]
+    #[test]
+    fn empty_input() {
+        use diehard::*;
+        use nist::{
+            aggregate::{anderson_darling, proportion, uniformity},
+            *,
+        };
+        use sp800_90b::*;
+        use test_utils::assert_empty_input;
+
+        let bits = core::iter::empty::<bool>;
+        let mut scratch = [0f32; 64];
+        let mut table = [0u32; nist_universal_table_len(6)];
+        let mut monkey = [0u8; diehard::monkey::MONKEY_SCRATCH_LEN];
+
+        // NIST tests
+        assert_empty_input(nist_freq_monobit(bits()));
+        assert_empty_input(nist_freq_monobit_bytes(&[]));
+        assert_empty_input(nist_freq_monobit_biased(bits(), 0.6));
+        assert_empty_input(nist_freq_block(bits(), 20));
+        assert_empty_input(nist_runs(bits()));
+        assert_empty_input(nist_longest_run(bits()));
+        assert_empty_input(nist_longest_run_custom(bits(), 8, &[1, 2, 3], &[0.25; 4]));
+        assert_empty_input(nist_matrix_rank(bits(), 32, 32));
+        assert_empty_input(nist_dft(bits(), &mut scratch));
+        assert_empty_input(nist_dft_with_options(
+            bits(),
+            &mut scratch,
+            DftOptions::new(PadMode::ZeroPad, WindowFn::Hann),
+        ));
+        assert_empty_input(nist_non_overlapping_template(
+            bits(),
+            &[false, false, true],
+            100,
+        ));
+        assert_empty_input(nist_overlapping_template(bits(), 9, 1032));
+        assert_empty_input(nist_universal(helpers::BitIter::new([]), &mut table));
+        assert_empty_input(nist_universal_with_params(bits(), 6, 640, &mut table));
+        assert_empty_input(nist_linear_complexity(bits(), 500));
+        assert_empty_input(nist_serial(bits(), 8));
+        assert_empty_input(nist_approx_entropy(bits(), 6));
+        assert_empty_input(nist_cusum(bits(), CusumMode::Forward));
+        assert_empty_input(nist_random_excursions(bits()));
+        assert_empty_input(nist_random_excursions_variant(bits()));
+        assert_empty_input(autocorrelation(bits(), 1));
+        #[cfg(feature = "fixed")]
+        {
+            assert_empty_input(nist_freq_monobit_passfail(bits(), 0.01));
+            assert_empty_input(nist_runs_passfail(bits(), 0.01));
+            assert_empty_input(autocorrelation_passfail(bits(), 1, 0.01));
+        }
+        assert_empty_input(uniformity(&[]));
+        assert_empty_input(anderson_darling(&mut []));
+        assert_empty_input(proportion(&[], 0.01));
+
+        let report = run_suite(&[], &SuiteConfig::default());
+        report
+            .iter()
+            .for_each(|(_, r)| assert_empty_input(r.clone()));
+
+        // Diehard tests
+        assert_empty_input(birthday_spacings(bits(), 24, 512));
+        assert_empty_input(count_the_ones_stream(&[]));
+        assert_empty_input(craps(bits(), 1));
+        assert_empty_input(gap_test(core::iter::empty(), 0.0, 0.5));
+        assert_empty_input(minimum_distance(bits(), 100));
+        assert_empty_input(overlapping_permutations(core::iter::empty()));
+        assert_empty_input(monkey::opso(bits(), 0, &mut monkey));
+        assert_empty_input(monkey::oqso(bits(), 0, &mut monkey));
+        assert_empty_input(monkey::dna(bits(), 0, &mut monkey));
+        assert_empty_input(overlapping_sums(core::iter::empty()));
+        assert_empty_input(parking_lot(bits(), 1));
+        assert_empty_input(poker_test(&[], 5, 10));
+        assert_empty_input(runs_up_down(core::iter::empty()));
+        assert_empty_input(spheres_3d(bits()));
+        assert_empty_input(squeeze(bits(), 1));
+
+        // SP 800-90B estimates and helpers
+        let symbols = core::iter::empty::<u8>;
+        assert_empty_input(most_common_value(&[]));
+        assert_empty_input(most_common_value_symbols(symbols(), 16));
+        assert_empty_input(markov_estimate(bits()));
+        assert_empty_input(collision_estimate(&[]));
+        assert_empty_input(collision_estimate_symbols(symbols(), 16));
+        assert_empty_input(t_tuple_estimate(&[], &mut [0u32; tuple_scratch_len(0)]));
+        assert_empty_input(lrs_estimate(&[], &mut [0u32; tuple_scratch_len(0)]));
+        #[cfg(feature = "rand_core_0_6")]
+        assert_empty_input(iid_permutation_test(&[], &mut [], &mut rand::rngs::OsRng));
+        assert_empty_input(helpers::chi_square_bytes(&[]));
+        assert_empty_input(helpers::chi_square_symbols(symbols(), 16));
+
+        // AIS 31 tests
+        let mut words = std::vec![0u64; ais31::T0_WORDS];
+        assert_empty_input(ais31::t0_disjointness(&[], &mut words));
+        assert_empty_input(ais31::t1_monobit(&[]));
+        assert_empty_input(ais31::t2_poker(&[]));
+        assert_empty_input(ais31::t3_runs(&[]));
+        assert_empty_input(ais31::t4_long_run(&[]));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let errors = [
+            Error::RngFailed,
+            Error::InsufficientSampleSize(100),
+            Error::BadPValue {
+                p: 0.004,
+                statistic: 12.5,
+            },
+            Error::InvalidAlpha(1.5),
+        ];
+        for e in errors {
+            let s = serde_json::to_string(&e).unwrap();
+            assert_eq!(serde_json::from_str::<Error>(&s).unwrap(), e);
+        }
+
+        let r = TestResult::new(0.123456, 7.5);
+        let s = serde_json::to_string(&r).unwrap();
+        assert_eq!(serde_json::from_str::<TestResult>(&s).unwrap(), r);
+    }
+
+    #[test]
+    #[cfg(feature = "error_in_core")]
+    fn error_in_core() {
+        use std::boxed::Box;
+
+        let e: Box<dyn core::error::Error> = Box::new(Error::RngFailed);
+        assert_eq!(e.to_string(), "RNG failed");
+    }
+}
+
+/// Shared helpers for unit tests
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::Error;
+
+    /// Assert a test rejects empty input with `InsufficientSampleSize(0)`
+    #[track_caller]
+    pub fn assert_empty_input<T: core::fmt::Debug>(r: Result<T, Error>) {
+        assert!(
+            matches!(r, Err(Error::InsufficientSampleSize(0))),
+            "Unexpected result for empty input: {r:?}"
+        );
+    }
+
+    /// Fill a buffer using a simple LCG, for reproducible test vectors
+    ///
+    /// Bytes are the top 8 bits of `x = x * 1664525 + 1013904223 (mod 2^32)`,
+    /// allowing reference values to be computed with other tools.
+    pub fn lcg_fill(buff: &mut [u8], seed: u32) {
+        let mut x = seed;
+
+        for b in buff.iter_mut() {
+            x = x.wrapping_mul(1664525).wrapping_add(1013904223);
+            *b = (x >> 24) as u8;
+        }
+    }
 }
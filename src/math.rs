@@ -0,0 +1,135 @@
+//! Floating point functions used by the tests
+//!
+//! These use `libm` by default, or with the `std-math` feature the `std` implementations
+//! where these exist, which may be faster on hosted targets. The error and log-gamma
+//! functions have no `std` equivalent so always use `libm`.
+
+pub(crate) use libm::{erfc, erfcf, erff, lgamma, lgammaf};
+
+/// Define math functions with a `libm` and `std` implementation
+macro_rules! math_fns {
+    ($($name:ident($($arg:ident),+): $t:ty => $ret:ty, $method:ident;)*) => {
+        $(
+            #[inline]
+            pub(crate) fn $name($($arg: $t),+) -> $ret {
+                #[cfg(feature = "std-math")]
+                return math_fns!(@std $t, $method, $($arg),+);
+
+                #[cfg(not(feature = "std-math"))]
+                return libm::$name($($arg),+);
+            }
+        )*
+    };
+    (@std $t:ty, $method:ident, $x:ident) => {
+        <$t>::$method($x)
+    };
+    (@std $t:ty, $method:ident, $x:ident, $y:ident) => {
+        <$t>::$method($x, $y)
+    };
+}
+
+math_fns! {
+    ceilf(x): f32 => f32, ceil;
+    cos(x): f64 => f64, cos;
+    exp(x): f64 => f64, exp;
+    exp2(x): f64 => f64, exp2;
+    expf(x): f32 => f32, exp;
+    fabs(x): f64 => f64, abs;
+    fabsf(x): f32 => f32, abs;
+    floor(x): f64 => f64, floor;
+    hypotf(x, y): f32 => f32, hypot;
+    log(x): f64 => f64, ln;
+    log1p(x): f64 => f64, ln_1p;
+    log2(x): f64 => f64, log2;
+    log2f(x): f32 => f32, log2;
+    logf(x): f32 => f32, ln;
+    pow(x, y): f64 => f64, powf;
+    powf(x, y): f32 => f32, powf;
+    sqrt(x): f64 => f64, sqrt;
+    sqrtf(x): f32 => f32, sqrt;
+    trunc(x): f64 => f64, trunc;
+}
+
+/// Sine and cosine of `x`
+#[inline]
+pub(crate) fn sincos(x: f64) -> (f64, f64) {
+    #[cfg(feature = "std-math")]
+    return x.sin_cos();
+
+    #[cfg(not(feature = "std-math"))]
+    return libm::sincos(x);
+}
+
+/// Sine and cosine of `x`
+#[inline]
+pub(crate) fn sincosf(x: f32) -> (f32, f32) {
+    #[cfg(feature = "std-math")]
+    return x.sin_cos();
+
+    #[cfg(not(feature = "std-math"))]
+    return libm::sincosf(x);
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    /// Check values agree to within `ulps` units in the last place
+    fn check_f64(name: &str, a: f64, b: f64, ulps: u64) {
+        let d = (a.to_bits() as i64).abs_diff(b.to_bits() as i64);
+        assert!(a == b || d <= ulps, "{name}: {a} != {b} ({d} ulps)");
+    }
+
+    fn check_f32(name: &str, a: f32, b: f32, ulps: u32) {
+        let d = (a.to_bits() as i32).abs_diff(b.to_bits() as i32);
+        assert!(a == b || d <= ulps, "{name}: {a} != {b} ({d} ulps)");
+    }
+
+    #[test]
+    fn backends_agree() {
+        // Compare the backends directly, so this checks agreement whichever is selected.
+        // Both are correctly rounded or close to this, so agree exactly for sqrt and the
+        // rounding functions and within an ulp otherwise
+        for i in 1..1000 {
+            let x = i as f64 * 0.37;
+            let y = i as f64 * -0.011;
+            let (xf, yf) = (x as f32, y as f32);
+
+            check_f64("sqrt", libm::sqrt(x), x.sqrt(), 0);
+            check_f32("sqrtf", libm::sqrtf(xf), xf.sqrt(), 0);
+            check_f64("floor", libm::floor(x), x.floor(), 0);
+            check_f64("trunc", libm::trunc(x), x.trunc(), 0);
+            check_f32("ceilf", libm::ceilf(xf), xf.ceil(), 0);
+            check_f64("fabs", libm::fabs(y), y.abs(), 0);
+            check_f32("fabsf", libm::fabsf(yf), yf.abs(), 0);
+
+            check_f64("exp", libm::exp(y), y.exp(), 1);
+            check_f64("exp2", libm::exp2(y), y.exp2(), 1);
+            check_f32("expf", libm::expf(yf), yf.exp(), 1);
+            check_f64("log", libm::log(x), x.ln(), 1);
+            check_f64("log1p", libm::log1p(x), x.ln_1p(), 1);
+            check_f64("log2", libm::log2(x), x.log2(), 1);
+            check_f32("logf", libm::logf(xf), xf.ln(), 1);
+            check_f32("log2f", libm::log2f(xf), xf.log2(), 1);
+            check_f64("pow", libm::pow(x, y), x.powf(y), 1);
+            check_f32("powf", libm::powf(xf, yf), xf.powf(yf), 1);
+            check_f64("hypot", libm::hypot(x, y), x.hypot(y), 1);
+            check_f32("hypotf", libm::hypotf(xf, yf), xf.hypot(yf), 1);
+            check_f64("cos", libm::cos(x), x.cos(), 1);
+            check_f32("sinf", libm::sinf(xf), xf.sin(), 1);
+
+            let (s, c) = libm::sincos(x);
+            check_f64("sincos", s, x.sin(), 1);
+            check_f64("sincos", c, x.cos(), 1);
+            let (s, c) = libm::sincosf(xf);
+            check_f32("sincosf", s, xf.sin(), 1);
+            check_f32("sincosf", c, xf.cos(), 1);
+
+            // Selected backend matches these
+            check_f64("exp", exp(y), y.exp(), 1);
+            check_f32("powf", powf(xf, yf), xf.powf(yf), 1);
+        }
+    }
+}
@@ -1,61 +1,535 @@
 //! NIST 800-22 tests
+//!
+//! Tests take any [AsBits] source, such as an iterator of bits (see
+//! [BitIter](crate::helpers::BitIter) for buffers), `&[bool]`, or with the `bitvec` feature
+//! a `&BitSlice`.
 
-use crate::Error;
+use crate::helpers::{AsBits, BitRing};
+use crate::math;
+use crate::{Error, TestResult};
+
+pub mod aggregate;
+pub mod templates;
+
+mod approx_entropy;
+pub use approx_entropy::{
+    nist_approx_entropy, nist_approx_entropy_result, nist_approx_entropy_with_alpha,
+    APPROX_ENTROPY_MAX_BLOCK_LEN,
+};
+
+mod autocorrelation;
+pub use autocorrelation::{
+    autocorrelation, autocorrelation_result, autocorrelation_with_alpha, AUTOCORRELATION_MAX_LAG,
+    AUTOCORRELATION_MIN_LEN,
+};
+
+mod builder;
+pub use builder::{
+    ApproxEntropyTest, BlockFrequencyTest, CusumTest, LinearComplexityTest, LongestRunTest,
+    MonobitTest, NonOverlappingTemplateTest, OverlappingTemplateTest, SerialTest, Test,
+    UniversalTest,
+};
+
+mod cusum;
+pub use cusum::{
+    nist_cusum, nist_cusum_result, nist_cusum_with_alpha, nist_cusum_with_min,
+    nist_cusum_with_min_result, CusumMode, CUSUM_MIN_LEN,
+};
+
+mod dft;
+pub use dft::{
+    nist_dft, nist_dft_bluestein_scratch_len, nist_dft_result, nist_dft_scratch_len,
+    nist_dft_with_alpha, nist_dft_with_options, nist_dft_with_options_result,
+    nist_dft_with_options_with_alpha, DftOptions, PadMode, WindowFn, DFT_MIN_LEN,
+};
+
+mod excursions;
+pub use excursions::{
+    nist_random_excursions, nist_random_excursions_result, nist_random_excursions_variant,
+    nist_random_excursions_variant_result, nist_random_excursions_variant_with_alpha,
+    nist_random_excursions_with_alpha, EXCURSIONS_MIN_CYCLES,
+};
+
+#[cfg(feature = "fixed")]
+mod fixed;
+#[cfg(feature = "fixed")]
+pub use fixed::{autocorrelation_passfail, nist_freq_monobit_passfail, nist_runs_passfail};
+
+mod linear_complexity;
+pub use linear_complexity::{
+    nist_linear_complexity, nist_linear_complexity_result, nist_linear_complexity_with_alpha,
+    LINEAR_COMPLEXITY_MAX_BLOCK_LEN,
+};
+
+mod longest_run;
+pub use longest_run::{
+    nist_longest_run, nist_longest_run_custom, nist_longest_run_custom_result,
+    nist_longest_run_custom_with_alpha, nist_longest_run_result, nist_longest_run_with_alpha,
+    LONGEST_RUN_MAX_CATEGORIES, LONGEST_RUN_MIN_LEN,
+};
+
+mod matrix_rank;
+pub use matrix_rank::{
+    nist_matrix_rank, nist_matrix_rank_result, nist_matrix_rank_with_alpha, MATRIX_RANK_MAX_DIM,
+};
+
+mod non_overlapping_template;
+pub use non_overlapping_template::{
+    nist_non_overlapping_template, nist_non_overlapping_template_result,
+    nist_non_overlapping_template_with_alpha, TEMPLATE_MAX_LEN,
+};
+
+mod overlapping_template;
+pub use overlapping_template::{
+    nist_overlapping_template, nist_overlapping_template_result,
+    nist_overlapping_template_with_alpha, nist_overlapping_template_with_probs,
+    nist_overlapping_template_with_probs_result, nist_overlapping_template_with_probs_with_alpha,
+    OVERLAPPING_BLOCK_LEN, OVERLAPPING_PROBS, OVERLAPPING_TEMPLATE_LEN,
+};
+
+mod patterns;
+
+mod runs;
+pub use runs::{
+    nist_runs, nist_runs_result, nist_runs_with_alpha, nist_runs_with_min,
+    nist_runs_with_min_result, RUNS_MIN_LEN,
+};
+
+mod serial;
+pub use serial::{nist_serial, nist_serial_result, nist_serial_with_alpha, SERIAL_MAX_BLOCK_LEN};
+
+mod suite;
+#[cfg(feature = "rayon")]
+pub use suite::run_suite_parallel;
+pub use suite::{
+    recommended_min_bits, run_suite, SuiteConfig, SuiteMode, SuiteReport, SuiteTest, TestId,
+    SUITE_TESTS, SUITE_UNIVERSAL_MAX_BLOCK_LEN,
+};
+
+mod universal;
+pub use universal::{
+    nist_universal, nist_universal_result, nist_universal_table_len, nist_universal_with_alpha,
+    nist_universal_with_params, nist_universal_with_params_result,
+    nist_universal_with_params_with_alpha, UNIVERSAL_MAX_BLOCK_LEN, UNIVERSAL_MIN_LEN,
+};
+
+/// Minimum recommended sequence length (n) for the monobit test
+pub const MONOBIT_MIN_LEN: usize = 100;
 
 /// NIST Frequency (Monobit) Test over an iterator of N bits
 ///
+/// This requires at least [MONOBIT_MIN_LEN] bits, returning
+/// [Error::InsufficientSampleSize] otherwise, see [nist_freq_monobit_with_min] to
+/// override this. With the `double` feature the P-value is computed in f64 using
+/// [erfc_f64](crate::helpers::erfc_f64).
+///
 /// See [BitIter](crate::helpers::BitIter) for use with buffers
-pub fn nist_freq_monobit(data: impl Iterator<Item = bool>) -> Result<f32, Error> {
-    let mut v = 0isize;
-    let mut n = 0usize;
+pub fn nist_freq_monobit(data: impl AsBits) -> Result<f32, Error> {
+    nist_freq_monobit_result(data)?.check()
+}
 
-    // Sum 0/1 as -1/+1
-    for d in data {
-        n += 1;
+/// NIST Frequency (Monobit) Test at significance level `alpha`
+///
+/// See [nist_freq_monobit] for details
+pub fn nist_freq_monobit_with_alpha(data: impl AsBits, alpha: f32) -> Result<f32, Error> {
+    nist_freq_monobit_result(data)?.at_alpha(alpha)?.check()
+}
 
-        match d {
-            true => v += 1,
-            false => v -= 1,
-        }
+/// NIST Frequency (Monobit) Test returning the [TestResult], with statistic s_obs
+///
+/// See [nist_freq_monobit] for details
+pub fn nist_freq_monobit_result(data: impl AsBits) -> Result<TestResult, Error> {
+    let mut acc = MonobitAccumulator::new();
+
+    for d in data.into_bits() {
+        acc.push(d);
+    }
+
+    acc.finalize_result()
+}
+
+/// NIST Frequency (Monobit) Test requiring at least `min_samples` bits in place of
+/// [MONOBIT_MIN_LEN]
+///
+/// This allows short smoke tests during development and longer tests in CI from the same
+/// code. Raising the minimum is always valid, however the P-value relies on a normal
+/// approximation that degrades for short sequences, so below the recommended minimum
+/// P-values are unreliable in both directions and results should not be reported as
+/// conforming to SP 800-22. Empty sequences are always rejected.
+pub fn nist_freq_monobit_with_min(data: impl AsBits, min_samples: usize) -> Result<f32, Error> {
+    nist_freq_monobit_with_min_result(data, min_samples)?.check()
+}
+
+/// NIST Frequency (Monobit) Test requiring at least `min_samples` bits, returning the
+/// [TestResult]
+///
+/// See [nist_freq_monobit_with_min] for details
+pub fn nist_freq_monobit_with_min_result(
+    data: impl AsBits,
+    min_samples: usize,
+) -> Result<TestResult, Error> {
+    let mut acc = MonobitAccumulator::new();
+
+    for d in data.into_bits() {
+        acc.push(d);
+    }
+
+    acc.result(min_samples)
+}
+
+/// NIST Frequency (Monobit) Test over a buffer of bytes
+///
+/// This computes the sum from the popcount of each byte rather than bit by bit, giving
+/// identical results to [nist_freq_monobit] over a [BitIter](crate::helpers::BitIter)
+/// of the buffer.
+pub fn nist_freq_monobit_bytes(data: &[u8]) -> Result<f32, Error> {
+    nist_freq_monobit_bytes_result(data)?.check()
+}
+
+/// NIST Frequency (Monobit) Test over a buffer of bytes at significance level `alpha`
+///
+/// See [nist_freq_monobit_bytes] for details
+pub fn nist_freq_monobit_bytes_with_alpha(data: &[u8], alpha: f32) -> Result<f32, Error> {
+    nist_freq_monobit_bytes_result(data)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Frequency (Monobit) Test over a buffer of bytes returning the [TestResult], with
+/// statistic s_obs
+///
+/// See [nist_freq_monobit_bytes] for details
+pub fn nist_freq_monobit_bytes_result(data: &[u8]) -> Result<TestResult, Error> {
+    let mut acc = MonobitAccumulator::new();
+    acc.push_bytes(data);
+
+    acc.finalize_result()
+}
+
+/// Frequency (Monobit) Test against an expected probability `p0` of each bit being one
+///
+/// This generalises [nist_freq_monobit] for sources that are biased by design, such as
+/// raw physical sources prior to conditioning, with the statistic
+/// `s = |k - n p0| / sqrt(n p0 (1 - p0))` for k ones in n bits and P-value
+/// `erfc(s / sqrt(2))`. With `p0 = 0.5` this is identical to [nist_freq_monobit].
+///
+/// Returns [Error::InvalidParameter] unless `p0` is in (0, 1), and as with the standard
+/// test requires at least [MONOBIT_MIN_LEN] bits. The normal approximation requires both
+/// `n p0` and `n (1 - p0)` to be reasonably large, so strongly biased sources need
+/// correspondingly longer sequences.
+pub fn nist_freq_monobit_biased(data: impl AsBits, p0: f32) -> Result<f32, Error> {
+    nist_freq_monobit_biased_result(data, p0)?.check()
+}
+
+/// Frequency (Monobit) Test against an expected probability `p0` at significance level
+/// `alpha`
+///
+/// See [nist_freq_monobit_biased] for details
+pub fn nist_freq_monobit_biased_with_alpha(
+    data: impl AsBits,
+    p0: f32,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_freq_monobit_biased_result(data, p0)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// Frequency (Monobit) Test against an expected probability `p0` returning the
+/// [TestResult], with statistic s
+///
+/// See [nist_freq_monobit_biased] for details
+pub fn nist_freq_monobit_biased_result(data: impl AsBits, p0: f32) -> Result<TestResult, Error> {
+    // Inverted to reject NaN
+    if !(p0 > 0.0 && p0 < 1.0) {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut n = 0usize;
+    let mut ones = 0usize;
+    for d in data.into_bits() {
+        n += 1;
+        ones += d as usize;
     }
 
     // Check sample size meets minimum requirements
-    if n < 100 {
+    if n < MONOBIT_MIN_LEN {
         return Err(Error::InsufficientSampleSize(n));
     }
 
-    // Compute test statistic
-    let s = v.abs() as f32 / libm::sqrtf(n as f32);
+    // Compute test statistic relative to the expected mean and variance
+    let (n, p0) = (n as f64, p0 as f64);
+    let s = math::fabs(ones as f64 - n * p0) / math::sqrt(n * p0 * (1.0 - p0));
 
     // Compute P-value
-    let p = libm::erfcf(s / libm::sqrtf(2.0));
+    #[cfg(not(feature = "double"))]
+    let p = math::erfcf(s as f32 / math::sqrtf(2.0));
+
+    #[cfg(feature = "double")]
+    let p = crate::helpers::erfc_f64(s / core::f64::consts::SQRT_2) as f32;
 
-    // Check P value limit
-    if p < 0.01 {
-        return Err(Error::BadPValue(p));
+    Ok(TestResult::new(p, s as f32))
+}
+
+/// Streaming accumulator for the NIST Frequency (Monobit) Test
+///
+/// This tracks the running -1/+1 sum so bits can be pushed incrementally from a live
+/// source, with P-values computed on demand without buffering the sequence.
+#[derive(Clone, Debug, Default)]
+pub struct MonobitAccumulator {
+    v: i64,
+    n: usize,
+}
+
+impl MonobitAccumulator {
+    /// Create a new, empty, accumulator
+    pub const fn new() -> Self {
+        Self { v: 0, n: 0 }
     }
 
-    Ok(p)
+    /// Push a single bit
+    pub fn push(&mut self, bit: bool) {
+        self.n += 1;
+
+        match bit {
+            true => self.v += 1,
+            false => self.v -= 1,
+        }
+    }
+
+    /// Push all bits of a buffer, note that as a sum this is independent of bit order
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let ones = crate::popcount::popcount(bytes);
+        self.v += 2 * ones as i64 - (bytes.len() * 8) as i64;
+        self.n += bytes.len() * 8;
+    }
+
+    /// Number of bits accumulated
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    /// Check whether no bits have been accumulated
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Reset the accumulator
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Compute the P-value for the bits accumulated so far
+    ///
+    /// See [nist_freq_monobit] for details
+    pub fn finalize(&self) -> Result<f32, Error> {
+        self.finalize_result()?.check()
+    }
+
+    /// Compute the [TestResult] for the bits accumulated so far
+    ///
+    /// See [nist_freq_monobit_result] for details
+    pub fn finalize_result(&self) -> Result<TestResult, Error> {
+        self.result(MONOBIT_MIN_LEN)
+    }
+
+    /// Compute the [TestResult] requiring at least `min_samples` bits
+    fn result(&self, min_samples: usize) -> Result<TestResult, Error> {
+        let (v, n) = (self.v, self.n);
+
+        // Check sample size meets minimum requirements
+        if n < min_samples.max(1) {
+            return Err(Error::InsufficientSampleSize(n));
+        }
+
+        // Compute test statistic, with the sums held as integers and promoted to f64
+        // as f32 loses precision beyond 2^24 bits
+        let s = v.unsigned_abs() as f64 / math::sqrt(n as f64);
+
+        // Compute P-value
+        #[cfg(not(feature = "double"))]
+        let p = math::erfcf(s as f32 / math::sqrtf(2.0));
+
+        #[cfg(feature = "double")]
+        let p = crate::helpers::erfc_f64(s / core::f64::consts::SQRT_2) as f32;
+
+        Ok(TestResult::new(p, s as f32))
+    }
+}
+
+/// Maximum window length (in bits) for [sliding_monobit]
+pub const SLIDING_MONOBIT_MAX_WINDOW: usize = 1 << 16;
+
+/// NIST Frequency (Monobit) Test over a sliding window of `window` bits, emitting a
+/// P-value every `step` bits
+///
+/// The first result is emitted once `window` bits have been read, then every `step`
+/// bits over the most recent `window` bits, giving a time series of P-values for
+/// detecting a slowly degrading source. Each result is as for [nist_freq_monobit], with
+/// failures returning [Error::BadPValue] so the P-value is retained, and any trailing
+/// bits shorter than `step` are discarded.
+///
+/// The window is held in a [BitRing] (8 KiB) within the iterator, so `window` is
+/// limited to [MONOBIT_MIN_LEN]..=[SLIDING_MONOBIT_MAX_WINDOW] and `step` must be
+/// non-zero, otherwise a single [Error::InvalidParameter] is emitted.
+pub fn sliding_monobit(
+    data: impl Iterator<Item = bool>,
+    window: usize,
+    step: usize,
+) -> impl Iterator<Item = Result<f32, Error>> {
+    SlidingMonobit {
+        data,
+        window,
+        step,
+        ring: BitRing::with_capacity(window).unwrap_or_default(),
+        acc: MonobitAccumulator::new(),
+        done: false,
+    }
+}
+
+/// Iterator state for [sliding_monobit]
+struct SlidingMonobit<I: Iterator<Item = bool>> {
+    data: I,
+    window: usize,
+    step: usize,
+    ring: BitRing<{ SLIDING_MONOBIT_MAX_WINDOW / 8 }>,
+    acc: MonobitAccumulator,
+    done: bool,
+}
+
+impl<I: Iterator<Item = bool>> Iterator for SlidingMonobit<I> {
+    type Item = Result<f32, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // Check window and step are supported
+        if !(MONOBIT_MIN_LEN..=SLIDING_MONOBIT_MAX_WINDOW).contains(&self.window) || self.step == 0
+        {
+            self.done = true;
+            return Some(Err(Error::InvalidParameter));
+        }
+
+        // Fill the window, or advance by a step
+        let need = match self.acc.n < self.window {
+            true => self.window - self.acc.n,
+            false => self.step,
+        };
+
+        for _ in 0..need {
+            let Some(bit) = self.data.next() else {
+                self.done = true;
+                return None;
+            };
+
+            // Replace the oldest bit once the window is full
+            if let Some(old) = self.ring.push(bit) {
+                match old {
+                    true => self.acc.v -= 1,
+                    false => self.acc.v += 1,
+                }
+                self.acc.n -= 1;
+            }
+            self.acc.push(bit);
+        }
+
+        Some(self.acc.finalize())
+    }
+}
+
+/// Minimum block length (M) for the block frequency test
+pub const FREQ_BLOCK_MIN_LEN: usize = 20;
+
+/// Compute the minimum block length (M) for the block frequency test over n bits,
+/// meeting both M >= 20 and M > 0.01 n
+pub const fn nist_freq_block_min_len(n: usize) -> usize {
+    let m = n / 100 + 1;
+    match m > FREQ_BLOCK_MIN_LEN {
+        true => m,
+        false => FREQ_BLOCK_MIN_LEN,
+    }
 }
 
 /// NIST Block Frequency Test over an iterator of N bits with block_len sized blocks
 ///
+/// The specification requires M >= [FREQ_BLOCK_MIN_LEN], M > 0.01 n, and fewer than 100
+/// blocks, returning [Error::InvalidParameter] where these are not met. As the sequence
+/// length is only known after iterating, the latter two (which are equivalent) are
+/// checked from the number of blocks counted, see [nist_freq_block_min_len] to select M.
+/// Sequences without a complete block return [Error::InsufficientSampleSize] with the
+/// number of bits consumed.
+///
 /// See [BitIter](crate::helpers::BitIter) for use with buffers
-pub fn nist_freq_block(
-    mut data: impl Iterator<Item = bool>,
+pub fn nist_freq_block(data: impl AsBits, block_len: usize) -> Result<f32, Error> {
+    nist_freq_block_result(data, block_len)?.check()
+}
+
+/// NIST Block Frequency Test at significance level `alpha`
+///
+/// See [nist_freq_block] for details
+pub fn nist_freq_block_with_alpha(
+    data: impl AsBits,
     block_len: usize,
+    alpha: f32,
 ) -> Result<f32, Error> {
-    let mut num_blocks = 0;
-    let mut x2_partial = 0.0;
+    nist_freq_block_result(data, block_len)?
+        .at_alpha(alpha)?
+        .check()
+}
 
-    // Compute stats for each block
-    loop {
-        let block = (&mut data).take(block_len);
+/// NIST Block Frequency Test returning the [TestResult], with statistic x^2
+///
+/// See [nist_freq_block] for details
+pub fn nist_freq_block_result(data: impl AsBits, block_len: usize) -> Result<TestResult, Error> {
+    // Check block length meets minimum requirements
+    if block_len < FREQ_BLOCK_MIN_LEN {
+        return Err(Error::InvalidParameter);
+    }
+
+    check_freq_block(freq_block(data.into_bits(), block_len)?)
+}
+
+/// NIST Block Frequency Test over a buffer of bytes with block_len a multiple of 8,
+/// counting the ones in each block by popcount
+///
+/// This gives identical results to [nist_freq_block_result] over a
+/// [BitIter](crate::helpers::BitIter) of the buffer.
+pub(crate) fn freq_block_bytes_result(data: &[u8], block_len: usize) -> Result<TestResult, Error> {
+    // Check block length meets minimum requirements and is whole bytes
+    if block_len < FREQ_BLOCK_MIN_LEN || block_len % 8 != 0 {
+        return Err(Error::InvalidParameter);
+    }
+
+    let blocks = data
+        .chunks(block_len / 8)
+        .map(|c| (c.len() * 8, crate::popcount::popcount(c)));
+
+    check_freq_block(freq_block_counts(blocks, block_len)?)
+}
+
+/// Check N < 100, equivalent to M > 0.01 n including any discarded bits
+fn check_freq_block((r, num_blocks): (TestResult, usize)) -> Result<TestResult, Error> {
+    if num_blocks >= 100 {
+        return Err(Error::InvalidParameter);
+    }
+
+    Ok(r)
+}
+
+/// Compute the block frequency result and number of blocks, without checking the
+/// specification requirements
+fn freq_block(
+    mut data: impl Iterator<Item = bool>,
+    block_len: usize,
+) -> Result<(TestResult, usize), Error> {
+    // Count the ones in each block
+    let blocks = core::iter::from_fn(move || {
         let mut block_n = 0;
         let mut block_v = 0;
 
-        // Count the ones in the block
-        for v in block {
+        for v in (&mut data).take(block_len) {
             block_n += 1;
 
             if v {
@@ -63,65 +537,282 @@ pub fn nist_freq_block(
             }
         }
 
+        Some((block_n, block_v))
+    });
+
+    freq_block_counts(blocks, block_len)
+}
+
+/// Compute the block frequency result and number of blocks from the (length, ones) of
+/// each block, ending at the first incomplete block
+fn freq_block_counts(
+    blocks: impl Iterator<Item = (usize, usize)>,
+    block_len: usize,
+) -> Result<(TestResult, usize), Error> {
+    // Accumulate in f64 with the `double` feature
+    #[cfg(not(feature = "double"))]
+    type Float = f32;
+    #[cfg(feature = "double")]
+    type Float = f64;
+
+    let mut num_blocks = 0;
+    let mut bits = 0;
+    let mut x2_partial: Float = 0.0;
+
+    // Compute stats for each block
+    for (block_n, block_v) in blocks {
+        bits += block_n;
+
         // Discard if block_n < block_len
         if block_n < block_len {
             break;
         }
 
         // Compute proportion of ones
-        let block_p = (block_v as f32 / block_n as f32) - 0.5;
-
-        let block_x2 = libm::powf(block_p, 2.0);
+        let block_p = (block_v as Float / block_n as Float) - 0.5;
 
         // Add to partial x^2 calculation
-        x2_partial += block_x2;
+        x2_partial += block_p * block_p;
 
         // Update block and value counts
         num_blocks += 1;
     }
 
+    // Check we have at least one block, reporting the total bits consumed
+    if num_blocks == 0 {
+        return Err(Error::InsufficientSampleSize(bits));
+    }
+
     // Compute x^2
-    let x2 = 4f32 * block_len as f32 * x2_partial;
+    let x2 = 4.0 * block_len as Float * x2_partial;
 
     // Compute p
-    let p = 1.0 - nist_igamma(num_blocks as f32 / 2.0, x2 / 2.0);
+    #[cfg(not(feature = "double"))]
+    let p = igamma_upper(num_blocks as f32 / 2.0, x2 / 2.0);
 
-    // Check p value
-    if p < 0.01 {
-        return Err(Error::BadPValue(p));
-    }
+    #[cfg(feature = "double")]
+    let p = igamma_upper_f64(num_blocks as f64 / 2.0, x2 / 2.0) as f32;
 
-    Ok(p)
+    Ok((TestResult::new(p, x2 as f32), num_blocks))
 }
 
-// {\displaystyle \gamma (s,x)} = EXP(GAMMALN(s))*GAMMA.DIST(x,s,1,TRUE).
+/// Maximum iterations for the incomplete gamma series and continued fraction
+const IGAMMA_MAX_ITERATIONS: usize = 1000;
+
+/// Regularised lower incomplete gamma function, P(a, x) = γ(a, x) / Γ(a)
+///
+/// This uses series and continued fraction expansions by default, or with the
+/// `special-gamma` feature the `special` crate's implementation (Algorithm AS 239) in
+/// f64. With the `double` feature this is computed in f64. Returns NaN for `a <= 0` or
+/// `x < 0`.
+///
+/// For the P-values of chi-square tests use [igamma_upper], which does not lose precision
+/// in the upper tail.
+pub fn igamma_lower(a: f32, x: f32) -> f32 {
+    #[cfg(feature = "double")]
+    return igamma_lower_f64(a as f64, x as f64) as f32;
+
+    #[cfg(all(not(feature = "double"), not(feature = "special-gamma")))]
+    return igamma_libm(a, x);
 
-/// Incomplete gamma function (attempt, probably incomplete / incorrect)
-// TODO: find a better version / test suite to confirm the correct operation of this
-fn nist_igamma(a: f32, x: f32) -> f32 {
-    let epsilon = 1e-8;
+    #[cfg(all(not(feature = "double"), feature = "special-gamma"))]
+    return igamma_special(a as f64, x as f64) as f32;
+}
 
-    let mut sum = 1.0f32;
-    let mut term = 1.0f32;
-    let mut n = 0;
-    let max_iterations = 100;
+/// Regularised upper incomplete gamma function, Q(a, x) = 1 - P(a, x)
+///
+/// This is the P-value of a chi-square statistic x^2 with k degrees of freedom as
+/// `Q(k / 2, x^2 / 2)`. Unlike computing `1 - P(a, x)` from [igamma_lower] this is
+/// evaluated directly, so remains accurate for small values where P(a, x) rounds towards
+/// one. The `special` crate only provides P(a, x), so with the `special-gamma` feature the
+/// complement is taken in f64 prior to rounding, so values below around 1e-16 round to
+/// zero. Returns NaN for `a <= 0` or `x < 0`.
+pub fn igamma_upper(a: f32, x: f32) -> f32 {
+    #[cfg(feature = "double")]
+    return igamma_upper_f64(a as f64, x as f64) as f32;
 
-    while libm::fabsf(term) >= epsilon * libm::fabsf(sum) && n < max_iterations {
-        term *= x / (a + n as f32 + 1.0);
-        sum += term;
-        n += 1;
+    #[cfg(all(not(feature = "double"), not(feature = "special-gamma")))]
+    return igammac_libm(a, x);
+
+    #[cfg(all(not(feature = "double"), feature = "special-gamma"))]
+    return (1.0 - igamma_special(a as f64, x as f64)) as f32;
+}
+
+/// Regularised lower incomplete gamma function in f64, using [igamma_libm_f64] by
+/// default or the `special` crate with the `special-gamma` feature
+#[allow(dead_code)]
+pub(crate) fn igamma_lower_f64(a: f64, x: f64) -> f64 {
+    #[cfg(not(feature = "special-gamma"))]
+    return igamma_libm_f64(a, x);
+
+    #[cfg(feature = "special-gamma")]
+    return igamma_special(a, x);
+}
+
+/// Regularised upper incomplete gamma function in f64, using [igammac_libm_f64] by
+/// default or the complement from the `special` crate with the `special-gamma` feature
+#[cfg_attr(not(any(feature = "double", test)), allow(dead_code))]
+pub(crate) fn igamma_upper_f64(a: f64, x: f64) -> f64 {
+    #[cfg(not(feature = "special-gamma"))]
+    return igammac_libm_f64(a, x);
+
+    #[cfg(feature = "special-gamma")]
+    return 1.0 - igamma_special(a, x);
+}
+
+/// Regularised lower incomplete gamma function using the `special` crate
+#[cfg(feature = "special-gamma")]
+fn igamma_special(a: f64, x: f64) -> f64 {
+    use special::Gamma;
+
+    if a <= 0.0 || x < 0.0 {
+        return f64::NAN;
     }
 
-    let gamma = sum * libm::powf(x, a) * libm::expf(-x) / libm::tgammaf(a + 1.0);
+    x.inc_gamma(a)
+}
+
+/// Define the `libm` incomplete gamma function and its expansions for a float type
+macro_rules! igamma_libm {
+    ($t:ty, $libm:ident, $libm_upper:ident, $series:ident, $continued_fraction:ident, $exp:ident, $log:ident, $lgamma:ident, $fabs:ident) => {
+        /// Regularised lower incomplete gamma function using only `libm`
+        ///
+        /// This uses the series expansion for x < a + 1 and the continued fraction
+        /// expansion of the upper function Q(a, x) = 1 - P(a, x) otherwise, each of which
+        /// converge quickly in their respective regions.
+        #[allow(dead_code)]
+        fn $libm(a: $t, x: $t) -> $t {
+            if a <= 0.0 || x < 0.0 {
+                return <$t>::NAN;
+            }
+            if x == 0.0 {
+                return 0.0;
+            }
+
+            // Common prefix, x^a e^-x / Γ(a)
+            let prefix = math::$exp(a * math::$log(x) - x - math::$lgamma(a));
+
+            match x < a + 1.0 {
+                true => prefix * $series(a, x),
+                false => 1.0 - prefix * $continued_fraction(a, x),
+            }
+        }
+
+        /// Regularised upper incomplete gamma function using only `libm`
+        ///
+        /// As for the lower function, with the complement taken of the series expansion
+        /// rather than the continued fraction so small values of Q(a, x) are not rounded.
+        #[allow(dead_code)]
+        fn $libm_upper(a: $t, x: $t) -> $t {
+            if a <= 0.0 || x < 0.0 {
+                return <$t>::NAN;
+            }
+            if x == 0.0 {
+                return 1.0;
+            }
+
+            let prefix = math::$exp(a * math::$log(x) - x - math::$lgamma(a));
+
+            match x < a + 1.0 {
+                true => 1.0 - prefix * $series(a, x),
+                false => prefix * $continued_fraction(a, x),
+            }
+        }
+
+        /// Series expansion of P(a, x) without the common prefix
+        #[allow(dead_code)]
+        fn $series(a: $t, x: $t) -> $t {
+            let mut ap = a;
+            let mut term = 1.0 / a;
+            let mut sum = term;
+
+            for _ in 0..IGAMMA_MAX_ITERATIONS {
+                ap += 1.0;
+                term *= x / ap;
+                sum += term;
+
+                if math::$fabs(term) < math::$fabs(sum) * <$t>::EPSILON {
+                    break;
+                }
+            }
+
+            sum
+        }
+
+        /// Continued fraction expansion of Q(a, x) without the common prefix, using the
+        /// modified Lentz method
+        #[allow(dead_code)]
+        fn $continued_fraction(a: $t, x: $t) -> $t {
+            // Smallest value to avoid division by zero
+            let tiny = <$t>::MIN_POSITIVE / <$t>::EPSILON;
+
+            let mut b = x + 1.0 - a;
+            let mut c = 1.0 / tiny;
+            let mut d = 1.0 / b;
+            let mut h = d;
+
+            for i in 1..=IGAMMA_MAX_ITERATIONS {
+                let an = -(i as $t) * (i as $t - a);
+                b += 2.0;
+
+                d = an * d + b;
+                if math::$fabs(d) < tiny {
+                    d = tiny;
+                }
+                c = b + an / c;
+                if math::$fabs(c) < tiny {
+                    c = tiny;
+                }
+
+                d = 1.0 / d;
+                let delta = d * c;
+                h *= delta;
+
+                if math::$fabs(delta - 1.0) < <$t>::EPSILON {
+                    break;
+                }
+            }
 
-    gamma
+            h
+        }
+    };
 }
 
+igamma_libm!(
+    f32,
+    igamma_libm,
+    igammac_libm,
+    igamma_series,
+    igamma_continued_fraction,
+    expf,
+    logf,
+    lgammaf,
+    fabsf
+);
+
+igamma_libm!(
+    f64,
+    igamma_libm_f64,
+    igammac_libm_f64,
+    igamma_series_f64,
+    igamma_continued_fraction_f64,
+    exp,
+    log,
+    lgamma,
+    fabs
+);
+
 #[cfg(test)]
 mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
     use assert_approx_eq::assert_approx_eq;
     use bitvec::prelude::*;
-    use rand::{rngs::OsRng, RngCore};
+    use rand::{
+        rngs::{OsRng, StdRng},
+        RngCore, SeedableRng,
+    };
 
     use super::*;
     use crate::helpers::BitIter;
@@ -157,25 +848,264 @@ mod tests {
         nist_freq_monobit(BitIter::from([0x00u8; 128])).expect_err("Monobit p > threshold");
     }
 
+    #[test]
+    fn nist_monobit_accumulator() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 128];
+        rng.fill_bytes(&mut buff);
+
+        // Bit-wise and byte-wise accumulation should match the iterator test
+        let mut a = MonobitAccumulator::new();
+        let mut b = MonobitAccumulator::new();
+        BitIter::new(&buff).for_each(|d| a.push(d));
+        b.push_bytes(&buff);
+
+        let r = nist_freq_monobit_result(BitIter::new(&buff)).unwrap();
+        assert_eq!(a.finalize_result(), Ok(r));
+        assert_eq!(b.finalize_result(), Ok(r));
+        assert_eq!(b.len(), 1024);
+
+        // Snapshots update as data is pushed
+        b.reset();
+        b.push_bytes(&[0xff; 8]);
+        assert_eq!(b.finalize(), Err(Error::InsufficientSampleSize(64)));
+        b.push_bytes(&[0xff; 8]);
+        assert!(matches!(b.finalize(), Err(Error::BadPValue { .. })));
+    }
+
+    #[test]
+    fn nist_monobit_min() {
+        let buff = [0b0110_1001u8; 8];
+
+        // Lowered minimum allows short smoke tests
+        assert_eq!(
+            nist_freq_monobit(BitIter::new(&buff)),
+            Err(Error::InsufficientSampleSize(64))
+        );
+        assert_eq!(nist_freq_monobit_with_min(BitIter::new(&buff), 64), Ok(1.0));
+
+        // Raised minimum rejects otherwise sufficient sequences
+        let buff = [0b0110_1001u8; 16];
+        assert_eq!(
+            nist_freq_monobit_with_min(BitIter::new(&buff), 1024),
+            Err(Error::InsufficientSampleSize(128))
+        );
+        assert_eq!(
+            nist_freq_monobit_with_min_result(BitIter::new(&buff), MONOBIT_MIN_LEN),
+            nist_freq_monobit_result(BitIter::new(&buff))
+        );
+
+        // Empty sequences are always rejected
+        assert_eq!(
+            nist_freq_monobit_with_min(BitIter::new(&[]), 0),
+            Err(Error::InsufficientSampleSize(0))
+        );
+    }
+
+    #[test]
+    fn nist_monobit_biased() {
+        // Source producing ones with probability 0.6
+        let mut rng = StdRng::seed_from_u64(4);
+        let bits: Vec<bool> = (0..20_000)
+            .map(|_| rng.next_u32() < (0.6 * u32::MAX as f64) as u32)
+            .collect();
+
+        // Passes against the expected bias, failing the standard test
+        nist_freq_monobit_biased(&bits[..], 0.6).expect("Biased monobit failed");
+        nist_freq_monobit(&bits[..]).expect_err("Monobit p > threshold");
+        nist_freq_monobit_biased(&bits[..], 0.5).expect_err("Biased monobit p > threshold");
+        nist_freq_monobit_biased(&bits[..], 0.65).expect_err("Biased monobit p > threshold");
+
+        // Statistic relative to mean n p0 and variance n p0 (1 - p0), 130 ones in 200 bits
+        // against 0.6 gives s = 10 / sqrt(48)
+        let b: Vec<bool> = (0..200).map(|i| i < 130).collect();
+        let r = nist_freq_monobit_biased_result(&b[..], 0.6).unwrap();
+        assert_approx_eq!(r.statistic, 1.443376, 1e-5);
+        assert_approx_eq!(r.p_value, 0.148915, 1e-5);
+
+        // Matches the standard test where unbiased
+        let mut buff = [0u8; 128];
+        StdRng::seed_from_u64(5).fill_bytes(&mut buff);
+        let r = nist_freq_monobit_biased_result(BitIter::new(&buff), 0.5).unwrap();
+        let e = nist_freq_monobit_result(BitIter::new(&buff)).unwrap();
+        assert_approx_eq!(r.statistic, e.statistic, 1e-6);
+        assert_approx_eq!(r.p_value, e.p_value, 1e-6);
+
+        for p0 in [0.0, 1.0, -0.1, f32::NAN] {
+            assert_eq!(
+                nist_freq_monobit_biased(BitIter::new(&buff), p0),
+                Err(Error::InvalidParameter)
+            );
+        }
+        assert_eq!(
+            nist_freq_monobit_biased(BitIter::new(&buff[..12]), 0.6),
+            Err(Error::InsufficientSampleSize(96))
+        );
+    }
+
+    #[test]
+    fn nist_monobit_sliding() {
+        let mut buff = [0u8; 4096];
+        StdRng::seed_from_u64(3).fill_bytes(&mut buff);
+
+        // Each result matches the monobit test over the window
+        let (window, step) = (1024, 256);
+        let mut n = 0;
+        for (i, r) in sliding_monobit(BitIter::new(&buff), window, step).enumerate() {
+            let w = &buff[i * step / 8..][..window / 8];
+            assert_eq!(r, nist_freq_monobit(BitIter::new(w)));
+            n += 1;
+        }
+        assert_eq!(n, (buff.len() * 8 - window) / step + 1);
+
+        // Degradation is detected once biased bits dominate the window
+        buff[2048..].fill(0xff);
+        let r: Vec<_> = sliding_monobit(BitIter::new(&buff), 4096, 1024).collect();
+        assert_eq!(r.len(), 29);
+        assert!(r[..13].iter().filter(|r| r.is_err()).count() < 2);
+        assert!(r[18..]
+            .iter()
+            .all(|r| matches!(r, Err(Error::BadPValue { .. }))));
+
+        // Partial windows emit nothing
+        assert_eq!(
+            sliding_monobit(BitIter::new(&buff[..12]), 100, 1).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn nist_monobit_sliding_invalid() {
+        let buff = [0u8; 16];
+
+        for (window, step) in [(99, 1), (SLIDING_MONOBIT_MAX_WINDOW + 1, 1), (100, 0)] {
+            let r: Vec<_> = sliding_monobit(BitIter::new(&buff), window, step).collect();
+            assert_eq!(&r, &[Err(Error::InvalidParameter)]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "double")]
+    fn nist_double_stability() {
+        // 10 Mbit, where f32 accumulation loses low bits
+        let mut buff = std::vec![0u8; 10_000_000 / 8];
+        StdRng::seed_from_u64(4).fill_bytes(&mut buff);
+        let n = buff.len() * 8;
+
+        // Monobit matches the f64 reference from the integer sum
+        let ones = crate::popcount::popcount(&buff);
+        let s = (2 * ones as i64 - n as i64).unsigned_abs() as f64 / math::sqrt(n as f64);
+        let p = crate::helpers::erfc_f64(s / core::f64::consts::SQRT_2);
+
+        let r = nist_freq_monobit_bytes_result(&buff).unwrap();
+        assert_approx_eq!(r.p_value as f64, p, 1e-7);
+
+        // Block frequency matches the f64 reference
+        let m = nist_freq_block_min_len(n).next_multiple_of(8);
+        let x2 = 4.0
+            * m as f64
+            * buff
+                .chunks_exact(m / 8)
+                .map(|c| crate::popcount::popcount(c) as f64 / m as f64 - 0.5)
+                .map(|p| p * p)
+                .sum::<f64>();
+        let p = igamma_upper_f64((n / m) as f64 / 2.0, x2 / 2.0);
+
+        let r = nist_freq_block_result(BitIter::new(&buff), m).unwrap();
+        assert_approx_eq!(r.statistic as f64, x2, x2 * 1e-7);
+        assert_approx_eq!(r.p_value as f64, p, 1e-7);
+    }
+
+    #[test]
+    fn nist_monobit_bytes() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // Byte-wise results match the iterator test
+        for len in [13, 128, 1000, 4096] {
+            let mut buff = [0u8; 4096];
+            rng.fill_bytes(&mut buff[..len]);
+
+            let buff = &buff[..len];
+            assert_eq!(
+                nist_freq_monobit_bytes_result(buff),
+                nist_freq_monobit_result(BitIter::new(buff))
+            );
+        }
+
+        assert_eq!(
+            nist_freq_monobit_bytes(&[0x00; 12]),
+            Err(Error::InsufficientSampleSize(96))
+        );
+        nist_freq_monobit_bytes(&[0xff; 128]).expect_err("Monobit p > threshold");
+    }
+
+    #[test]
+    fn nist_monobit_large() {
+        // ~50 million bits, beyond exact f32 integers
+        let mut buff = std::vec![0u8; 6_250_003];
+        crate::test_utils::lcg_fill(&mut buff, 17);
+
+        let reference = |buff: &[u8]| {
+            let n = buff.len() as i64 * 8;
+            let ones = buff.iter().map(|b| b.count_ones() as i64).sum::<i64>();
+            let s = (2 * ones - n).unsigned_abs() as f64 / math::sqrt(n as f64);
+            (s, math::erfc(s / core::f64::consts::SQRT_2))
+        };
+
+        let (s, p) = reference(&buff);
+        let r = nist_freq_monobit_bytes_result(&buff).unwrap();
+        assert_approx_eq!(r.statistic as f64, s, s * 1e-6);
+        assert_approx_eq!(r.p_value as f64, p, 1e-5);
+
+        // Biased, with the sum also beyond exact f32 integers
+        for b in buff.iter_mut().step_by(2) {
+            *b = 0xff;
+        }
+
+        let (s, _) = reference(&buff);
+        let r = nist_freq_monobit_bytes_result(&buff).unwrap();
+        assert!(s > 3000.0);
+        assert_approx_eq!(r.statistic as f64, s, s * 1e-6);
+    }
+
+    #[test]
+    fn nist_monobit_alpha() {
+        let buff = bits![
+            1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0,
+            1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1,
+            0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 0
+        ];
+
+        // Spec example p = 0.109599 passes at 0.05 but not 0.2
+        nist_freq_monobit_with_alpha(buff.iter().by_vals(), 0.05).expect("Monobit test failed");
+        nist_freq_monobit_with_alpha(buff.iter().by_vals(), 0.2)
+            .expect_err("Monobit p > threshold");
+
+        assert_eq!(
+            nist_freq_monobit_with_alpha(buff.iter().by_vals(), 1.0),
+            Err(Error::InvalidAlpha(1.0))
+        );
+    }
+
     #[test]
     fn nist_block_ok() {
         let mut rng = OsRng {};
         let mut buff = [0u8; 100];
         rng.fill_bytes(&mut buff);
 
-        nist_freq_block(BitIter::new(&buff), 10).expect("Monobit test failed");
+        nist_freq_block(BitIter::new(&buff), 20).expect("Block frequency test failed");
     }
 
     #[test]
     fn nist_block_ex() {
-        // Example from specification
-        let buff = [0b01100110, 0b00000010];
-        let data = BitIter::new(&buff).take(10);
+        // Example from specification, 0110011010 MSB first
+        let buff = [0b0110_0110, 0b1000_0000];
+        let data = BitIter::new_msb(&buff).take(10);
 
-        let p = nist_freq_block(data, 3).expect("Block frequency test failed");
-
-        // Check p value matches test vector
-        assert_approx_eq!(p, 0.801252);
+        // Check p value matches test vector, below the minimum block length
+        let (r, _) = freq_block(data, 3).unwrap();
+        assert_approx_eq!(r.p_value, 0.801252);
     }
 
     #[test]
@@ -188,33 +1118,161 @@ mod tests {
             0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 0
         ];
 
-        let p = nist_freq_block(buff.iter().by_vals(), 10).expect("Block frequency test failed");
+        // Check p value matches test vector, below the minimum block length
+        let (r, _) = freq_block(buff.iter().by_vals(), 10).unwrap();
+        assert_approx_eq!(r.p_value, 0.706438);
 
-        // Check p value matches test vector
-        assert_approx_eq!(p, 0.706438);
+        // Fewer bits than a block reports the bits consumed, rather than a NaN P-value
+        assert_eq!(
+            freq_block(buff[..5].iter().by_vals(), 10),
+            Err(Error::InsufficientSampleSize(5))
+        );
     }
 
     #[test]
     fn nist_block_fail() {
         // 100-bit test from specification
         let buff = bits![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
-        nist_freq_block(buff.iter().by_vals(), 10).expect_err("Block frequency test failed");
+        freq_block(buff.iter().by_vals(), 10)
+            .unwrap()
+            .0
+            .check()
+            .expect_err("Block frequency test failed");
+
+        // Alternating blocks of ones and zeros
+        let buff: [u8; 100] = core::array::from_fn(|i| if (i / 5) % 2 == 0 { 0xff } else { 0x00 });
+        nist_freq_block(BitIter::new(&buff), 40).expect_err("Block frequency p > threshold");
+    }
+
+    #[test]
+    fn nist_block_bytes() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        // Byte-wise results match the iterator test, including partial blocks and errors
+        for (len, block_len) in [(100, 40), (1000, 128), (1001, 128), (1000, 32), (2, 24)] {
+            let mut buff = [0u8; 1001];
+            rng.fill_bytes(&mut buff[..len]);
+
+            let buff = &buff[..len];
+            assert_eq!(
+                freq_block_bytes_result(buff, block_len),
+                nist_freq_block_result(BitIter::new(buff), block_len)
+            );
+        }
+
+        assert_eq!(
+            freq_block_bytes_result(&[0u8; 100], 20),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn nist_block_invalid() {
+        let buff = [0x55u8; 250];
+
+        // M < 20
+        assert_eq!(
+            nist_freq_block(BitIter::new(&buff), 19),
+            Err(Error::InvalidParameter)
+        );
+
+        // N >= 100, with M = 0.01 n
+        assert_eq!(
+            nist_freq_block(BitIter::new(&buff), 20),
+            Err(Error::InvalidParameter)
+        );
+
+        // M > 0.01 n, including the discarded partial block
+        assert!(nist_freq_block(BitIter::new(&buff[..249]), 20).is_ok());
+        assert!(nist_freq_block(BitIter::new(&buff), 21).is_ok());
+
+        // No complete blocks
+        assert_eq!(
+            nist_freq_block(BitIter::new(&buff[..2]), 20),
+            Err(Error::InsufficientSampleSize(16))
+        );
+        assert_eq!(
+            nist_freq_block(core::iter::empty::<bool>(), 20),
+            Err(Error::InsufficientSampleSize(0))
+        );
+
+        assert_eq!(nist_freq_block_min_len(1000), FREQ_BLOCK_MIN_LEN);
+        assert_eq!(nist_freq_block_min_len(2000), 21);
+        assert_eq!(nist_freq_block_min_len(1_000_000), 10_001);
     }
 
     #[test]
     fn igamma() {
+        // Regularised values, P(1.5, 0.5) = 0.1761358672 / Γ(1.5) and
+        // P(10, 15) = 337531.5036053981834998 / Γ(10)
         let tests = &[
-            (1.0, 1.0, 0.6321205588),
-            (1.0, 2.0, 0.8646647167),
-            // TODO: expand igamma impl to handle > 1 values
-            //(1.5, 0.5, 0.1761358672),
-            //(10.0, 15.0, 337531.5036053981834998)
+            (1.0, 1.0, 0.632_120_56),
+            (1.0, 2.0, 0.864_664_7),
+            (1.5, 0.5, 0.198_748_04),
+            (10.0, 15.0, 0.930_146_3),
+            (50.0, 45.0, 0.246_802_03),
+            (0.5, 3.0, 0.985_694_1),
+            (2.5, 10.0, 0.998_750_3),
+            (20.0, 0.5, 0.0),
+            (1.0, 0.0, 0.0),
         ];
 
         for (a, x, g) in tests {
-            let v = nist_igamma(*a, *x);
+            let v = igamma_lower(*a, *x);
+
+            assert_approx_eq!(v, *g, 1e-5f32);
+
+            // Implementations agree
+            assert_approx_eq!(igamma_libm(*a, *x), *g, 1e-5f32);
+            assert_approx_eq!(igamma_libm_f64(*a as f64, *x as f64), *g as f64, 1e-6);
+            #[cfg(feature = "special-gamma")]
+            assert_approx_eq!(igamma_special(*a as f64, *x as f64), *g as f64, 1e-6);
+        }
+
+        // f64 values match to higher precision
+        assert_approx_eq!(igamma_lower_f64(10.0, 15.0), 0.930_146_339_300_6, 1e-12);
+        assert_approx_eq!(igamma_lower_f64(50.0, 45.0), 0.246_802_034_400_2, 1e-12);
+
+        assert!(igamma_lower(0.0, 1.0).is_nan());
+        assert!(igamma_lower(1.0, -1.0).is_nan());
+    }
+
+    #[test]
+    fn igamma_upper_small() {
+        // Reference values of Q(a, x) near the 0.01 threshold and below
+        let tests = &[
+            (5.0, 12.0, 0.007_600_391),
+            (10.0, 22.0, 0.001_504_932),
+            (2.5, 11.0, 5.235_983e-4),
+            (0.5, 5.4, 0.001_015_001),
+            (50.0, 75.0, 9.039_32e-4),
+            (1.0, 7.0, 9.118_82e-4),
+        ];
+
+        for (a, x, q) in tests {
+            // Relative error remains small, unlike the complement of the lower function
+            let v = igamma_upper(*a, *x);
+            assert!(
+                ((v - q) / q).abs() < 1e-4,
+                "Q({a}, {x}) = {v}, expected {q}"
+            );
+
+            let v = igammac_libm_f64(*a as f64, *x as f64);
+            assert_approx_eq!(v, *q as f64, *q as f64 * 1e-6);
+            assert_approx_eq!(igamma_upper_f64(*a as f64, *x as f64), v, 1e-9);
+        }
+
+        // Complement of the lower function rounds the far tail to zero
+        assert_eq!(1.0 - igamma_libm(3.0, 60.0), 0.0);
+        assert_approx_eq!(igammac_libm(3.0, 60.0), 1.629_587e-23, 1e-28);
+        assert_approx_eq!(igammac_libm_f64(3.0, 60.0), 1.629_586_652_937_8e-23, 1e-35);
 
-            assert_approx_eq!(v, *g, 1e-6f32);
+        // Lower and upper functions are complementary
+        for (a, x) in [(1.5, 0.5), (10.0, 15.0), (2.5, 0.5)] {
+            assert_approx_eq!(igamma_lower(a, x) + igamma_upper(a, x), 1.0, 1e-6);
         }
+        assert_eq!(igamma_upper(3.0, 0.0), 1.0);
+        assert!(igamma_upper(0.0, 1.0).is_nan());
+        assert!(igamma_upper(1.0, -1.0).is_nan());
     }
 }
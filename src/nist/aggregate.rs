@@ -0,0 +1,428 @@
+//! Second-level analysis of P-values from a test applied to many sequences
+//!
+//! SP 800-22 section 4.2 recommends checking both the proportion of sequences passing a
+//! test and the uniformity of the resulting P-values, rather than relying on a single
+//! sequence.
+
+use super::igamma_upper;
+use crate::math;
+use crate::Error;
+
+/// Minimum number of P-values for the uniformity check
+pub const UNIFORMITY_MIN_SAMPLES: usize = 55;
+
+/// Significance level for the uniformity of P-values
+pub const UNIFORMITY_ALPHA: f32 = 0.0001;
+
+/// Proportion of sequences passing a test, with the acceptable confidence interval
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProportionResult {
+    /// Number of sequences with P-values meeting the significance level
+    pub passed: usize,
+
+    /// Total number of sequences
+    pub total: usize,
+
+    /// Lower bound of the acceptable proportion
+    pub lower: f32,
+
+    /// Upper bound of the acceptable proportion
+    pub upper: f32,
+}
+
+impl ProportionResult {
+    /// Proportion of sequences passing the test
+    pub fn proportion(&self) -> f32 {
+        self.passed as f32 / self.total as f32
+    }
+
+    /// Check whether the proportion lies within the confidence interval
+    pub fn is_within(&self) -> bool {
+        let p = self.proportion();
+        p >= self.lower && p <= self.upper
+    }
+}
+
+/// Compute the uniformity of P-values from a test applied to many sequences
+///
+/// The P-values are bucketed into ten equal intervals over [0, 1] and compared with a
+/// uniform distribution, returning the P-value of the P-values (P-value_T). This
+/// requires at least [UNIFORMITY_MIN_SAMPLES] P-values, and fails with
+/// [Error::BadPValue] where P-value_T is below [UNIFORMITY_ALPHA].
+///
+/// See [PValueHistogram] to accumulate P-values without storing them, and
+/// [anderson_darling] for a check more sensitive to the tails.
+pub fn uniformity(p_values: &[f32]) -> Result<f32, Error> {
+    let s = p_values.len();
+
+    // Check sample size meets minimum requirements
+    if s < UNIFORMITY_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(s));
+    }
+
+    // Bucket P-values, with 1.0 in the last interval
+    let mut counts = [0usize; 10];
+    for p in p_values {
+        counts[bucket(*p)] += 1;
+    }
+
+    counts_uniformity(&counts, s)
+}
+
+/// Online histogram of P-values for [uniformity], without storing the values
+///
+/// P-values outside [0, 1] (or NaN) are rejected by [PValueHistogram::push]. When
+/// collected from an iterator the first of these is retained and reported by
+/// [PValueHistogram::chi_square_uniformity].
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PValueHistogram {
+    counts: [usize; 10],
+    invalid: Option<f32>,
+}
+
+impl PValueHistogram {
+    /// Create a new, empty, histogram
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; 10],
+            invalid: None,
+        }
+    }
+
+    /// Add a P-value, returning [Error::BadPValue] outside [0, 1]
+    pub fn push(&mut self, p: f32) -> Result<(), Error> {
+        // Range check rejects NaN
+        if !(0.0..=1.0).contains(&p) {
+            return Err(Error::BadPValue {
+                p,
+                statistic: f32::NAN,
+            });
+        }
+
+        self.counts[bucket(p)] += 1;
+        Ok(())
+    }
+
+    /// Count of P-values in each of the ten intervals over [0, 1]
+    pub fn counts(&self) -> &[usize; 10] {
+        &self.counts
+    }
+
+    /// Number of P-values accumulated
+    pub fn len(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Check whether no P-values have been accumulated
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Compute the uniformity of the P-values accumulated so far
+    ///
+    /// See [uniformity] for details
+    pub fn chi_square_uniformity(&self) -> Result<f32, Error> {
+        if let Some(p) = self.invalid {
+            return Err(Error::BadPValue {
+                p,
+                statistic: f32::NAN,
+            });
+        }
+
+        counts_uniformity(&self.counts, self.len())
+    }
+}
+
+impl Extend<f32> for PValueHistogram {
+    fn extend<T: IntoIterator<Item = f32>>(&mut self, iter: T) {
+        for p in iter {
+            if let Err(Error::BadPValue { p, .. }) = self.push(p) {
+                self.invalid.get_or_insert(p);
+            }
+        }
+    }
+}
+
+impl FromIterator<f32> for PValueHistogram {
+    fn from_iter<T: IntoIterator<Item = f32>>(iter: T) -> Self {
+        let mut h = Self::new();
+        h.extend(iter);
+        h
+    }
+}
+
+/// Select the interval for a P-value, with 1.0 in the last interval
+fn bucket(p: f32) -> usize {
+    ((p * 10.0) as usize).min(9)
+}
+
+/// Compute the uniformity of `s` P-values bucketed into ten intervals
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+fn counts_uniformity(counts: &[usize; 10], s: usize) -> Result<f32, Error> {
+    // Check sample size meets minimum requirements
+    if s < UNIFORMITY_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(s));
+    }
+
+    // Compute x^2 over intervals
+    let e = s as f32 / 10.0;
+    let mut x2 = 0.0;
+    for v in counts {
+        x2 += math::powf(*v as f32 - e, 2.0) / e;
+    }
+
+    // Compute p
+    let p = igamma_upper(9.0 / 2.0, x2 / 2.0);
+
+    // Check p value, inverted to reject NaN
+    if !(p >= UNIFORMITY_ALPHA) {
+        return Err(Error::BadPValue { p, statistic: x2 });
+    }
+
+    Ok(p)
+}
+
+/// Compute the uniformity of P-values using the Anderson-Darling test
+///
+/// This sorts the P-values in place (without allocating) and computes
+/// `A^2 = -n - 1/n Σ (2i - 1) (ln u_i + ln(1 - u_{n + 1 - i}))` against U(0, 1), with
+/// the P-value from the asymptotic distribution (G. Marsaglia, J. Marsaglia, "Evaluating
+/// the Anderson-Darling Distribution", 2004). Weighting the tails more heavily than
+/// [uniformity] or [ks_test](crate::helpers::ks_test) makes this more sensitive to
+/// excesses of very small (or large) P-values.
+///
+/// As with [uniformity] this requires at least [UNIFORMITY_MIN_SAMPLES] P-values, and
+/// fails with [Error::BadPValue] where the result is below [UNIFORMITY_ALPHA] or any
+/// P-value lies outside [0, 1].
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+pub fn anderson_darling(p_values: &mut [f32]) -> Result<f32, Error> {
+    let n = p_values.len();
+
+    // Check sample size meets minimum requirements
+    if n < UNIFORMITY_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Check P-values, inverted to reject NaN
+    if let Some(p) = p_values.iter().find(|p| !(**p >= 0.0 && **p <= 1.0)) {
+        return Err(Error::BadPValue {
+            p: *p,
+            statistic: f32::NAN,
+        });
+    }
+
+    p_values.sort_unstable_by(f32::total_cmp);
+
+    // Compute A^2, clamping so P-values of 0 or 1 remain finite
+    let u = |i: usize| (p_values[i] as f64).clamp(1e-10, 1.0 - 1e-10);
+    let mut s = 0.0;
+    for i in 0..n {
+        s += (2 * i + 1) as f64 * (math::log(u(i)) + math::log(1.0 - u(n - 1 - i)));
+    }
+    let a2 = -(n as f64) - s / n as f64;
+
+    // Compute p from the asymptotic distribution
+    let p = (1.0 - anderson_darling_cdf(a2)) as f32;
+
+    // Check p value, inverted to reject NaN
+    if !(p >= UNIFORMITY_ALPHA) {
+        return Err(Error::BadPValue {
+            p,
+            statistic: a2 as f32,
+        });
+    }
+
+    Ok(p)
+}
+
+/// Asymptotic Anderson-Darling distribution P(A^2 < z)
+fn anderson_darling_cdf(z: f64) -> f64 {
+    if z < 2.0 {
+        math::exp(-1.2337141 / z) / math::sqrt(z)
+            * (2.00012
+                + (0.247105 - (0.0649821 - (0.0347962 - (0.011672 - 0.00168691 * z) * z) * z) * z)
+                    * z)
+    } else {
+        let e = 1.0776
+            - (2.30695 - (0.43424 - (0.082433 - (0.008056 - 0.0003146 * z) * z) * z) * z) * z;
+        math::exp(-math::exp(e))
+    }
+}
+
+/// Compute the proportion of sequences passing a test at significance level `alpha`
+///
+/// The acceptable range is (1 - α) ± 3 sqrt(α (1 - α) / m) for m sequences, see
+/// [ProportionResult::is_within]. Returns [Error::InsufficientSampleSize] where no
+/// P-values are provided.
+pub fn proportion(p_values: &[f32], alpha: f32) -> Result<ProportionResult, Error> {
+    let total = p_values.len();
+
+    // Check we have at least one P-value
+    if total == 0 {
+        return Err(Error::InsufficientSampleSize(0));
+    }
+
+    let passed = p_values.iter().filter(|p| **p >= alpha).count();
+
+    // Compute confidence interval
+    let p = 1.0 - alpha;
+    let d = 3.0 * math::sqrtf(p * alpha / total as f32);
+
+    Ok(ProportionResult {
+        passed,
+        total,
+        lower: p - d,
+        upper: p + d,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use assert_approx_eq::assert_approx_eq;
+
+    use super::*;
+
+    /// Generate 100 P-values with the specified counts per interval
+    fn p_values(counts: [usize; 10]) -> [f32; 100] {
+        let mut p = [0.0; 100];
+        let mut i = 0;
+
+        for (b, c) in counts.iter().enumerate() {
+            for j in 0..*c {
+                p[i] = (b as f32 + (j as f32 + 0.5) / *c as f32) / 10.0;
+                i += 1;
+            }
+        }
+
+        p
+    }
+
+    #[test]
+    fn uniformity_ok() {
+        let p = p_values([10; 10]);
+        assert_approx_eq!(uniformity(&p).unwrap(), 1.0, 1e-5);
+
+        // x^2 = 0.8
+        let p = p_values([12, 8, 10, 10, 10, 10, 10, 10, 10, 10]);
+        assert_approx_eq!(uniformity(&p).unwrap(), 0.999777, 1e-5);
+
+        // x^2 = 15.0
+        let p = p_values([20, 5, 10, 10, 10, 10, 10, 10, 10, 5]);
+        assert_approx_eq!(uniformity(&p).unwrap(), 0.090936, 1e-4);
+    }
+
+    #[test]
+    fn uniformity_fail() {
+        let p = p_values([0, 0, 0, 0, 100, 0, 0, 0, 0, 0]);
+        assert!(matches!(uniformity(&p), Err(Error::BadPValue { .. })));
+
+        assert_eq!(
+            uniformity(&[0.5; 54]),
+            Err(Error::InsufficientSampleSize(54))
+        );
+    }
+
+    #[test]
+    fn uniformity_histogram() {
+        // Streamed P-values match the batch check
+        for counts in [[10; 10], [20, 5, 10, 10, 10, 10, 10, 10, 10, 5]] {
+            let p = p_values(counts);
+
+            let mut h = PValueHistogram::new();
+            for v in p {
+                h.push(v).unwrap();
+            }
+            assert_eq!(h.counts(), &counts);
+            assert_eq!(h.len(), 100);
+            assert_eq!(h.chi_square_uniformity(), uniformity(&p));
+
+            let h: PValueHistogram = p.iter().copied().collect();
+            assert_eq!(h.chi_square_uniformity(), uniformity(&p));
+        }
+
+        // P-values outside [0, 1] are rejected
+        let mut h = PValueHistogram::new();
+        for v in [-0.1, 1.1, f32::NAN] {
+            assert!(matches!(h.push(v), Err(Error::BadPValue { .. })));
+        }
+        h.push(0.0).unwrap();
+        h.push(1.0).unwrap();
+        assert_eq!(h.counts(), &[1, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(
+            h.chi_square_uniformity(),
+            Err(Error::InsufficientSampleSize(2))
+        );
+
+        let h: PValueHistogram = p_values([10; 10]).into_iter().chain([1.5]).collect();
+        assert_eq!(h.len(), 100);
+        assert!(matches!(
+            h.chi_square_uniformity(),
+            Err(Error::BadPValue { p, statistic }) if p == 1.5 && statistic.is_nan()
+        ));
+    }
+
+    #[test]
+    fn anderson_darling_vector() {
+        // Slightly skewed grid of P-values
+        let mut p: Vec<f32> = (0..100)
+            .map(|i| math::powf((10 * i) as f32 / 1000.0 + 0.0005, 1.1))
+            .collect();
+        assert_approx_eq!(anderson_darling(&mut p).unwrap(), 0.677509, 1e-4);
+        assert!(p.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn anderson_darling_tails() {
+        // Uniform P-values, with the smallest 30 of 1000 replaced by 1e-8
+        let mut p: Vec<f32> = (0..1000).map(|i| (i as f32 + 0.5) / 1000.0).collect();
+        p[..30].fill(1e-8);
+
+        // Bucketed and KS checks miss the excess in the tail, with p ~ 0.32 for KS
+        assert!(uniformity(&p).is_ok());
+        assert_approx_eq!(crate::helpers::ks_test(&mut p.clone()), 0.324563, 1e-3);
+
+        // Anderson-Darling detects it, with p ~ 1.3e-8
+        assert!(matches!(
+            anderson_darling(&mut p),
+            Err(Error::BadPValue { p, statistic }) if p < 1e-6 && statistic > 12.0
+        ));
+    }
+
+    #[test]
+    fn anderson_darling_invalid() {
+        let mut p = [0.5f32; 100];
+        p[10] = 1.5;
+        assert!(matches!(
+            anderson_darling(&mut p),
+            Err(Error::BadPValue { p, .. }) if p == 1.5
+        ));
+
+        assert_eq!(
+            anderson_darling(&mut [0.5; 54]),
+            Err(Error::InsufficientSampleSize(54))
+        );
+    }
+
+    #[test]
+    fn proportion_bounds() {
+        // Interval from specification, m = 1000 and α = 0.01
+        let mut p = [0.5; 1000];
+        p[..5].fill(0.001);
+
+        let r = proportion(&p, 0.01).unwrap();
+        assert_eq!(r.passed, 995);
+        assert_eq!(r.total, 1000);
+        assert_approx_eq!(r.lower, 0.980561, 1e-5);
+        assert_approx_eq!(r.upper, 0.999439, 1e-5);
+        assert!(r.is_within());
+
+        // Too many failures
+        p[..25].fill(0.001);
+        assert!(!proportion(&p, 0.01).unwrap().is_within());
+    }
+}
@@ -0,0 +1,142 @@
+//! NIST Approximate Entropy Test
+
+use super::igamma_upper;
+use super::patterns::{PatternCounts, PATTERN_MAX_LEN};
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported block length (m) for the approximate entropy test
+pub const APPROX_ENTROPY_MAX_BLOCK_LEN: usize = PATTERN_MAX_LEN - 1;
+
+/// NIST Approximate Entropy Test over an iterator of N bits with block_len (m) sized
+/// patterns
+///
+/// Compares the frequencies of all overlapping m and m+1 bit patterns, with the
+/// sequence wrapped around by appending its first m bits, against those expected for
+/// a random sequence.
+///
+/// The specification recommends m < log2(n) - 5. As this is `no_std` and does not
+/// allocate, `block_len` is limited to 1..=[APPROX_ENTROPY_MAX_BLOCK_LEN] with the
+/// 2^(m+1) pattern counts (8 KiB) held on the stack.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_approx_entropy(data: impl AsBits, block_len: usize) -> Result<f32, Error> {
+    nist_approx_entropy_result(data, block_len)?.check()
+}
+
+/// NIST Approximate Entropy Test at significance level `alpha`
+///
+/// See [nist_approx_entropy] for details
+pub fn nist_approx_entropy_with_alpha(
+    data: impl AsBits,
+    block_len: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_approx_entropy_result(data, block_len)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Approximate Entropy Test returning the [TestResult], with statistic x^2
+///
+/// See [nist_approx_entropy] for details
+pub fn nist_approx_entropy_result(
+    data: impl AsBits,
+    block_len: usize,
+) -> Result<TestResult, Error> {
+    let m = block_len;
+
+    // Check block length is supported
+    if m == 0 || m > APPROX_ENTROPY_MAX_BLOCK_LEN {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Count overlapping m+1 bit patterns
+    let counts = PatternCounts::new(data.into_bits(), m + 1)?;
+    let n = counts.n();
+
+    // Compute phi for m and m+1 bit patterns, in f64 as ApEn is a small difference
+    let phi = |k| {
+        counts
+            .counts(k)
+            .filter(|c| *c > 0)
+            .map(|c| {
+                let v = c as f64 / n as f64;
+                v * math::log(v)
+            })
+            .sum::<f64>()
+    };
+    let ap_en = phi(m) - phi(m + 1);
+
+    // Compute x^2
+    let x2 = 2.0 * n as f64 * (core::f64::consts::LN_2 - ap_en);
+
+    // Compute p
+    let p = igamma_upper(math::powf(2.0, m as f32 - 1.0), x2 as f32 / 2.0);
+
+    Ok(TestResult::new(p, x2 as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_approx_entropy_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 1024];
+        rng.fill_bytes(&mut buff);
+
+        nist_approx_entropy(BitIter::new(&buff), 4).expect("Approximate entropy test failed");
+    }
+
+    #[test]
+    fn nist_approx_entropy_ex() {
+        // Example from specification, m = 3
+        let buff = bits![0, 1, 0, 0, 1, 1, 0, 1, 0, 1];
+
+        let p =
+            nist_approx_entropy(buff.iter().by_vals(), 3).expect("Approximate entropy test failed");
+        assert_approx_eq!(p, 0.261961, 1e-5);
+
+        // Example from specification, m = 2
+        let buff = bits![
+            1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0,
+            1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1,
+            0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 0
+        ];
+
+        let p =
+            nist_approx_entropy(buff.iter().by_vals(), 2).expect("Approximate entropy test failed");
+        assert_approx_eq!(p, 0.235301, 1e-5);
+    }
+
+    #[test]
+    fn nist_approx_entropy_fail() {
+        nist_approx_entropy(BitIter::from([0b1001_0110u8; 128]), 4)
+            .expect_err("Approximate entropy p > threshold");
+        nist_approx_entropy(BitIter::from([0xffu8; 128]), 4)
+            .expect_err("Approximate entropy p > threshold");
+    }
+
+    #[test]
+    fn nist_approx_entropy_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            nist_approx_entropy(BitIter::new(&buff), 0),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_approx_entropy(BitIter::new(&buff), APPROX_ENTROPY_MAX_BLOCK_LEN + 1),
+            Err(Error::InvalidParameter)
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Autocorrelation Test
+
+use crate::helpers::{AsBits, BitRing};
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported lag (d), bounding the ring buffer to 1 KiB
+pub const AUTOCORRELATION_MAX_LAG: usize = 1 << 13;
+
+/// Minimum number of compared bits (n - d) for the autocorrelation test
+pub const AUTOCORRELATION_MIN_LEN: usize = 100;
+
+/// Autocorrelation Test over an iterator of N bits at lag d
+///
+/// Counts the mismatches A(d) between the sequence and itself shifted by `lag` bits, as
+/// in the AIS 31 and FIPS 140-1 autocorrelation tests, with the P-value
+/// `erfc(|2 A(d) - (n - d)| / sqrt(2 (n - d)))`. This detects periodic structure at the
+/// chosen lag that frequency tests miss.
+///
+/// As this is `no_std` and does not allocate, the most recent `lag` bits are held in a
+/// [BitRing] of [AUTOCORRELATION_MAX_LAG] bits (1 KiB) on the stack, so `lag` must be
+/// within 1..=[AUTOCORRELATION_MAX_LAG], returning [Error::InvalidParameter] otherwise.
+/// At least [AUTOCORRELATION_MIN_LEN] bits must be compared, returning
+/// [Error::InsufficientSampleSize] otherwise.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn autocorrelation(data: impl AsBits, lag: usize) -> Result<f32, Error> {
+    autocorrelation_result(data, lag)?.check()
+}
+
+/// Autocorrelation Test at significance level `alpha`
+///
+/// See [autocorrelation] for details
+pub fn autocorrelation_with_alpha(data: impl AsBits, lag: usize, alpha: f32) -> Result<f32, Error> {
+    autocorrelation_result(data, lag)?.at_alpha(alpha)?.check()
+}
+
+/// Autocorrelation Test returning the [TestResult], with statistic
+/// `(2 A(d) - (n - d)) / sqrt(n - d)`
+///
+/// See [autocorrelation] for details
+pub fn autocorrelation_result(data: impl AsBits, lag: usize) -> Result<TestResult, Error> {
+    let (m, a) = count_mismatches(data, lag)?;
+
+    // Compute test statistic and P-value
+    let s = (2.0 * a as f64 - m as f64) / math::sqrt(m as f64);
+    let p = math::erfc(math::fabs(s) / core::f64::consts::SQRT_2);
+
+    Ok(TestResult::new(p as f32, s as f32))
+}
+
+/// Count the compared bits (n - d) and mismatches A(d) at lag d, checking the lag and
+/// sample size requirements
+pub(super) fn count_mismatches(data: impl AsBits, lag: usize) -> Result<(usize, usize), Error> {
+    // Check lag is supported
+    let mut ring = match lag {
+        0 => return Err(Error::InvalidParameter),
+        _ => BitRing::<{ AUTOCORRELATION_MAX_LAG / 8 }>::with_capacity(lag)?,
+    };
+
+    let mut n = 0usize;
+    let mut a = 0usize;
+
+    // Count mismatches with the bit `lag` positions earlier
+    for d in data.into_bits() {
+        if let Some(old) = ring.push(d) {
+            a += (old != d) as usize;
+        }
+        n += 1;
+    }
+
+    // Check sample size meets minimum requirements
+    let m = n.saturating_sub(lag);
+    if m < AUTOCORRELATION_MIN_LEN {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    Ok((m, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn autocorrelation_ok() {
+        let mut buff = [0u8; 1250];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        for lag in [1, 2, 8, 31, 1000] {
+            autocorrelation(BitIter::new(&buff), lag).expect("Autocorrelation test failed");
+        }
+    }
+
+    #[test]
+    fn autocorrelation_vector() {
+        let mut buff = [0u8; 1250];
+        lcg_fill(&mut buff, 37);
+
+        // Check statistics and p values match reference
+        for (lag, s, p) in [
+            (1, 2.230112, 0.025740),
+            (8, 2.120849, 0.033935),
+            (1000, 1.264911, 0.205903),
+        ] {
+            let r = autocorrelation_result(BitIter::new(&buff), lag).unwrap();
+            assert_approx_eq!(r.statistic, s, 1e-4);
+            assert_approx_eq!(r.p_value, p, 1e-4);
+        }
+    }
+
+    #[test]
+    fn autocorrelation_fail() {
+        // Random data with an injected period of 24 bits
+        let mut buff = [0u8; 1251];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff[..3]);
+        (3..buff.len()).for_each(|i| buff[i] = buff[i - 3]);
+
+        autocorrelation(BitIter::new(&buff), 24).expect_err("Autocorrelation p > threshold");
+        autocorrelation(BitIter::new(&buff), 48).expect_err("Autocorrelation p > threshold");
+
+        // Alternating bits are perfectly anti-correlated at odd lags
+        autocorrelation(BitIter::new(&[0x55; 128]), 1).expect_err("Autocorrelation p > threshold");
+    }
+
+    #[test]
+    fn autocorrelation_invalid() {
+        let buff = [0u8; 128];
+
+        for lag in [0, AUTOCORRELATION_MAX_LAG + 1] {
+            assert_eq!(
+                autocorrelation(BitIter::new(&buff), lag),
+                Err(Error::InvalidParameter)
+            );
+        }
+
+        assert_eq!(
+            autocorrelation(core::iter::empty::<bool>(), 1),
+            Err(Error::InsufficientSampleSize(0))
+        );
+        assert_eq!(
+            autocorrelation(BitIter::new(&buff[..16]), 100),
+            Err(Error::InsufficientSampleSize(128))
+        );
+    }
+}
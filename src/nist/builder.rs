@@ -0,0 +1,941 @@
+//! Builders for configuring and running individual NIST tests
+
+use super::suite::non_overlapping_min_bits;
+use super::universal::universal_block_len;
+use super::*;
+use crate::DEFAULT_ALPHA;
+
+/// Default block length (M) for the block frequency test where the sequence length is
+/// not known, from the reference implementation
+const FREQ_BLOCK_DEFAULT_LEN: usize = 128;
+
+/// Default block length (M) for the linear complexity test, from the reference
+/// implementation
+const LINEAR_COMPLEXITY_DEFAULT_LEN: usize = 500;
+
+/// Number of blocks (N) for the non-overlapping template test where the block length is
+/// not specified
+const NON_OVERLAPPING_DEFAULT_BLOCKS: usize = 8;
+
+/// Default pattern length (m) for the serial test, as in [SuiteConfig]
+const SERIAL_DEFAULT_LEN: usize = 8;
+
+/// Default pattern length (m) for the approximate entropy test, as in [SuiteConfig]
+const APPROX_ENTROPY_DEFAULT_LEN: usize = 6;
+
+/// Builders for configuring and running individual tests
+///
+/// These provide named parameters and a significance level for each test, in place of
+/// positional arguments to the free functions. Parameters are validated by `run` before
+/// any input is consumed, so a misconfigured test does not waste a stream of RNG output.
+///
+/// By default tests run over any sequence long enough to compute their statistic. With
+/// `require_recommended_len()` sequences shorter than [recommended_min_bits] for the
+/// configured parameters return [Error::InsufficientSampleSize] without being consumed,
+/// which requires an iterator of known length such as [BitIter](crate::helpers::BitIter)
+/// (returning [Error::InvalidParameter] otherwise).
+///
+/// ```
+/// use rngcheck::{helpers::BitIter, nist::Test};
+///
+/// let data = [0xa5u8; 100];
+///
+/// let r = Test::block_frequency()
+///     .block_len(128)
+///     .alpha(0.001)
+///     .run(BitIter::new(&data))
+///     .unwrap();
+/// assert!(r.passed);
+/// ```
+pub struct Test;
+
+impl Test {
+    /// Frequency (monobit) test, see [nist_freq_monobit]
+    pub const fn monobit() -> MonobitTest {
+        MonobitTest {
+            min_samples: MONOBIT_MIN_LEN,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Frequency test within a block, see [nist_freq_block]
+    pub const fn block_frequency() -> BlockFrequencyTest {
+        BlockFrequencyTest {
+            block_len: None,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Test for the longest run of ones in a block, see [nist_longest_run]
+    pub const fn longest_run() -> LongestRunTest<'static> {
+        LongestRunTest {
+            custom: None,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Non-overlapping template matching test for `template`, see
+    /// [nist_non_overlapping_template]
+    pub const fn non_overlapping_template(template: &[bool]) -> NonOverlappingTemplateTest<'_> {
+        NonOverlappingTemplateTest {
+            template,
+            block_len: None,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Overlapping template matching test, see [nist_overlapping_template]
+    pub const fn overlapping_template() -> OverlappingTemplateTest {
+        OverlappingTemplateTest {
+            template_len: OVERLAPPING_TEMPLATE_LEN,
+            block_len: OVERLAPPING_BLOCK_LEN,
+            probs: None,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Maurer's "universal statistical" test using the caller-provided `table`, see
+    /// [nist_universal]
+    pub fn universal(table: &mut [u32]) -> UniversalTest<'_> {
+        UniversalTest {
+            table,
+            block_len: None,
+            init_blocks: None,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Linear complexity test, see [nist_linear_complexity]
+    pub const fn linear_complexity() -> LinearComplexityTest {
+        LinearComplexityTest {
+            block_len: LINEAR_COMPLEXITY_DEFAULT_LEN,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Serial test, see [nist_serial]
+    pub const fn serial() -> SerialTest {
+        SerialTest {
+            block_len: SERIAL_DEFAULT_LEN,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Approximate entropy test, see [nist_approx_entropy]
+    pub const fn approx_entropy() -> ApproxEntropyTest {
+        ApproxEntropyTest {
+            block_len: APPROX_ENTROPY_DEFAULT_LEN,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+
+    /// Cumulative sums test, see [nist_cusum]
+    pub const fn cusum() -> CusumTest {
+        CusumTest {
+            mode: CusumMode::Forward,
+            min_samples: CUSUM_MIN_LEN,
+            alpha: DEFAULT_ALPHA,
+            require_recommended_len: false,
+        }
+    }
+}
+
+/// Check a significance level is within (0, 1), as in [TestResult::at_alpha]
+fn check_alpha(alpha: f32) -> Result<(), Error> {
+    // Inverted to reject NaN
+    match alpha > 0.0 && alpha < 1.0 {
+        true => Ok(()),
+        false => Err(Error::InvalidAlpha(alpha)),
+    }
+}
+
+/// Compute [recommended_min_bits] for `test` with the parameters set by `params`
+fn recommended(test: TestId, params: impl FnOnce(&mut SuiteConfig)) -> usize {
+    let mut config = SuiteConfig::default();
+    params(&mut config);
+    recommended_min_bits(test, &config)
+}
+
+/// Check the sequence has at least `min_bits` where required, see [Test]
+fn check_recommended_len(
+    required: bool,
+    data: &impl Iterator<Item = bool>,
+    min_bits: impl FnOnce() -> usize,
+) -> Result<(), Error> {
+    if !required {
+        return Ok(());
+    }
+
+    match exact_len(data) {
+        Some(n) if n < min_bits() => Err(Error::InsufficientSampleSize(n)),
+        Some(_) => Ok(()),
+        None => Err(Error::InvalidParameter),
+    }
+}
+
+/// Fetch the sequence length from an iterator where this is known exactly
+fn exact_len(data: &impl Iterator<Item = bool>) -> Option<usize> {
+    match data.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower),
+        _ => None,
+    }
+}
+
+/// Builder for the frequency (monobit) test, see [Test::monobit]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MonobitTest {
+    min_samples: usize,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl MonobitTest {
+    /// Set the minimum sequence length, defaulting to [MONOBIT_MIN_LEN], see
+    /// [nist_freq_monobit_with_min]
+    pub const fn min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::FrequencyMonobit, |_| ())
+        })?;
+
+        nist_freq_monobit_with_min_result(data, self.min_samples)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the frequency test within a block, see [Test::block_frequency]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BlockFrequencyTest {
+    block_len: Option<usize>,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl BlockFrequencyTest {
+    /// Set the block length M
+    ///
+    /// This must be at least [FREQ_BLOCK_MIN_LEN] and produce fewer than 100 blocks, see
+    /// [nist_freq_block_min_len]. Where this is not set M is the minimum meeting these
+    /// for an iterator of known length such as [BitIter](crate::helpers::BitIter),
+    /// rounded up to a whole number of bytes, or 128 otherwise.
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = Some(block_len);
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+
+        let data = data.into_bits();
+
+        let block_len = match (self.block_len, exact_len(&data)) {
+            (Some(m), _) => m,
+            (None, Some(n)) => nist_freq_block_min_len(n).next_multiple_of(8),
+            (None, None) => FREQ_BLOCK_DEFAULT_LEN,
+        };
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::FrequencyBlock, |c| {
+                c.block_frequency_len = Some(block_len)
+            })
+        })?;
+
+        nist_freq_block_result(data, block_len)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the test for the longest run of ones in a block, see [Test::longest_run]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LongestRunTest<'a> {
+    custom: Option<(usize, &'a [usize], &'a [f32])>,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl<'a> LongestRunTest<'a> {
+    /// Use a custom block size `m` and run categories, see [nist_longest_run_custom]
+    ///
+    /// Where this is not set the standard tables are selected from the sequence length.
+    pub const fn custom(mut self, m: usize, boundaries: &'a [usize], probs: &'a [f32]) -> Self {
+        self.custom = Some((m, boundaries, probs));
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::LongestRun, |_| ())
+        })?;
+
+        let r = match self.custom {
+            Some((m, boundaries, probs)) => {
+                nist_longest_run_custom_result(data, m, boundaries, probs)
+            }
+            None => nist_longest_run_result(data),
+        };
+
+        r?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the non-overlapping template matching test, see
+/// [Test::non_overlapping_template]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NonOverlappingTemplateTest<'a> {
+    template: &'a [bool],
+    block_len: Option<usize>,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl NonOverlappingTemplateTest<'_> {
+    /// Set the block length M, which must be at least the template length
+    ///
+    /// Where this is not set the sequence is split into 8 blocks as in the specification,
+    /// requiring an iterator of known length such as [BitIter](crate::helpers::BitIter).
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = Some(block_len);
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        let m = self.template.len();
+        if m == 0 || m > TEMPLATE_MAX_LEN {
+            return Err(Error::InvalidParameter);
+        }
+
+        let block_len = match (self.block_len, exact_len(&data)) {
+            (Some(b), _) if b < m => return Err(Error::InvalidParameter),
+            (Some(b), _) => b,
+            (None, Some(n)) if n / NON_OVERLAPPING_DEFAULT_BLOCKS < m => {
+                return Err(Error::InsufficientSampleSize(n))
+            }
+            (None, Some(n)) => n / NON_OVERLAPPING_DEFAULT_BLOCKS,
+            (None, None) => return Err(Error::InvalidParameter),
+        };
+        check_recommended_len(self.require_recommended_len, &data, || {
+            non_overlapping_min_bits(m, self.block_len)
+        })?;
+
+        nist_non_overlapping_template_result(data, self.template, block_len)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the overlapping template matching test, see [Test::overlapping_template]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OverlappingTemplateTest {
+    template_len: usize,
+    block_len: usize,
+    probs: Option<[f32; 6]>,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl OverlappingTemplateTest {
+    /// Set the length m of the all-ones template, defaulting to
+    /// [OVERLAPPING_TEMPLATE_LEN]
+    pub const fn template_len(mut self, template_len: usize) -> Self {
+        self.template_len = template_len;
+        self
+    }
+
+    /// Set the block length M, defaulting to [OVERLAPPING_BLOCK_LEN]
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = block_len;
+        self
+    }
+
+    /// Override the category probabilities, see [nist_overlapping_template_with_probs]
+    pub const fn probs(mut self, probs: [f32; 6]) -> Self {
+        self.probs = Some(probs);
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+
+        if self.template_len == 0 || self.template_len > self.block_len {
+            return Err(Error::InvalidParameter);
+        }
+
+        let data = data.into_bits();
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::OverlappingTemplate, |_| ())
+        })?;
+
+        let r = match &self.probs {
+            Some(probs) => nist_overlapping_template_with_probs_result(
+                data,
+                self.template_len,
+                self.block_len,
+                probs,
+            ),
+            None => nist_overlapping_template_result(data, self.template_len, self.block_len),
+        };
+
+        r?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for Maurer's "universal statistical" test, see [Test::universal]
+#[derive(Debug)]
+pub struct UniversalTest<'a> {
+    table: &'a mut [u32],
+    block_len: Option<usize>,
+    init_blocks: Option<usize>,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl UniversalTest<'_> {
+    /// Set the block length L, up to [UNIVERSAL_MAX_BLOCK_LEN]
+    ///
+    /// Where this is not set L is selected from the sequence length as in
+    /// [nist_universal], requiring an iterator of known length such as
+    /// [BitIter](crate::helpers::BitIter). The table must hold at least
+    /// [nist_universal_table_len] entries for L.
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = Some(block_len);
+        self
+    }
+
+    /// Set the number of initialisation blocks Q, defaulting to 10 * 2^L
+    pub const fn init_blocks(mut self, init_blocks: usize) -> Self {
+        self.init_blocks = Some(init_blocks);
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        let l = match (self.block_len, exact_len(&data)) {
+            (Some(l), _) => l,
+            (None, Some(n)) => universal_block_len(n)?,
+            (None, None) => return Err(Error::InvalidParameter),
+        };
+        if l == 0 || l > UNIVERSAL_MAX_BLOCK_LEN || self.table.len() < nist_universal_table_len(l) {
+            return Err(Error::InvalidParameter);
+        }
+
+        let q = match self.init_blocks {
+            Some(0) => return Err(Error::InvalidParameter),
+            Some(q) => q,
+            None => 10 * (1 << l),
+        };
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::Universal, |_| ())
+        })?;
+
+        nist_universal_with_params_result(data, l, q, self.table)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the linear complexity test, see [Test::linear_complexity]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LinearComplexityTest {
+    block_len: usize,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl LinearComplexityTest {
+    /// Set the block length M, defaulting to 500 and limited to
+    /// [LINEAR_COMPLEXITY_MAX_BLOCK_LEN]
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = block_len;
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::LinearComplexity, |c| {
+                c.linear_complexity_len = self.block_len
+            })
+        })?;
+
+        nist_linear_complexity_result(data, self.block_len)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the serial test, see [Test::serial]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SerialTest {
+    block_len: usize,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl SerialTest {
+    /// Set the pattern length m, defaulting to 8 and limited to 2..=[SERIAL_MAX_BLOCK_LEN]
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = block_len;
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, returning both [TestResult]s, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<(TestResult, TestResult), Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::Serial, |c| c.serial_len = self.block_len)
+        })?;
+
+        let (r1, r2) = nist_serial_result(data, self.block_len)?;
+
+        Ok((r1.at_alpha(self.alpha)?, r2.at_alpha(self.alpha)?))
+    }
+}
+
+/// Builder for the approximate entropy test, see [Test::approx_entropy]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ApproxEntropyTest {
+    block_len: usize,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl ApproxEntropyTest {
+    /// Set the pattern length m, defaulting to 6 and limited to
+    /// 1..=[APPROX_ENTROPY_MAX_BLOCK_LEN]
+    pub const fn block_len(mut self, block_len: usize) -> Self {
+        self.block_len = block_len;
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::ApproxEntropy, |c| {
+                c.approx_entropy_len = self.block_len
+            })
+        })?;
+
+        nist_approx_entropy_result(data, self.block_len)?.at_alpha(self.alpha)
+    }
+}
+
+/// Builder for the cumulative sums test, see [Test::cusum]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CusumTest {
+    mode: CusumMode,
+    min_samples: usize,
+    alpha: f32,
+    require_recommended_len: bool,
+}
+
+impl CusumTest {
+    /// Set the direction of the partial sums, defaulting to [CusumMode::Forward]
+    pub const fn mode(mut self, mode: CusumMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the minimum sequence length, defaulting to [CUSUM_MIN_LEN], see
+    /// [nist_cusum_with_min]
+    pub const fn min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples;
+        self
+    }
+
+    /// Set the significance level, defaulting to [DEFAULT_ALPHA]
+    pub const fn alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Require at least [recommended_min_bits] bits, see [Test]
+    pub const fn require_recommended_len(mut self) -> Self {
+        self.require_recommended_len = true;
+        self
+    }
+
+    /// Run the test over a source of bits, see [AsBits]
+    pub fn run(self, data: impl AsBits) -> Result<TestResult, Error> {
+        check_alpha(self.alpha)?;
+        let data = data.into_bits();
+
+        check_recommended_len(self.require_recommended_len, &data, || {
+            recommended(TestId::Cusum, |_| ())
+        })?;
+
+        nist_cusum_with_min_result(data, self.mode, self.min_samples)?.at_alpha(self.alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::BitIter;
+    use crate::test_utils::lcg_fill;
+
+    /// An iterator that panics if consumed, to check parameters are validated first
+    fn unreachable_bits() -> impl Iterator<Item = bool> {
+        core::iter::from_fn(|| panic!("iterator consumed"))
+    }
+
+    #[test]
+    fn builder_matches_free_functions() {
+        let mut buff = [0u8; 16384];
+        lcg_fill(&mut buff, 3);
+        let bits = || BitIter::new(&buff);
+
+        assert_eq!(
+            Test::monobit().run(bits()),
+            nist_freq_monobit_result(bits())
+        );
+        assert_eq!(
+            Test::block_frequency().block_len(1400).run(bits()),
+            nist_freq_block_result(bits(), 1400)
+        );
+        assert_eq!(
+            Test::longest_run().run(bits()),
+            nist_longest_run_result(bits())
+        );
+        assert_eq!(
+            Test::longest_run()
+                .custom(8, &[1, 2, 3], &[0.2148, 0.3672, 0.2305, 0.1875])
+                .run(bits()),
+            nist_longest_run_custom_result(
+                bits(),
+                8,
+                &[1, 2, 3],
+                &[0.2148, 0.3672, 0.2305, 0.1875]
+            )
+        );
+        assert_eq!(
+            Test::non_overlapping_template(templates::M5[0]).run(bits()),
+            nist_non_overlapping_template_result(bits(), templates::M5[0], buff.len())
+        );
+        assert_eq!(
+            Test::overlapping_template().run(bits()),
+            nist_overlapping_template_result(bits(), 9, 1032)
+        );
+        assert_eq!(
+            Test::linear_complexity().block_len(200).run(bits()),
+            nist_linear_complexity_result(bits(), 200)
+        );
+        assert_eq!(Test::serial().run(bits()), nist_serial_result(bits(), 8));
+        assert_eq!(
+            Test::approx_entropy().block_len(8).run(bits()),
+            nist_approx_entropy_result(bits(), 8)
+        );
+        assert_eq!(
+            Test::cusum().mode(CusumMode::Reverse).run(bits()),
+            nist_cusum_result(bits(), CusumMode::Reverse)
+        );
+
+        let mut a = [0u32; nist_universal_table_len(8)];
+        let mut b = [0u32; nist_universal_table_len(8)];
+        assert_eq!(
+            Test::universal(&mut a)
+                .block_len(8)
+                .init_blocks(1000)
+                .run(bits()),
+            nist_universal_with_params_result(bits(), 8, 1000, &mut b)
+        );
+    }
+
+    #[test]
+    fn builder_block_frequency_len() {
+        // Default block length meets N < 100 for long sequences
+        let mut buff = [0u8; 16384];
+        lcg_fill(&mut buff, 3);
+
+        let r = Test::block_frequency().run(BitIter::new(&buff));
+        assert_eq!(r, nist_freq_block_result(BitIter::new(&buff), 1312));
+        assert!(r.is_ok());
+
+        // Or uses 128 where the length is not known
+        let bits = BitIter::new(&buff).take(1000).filter(|_| true);
+        assert_eq!(
+            Test::block_frequency().run(bits),
+            nist_freq_block_result(BitIter::new(&buff).take(1000), 128)
+        );
+    }
+
+    #[test]
+    fn builder_alpha() {
+        let mut buff = [0u8; 1250];
+        lcg_fill(&mut buff, 3);
+
+        let r = Test::monobit().run(BitIter::new(&buff)).unwrap();
+        let a = Test::monobit()
+            .alpha(r.p_value + 0.001)
+            .run(BitIter::new(&buff))
+            .unwrap();
+        assert_eq!(a.p_value, r.p_value);
+        assert!(!a.passed);
+
+        // Minimum sample size is configurable
+        assert_eq!(
+            Test::monobit().min_samples(20000).run(BitIter::new(&buff)),
+            Err(Error::InsufficientSampleSize(10000))
+        );
+    }
+
+    #[test]
+    fn builder_recommended_len() {
+        let mut buff = [0u8; 16384];
+        lcg_fill(&mut buff, 3);
+        let bits = || BitIter::new(&buff);
+        let n = buff.len() * 8;
+
+        // Sequences meeting the recommendation run as usual
+        assert_eq!(
+            Test::cusum().require_recommended_len().run(bits()),
+            nist_cusum_result(bits(), CusumMode::Forward)
+        );
+        assert_eq!(
+            Test::serial().require_recommended_len().run(bits()),
+            nist_serial_result(bits(), 8)
+        );
+
+        // Shorter sequences are rejected, using the table for the configured parameters
+        let short = [
+            Test::overlapping_template()
+                .require_recommended_len()
+                .run(bits()),
+            Test::linear_complexity()
+                .require_recommended_len()
+                .run(bits()),
+            Test::non_overlapping_template(templates::M5[0])
+                .block_len(n + 1)
+                .require_recommended_len()
+                .run(bits()),
+        ];
+        for r in short {
+            assert_eq!(r, Err(Error::InsufficientSampleSize(n)));
+        }
+
+        assert_eq!(
+            Test::approx_entropy()
+                .block_len(10)
+                .require_recommended_len()
+                .run(bits().take(1 << 15)),
+            Err(Error::InsufficientSampleSize(1 << 15))
+        );
+
+        let min = recommended_min_bits(TestId::Cusum, &SuiteConfig::default());
+        assert_eq!(
+            Test::cusum()
+                .require_recommended_len()
+                .run(bits().take(min - 1)),
+            Err(Error::InsufficientSampleSize(min - 1))
+        );
+        assert!(Test::cusum()
+            .require_recommended_len()
+            .run(bits().take(min))
+            .is_ok());
+
+        // The length must be known up front
+        assert_eq!(
+            Test::monobit()
+                .require_recommended_len()
+                .run(unreachable_bits()),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn builder_validates_first() {
+        let mut table = [0u32; nist_universal_table_len(6)];
+
+        for alpha in [0.0, 1.0, f32::NAN] {
+            assert!(matches!(
+                Test::monobit().alpha(alpha).run(unreachable_bits()),
+                Err(Error::InvalidAlpha(_))
+            ));
+        }
+
+        let invalid = [
+            Test::block_frequency()
+                .block_len(19)
+                .run(unreachable_bits()),
+            Test::non_overlapping_template(&[])
+                .block_len(10)
+                .run(unreachable_bits()),
+            Test::non_overlapping_template(&[true; 4])
+                .block_len(3)
+                .run(unreachable_bits()),
+            // Default block length requires a known sequence length
+            Test::non_overlapping_template(&[true; 4]).run(unreachable_bits()),
+            Test::overlapping_template()
+                .template_len(9)
+                .block_len(8)
+                .run(unreachable_bits()),
+            Test::universal(&mut table)
+                .block_len(7)
+                .run(unreachable_bits()),
+            Test::universal(&mut table)
+                .block_len(6)
+                .init_blocks(0)
+                .run(unreachable_bits()),
+            Test::universal(&mut table).run(unreachable_bits()),
+            Test::linear_complexity()
+                .block_len(0)
+                .run(unreachable_bits()),
+            Test::linear_complexity()
+                .block_len(5001)
+                .run(unreachable_bits()),
+            Test::longest_run()
+                .custom(8, &[1, 2, 3], &[0.5, 0.5])
+                .run(unreachable_bits()),
+            Test::approx_entropy().block_len(0).run(unreachable_bits()),
+        ];
+        for r in invalid {
+            assert_eq!(r, Err(Error::InvalidParameter));
+        }
+        assert_eq!(
+            Test::serial().block_len(1).run(unreachable_bits()),
+            Err(Error::InvalidParameter)
+        );
+
+        // Short sequences are rejected before selecting parameters
+        assert_eq!(
+            Test::universal(&mut table).run(BitIter::new(&[0u8; 1000])),
+            Err(Error::InsufficientSampleSize(8000))
+        );
+    }
+}
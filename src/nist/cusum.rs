@@ -0,0 +1,213 @@
+//! NIST Cumulative Sums (Cusum) Test
+
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Direction of the partial sums for the cumulative sums test
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CusumMode {
+    /// Partial sums from the start of the sequence
+    Forward,
+    /// Partial sums from the end of the sequence
+    Reverse,
+}
+
+/// Minimum recommended sequence length (n) for the cumulative sums test
+pub const CUSUM_MIN_LEN: usize = 100;
+
+/// NIST Cumulative Sums (Cusum) Test over an iterator of N bits
+///
+/// Maps bits to -1/+1 and computes the maximum absolute partial sum z of the random walk,
+/// from the start ([CusumMode::Forward]) or end ([CusumMode::Reverse]) of the sequence,
+/// comparing this with the excursions expected for a random sequence. This requires at
+/// least [CUSUM_MIN_LEN] bits, returning [Error::InsufficientSampleSize] otherwise, see
+/// [nist_cusum_with_min] to override this.
+///
+/// Reverse sums are computed in a single forward pass from the total and the extremes of
+/// the forward partial sums, so the sequence does not need to be buffered. With the
+/// `double` feature the normal CDF is computed in f64 using
+/// [normal_cdf_f64](crate::helpers::normal_cdf_f64).
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_cusum(data: impl AsBits, mode: CusumMode) -> Result<f32, Error> {
+    nist_cusum_result(data, mode)?.check()
+}
+
+/// NIST Cumulative Sums (Cusum) Test at significance level `alpha`
+///
+/// See [nist_cusum] for details
+pub fn nist_cusum_with_alpha(data: impl AsBits, mode: CusumMode, alpha: f32) -> Result<f32, Error> {
+    nist_cusum_result(data, mode)?.at_alpha(alpha)?.check()
+}
+
+/// NIST Cumulative Sums (Cusum) Test returning the [TestResult], with statistic z, the
+/// maximum absolute partial sum
+///
+/// See [nist_cusum] for details
+pub fn nist_cusum_result(data: impl AsBits, mode: CusumMode) -> Result<TestResult, Error> {
+    nist_cusum_with_min_result(data, mode, CUSUM_MIN_LEN)
+}
+
+/// NIST Cumulative Sums (Cusum) Test requiring at least `min_samples` bits in place of
+/// [CUSUM_MIN_LEN]
+///
+/// As with [nist_freq_monobit_with_min](crate::nist::nist_freq_monobit_with_min), below
+/// the recommended minimum the P-value approximation is poor and results are unreliable.
+pub fn nist_cusum_with_min(
+    data: impl AsBits,
+    mode: CusumMode,
+    min_samples: usize,
+) -> Result<f32, Error> {
+    nist_cusum_with_min_result(data, mode, min_samples)?.check()
+}
+
+/// NIST Cumulative Sums (Cusum) Test requiring at least `min_samples` bits, returning the
+/// [TestResult]
+///
+/// See [nist_cusum_with_min] for details
+pub fn nist_cusum_with_min_result(
+    data: impl AsBits,
+    mode: CusumMode,
+    min_samples: usize,
+) -> Result<TestResult, Error> {
+    let mut n = 0usize;
+    let mut s = 0isize;
+
+    // Extremes of the partial sums S_1..S_n, and S_0..S_n-1 for reverse sums
+    let (mut max, mut min) = (0isize, 0isize);
+    let (mut prev_max, mut prev_min) = (0isize, 0isize);
+
+    for d in data.into_bits() {
+        n += 1;
+
+        prev_max = max;
+        prev_min = min;
+
+        match d {
+            true => s += 1,
+            false => s -= 1,
+        }
+
+        max = max.max(s);
+        min = min.min(s);
+    }
+
+    // Check sample size meets minimum requirements
+    if n < min_samples.max(1) {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute maximum absolute partial sum, reverse sums of the last k bits are S_n - S_n-k
+    let z = match mode {
+        CusumMode::Forward => max.max(-min),
+        CusumMode::Reverse => (s - prev_min).max(prev_max - s),
+    };
+
+    // Compute P-value
+    let p = cusum_p(n, z as usize);
+
+    Ok(TestResult::new(p, z as f32))
+}
+
+/// Compute the P-value for maximum partial sum z over n bits
+///
+/// Sums are computed in f64, with the normal CDF in f64 where the `double` feature is
+/// enabled.
+fn cusum_p(n: usize, z: usize) -> f32 {
+    #[cfg(not(feature = "double"))]
+    let phi = |x: f64| crate::helpers::normal_cdf(x as f32) as f64;
+
+    #[cfg(feature = "double")]
+    let phi = crate::helpers::normal_cdf_f64;
+
+    let (n, z) = (n as f64, z as f64);
+    let sqrt_n = math::sqrt(n);
+
+    let mut sum1 = 0.0;
+    let start = math::floor((-n / z + 1.0) / 4.0) as isize;
+    let end = math::floor((n / z - 1.0) / 4.0) as isize;
+    for k in start..=end {
+        let k = k as f64;
+        sum1 += phi((4.0 * k + 1.0) * z / sqrt_n) - phi((4.0 * k - 1.0) * z / sqrt_n);
+    }
+
+    let mut sum2 = 0.0;
+    let start = math::floor((-n / z - 3.0) / 4.0) as isize;
+    for k in start..=end {
+        let k = k as f64;
+        sum2 += phi((4.0 * k + 3.0) * z / sqrt_n) - phi((4.0 * k + 1.0) * z / sqrt_n);
+    }
+
+    (1.0 - sum1 + sum2) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_cusum_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 128];
+        rng.fill_bytes(&mut buff);
+
+        nist_cusum(BitIter::new(&buff), CusumMode::Forward).expect("Cusum test failed");
+        nist_cusum(BitIter::new(&buff), CusumMode::Reverse).expect("Cusum test failed");
+    }
+
+    #[test]
+    fn nist_cusum_spec() {
+        // 100-bit test from specification
+        let buff = bits![
+            1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0,
+            1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1,
+            0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 0
+        ];
+
+        // Check p values match test vectors (z = 16 forward, z = 19 reverse)
+        let p = nist_cusum(buff.iter().by_vals(), CusumMode::Forward).expect("Cusum test failed");
+        assert_approx_eq!(p, 0.219194, 1e-5);
+
+        let p = nist_cusum(buff.iter().by_vals(), CusumMode::Reverse).expect("Cusum test failed");
+        assert_approx_eq!(p, 0.114866, 1e-5);
+    }
+
+    #[test]
+    fn nist_cusum_fail() {
+        nist_cusum(BitIter::from([0xffu8; 128]), CusumMode::Forward)
+            .expect_err("Cusum p > threshold");
+        nist_cusum(BitIter::from([0x00u8; 128]), CusumMode::Reverse)
+            .expect_err("Cusum p > threshold");
+
+        // Balanced overall, but with a large excursion
+        let mut buff = [0x00u8; 128];
+        buff[..64].fill(0xff);
+        nist_cusum(BitIter::new(&buff), CusumMode::Forward).expect_err("Cusum p > threshold");
+        nist_cusum(BitIter::new(&buff), CusumMode::Reverse).expect_err("Cusum p > threshold");
+    }
+
+    #[test]
+    fn nist_cusum_insufficient() {
+        let buff = [0u8; 12];
+
+        assert_eq!(
+            nist_cusum(BitIter::new(&buff), CusumMode::Forward),
+            Err(Error::InsufficientSampleSize(96))
+        );
+
+        // Minimum may be lowered for smoke tests, or raised
+        nist_cusum_with_min(BitIter::from([0b0110_1001u8; 12]), CusumMode::Forward, 96)
+            .expect("Cusum test failed");
+        assert_eq!(
+            nist_cusum_with_min(BitIter::from([0b0110_1001u8; 16]), CusumMode::Reverse, 256),
+            Err(Error::InsufficientSampleSize(128))
+        );
+    }
+}
@@ -0,0 +1,562 @@
+//! NIST Discrete Fourier Transform (Spectral) Test
+
+use crate::math;
+use core::f32::consts::PI;
+
+use crate::helpers::AsBits;
+use crate::{Error, TestResult};
+
+/// Minimum sequence length (in bits) for the DFT test, from the specification
+pub const DFT_MIN_LEN: usize = 1000;
+
+/// Compute the scratch buffer length (in `f32`s) required by [nist_dft] for `n` bits
+pub const fn nist_dft_scratch_len(n: usize) -> usize {
+    2 * n
+}
+
+/// Compute the scratch buffer length (in `f32`s) required by [nist_dft_with_options] with
+/// [PadMode::Bluestein] for `n` bits
+///
+/// This is four times the next power of two of at least `2n - 1`, holding the chirp
+/// sequence and its filter for the convolution, so between 8 n and 16 n.
+pub const fn nist_dft_bluestein_scratch_len(n: usize) -> usize {
+    4 * (2 * n).saturating_sub(1).next_power_of_two()
+}
+
+/// Handling of sequence lengths that are not a power of two for [nist_dft_with_options]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum PadMode {
+    /// Return [Error::InvalidParameter], as required by the specification
+    #[default]
+    Error,
+    /// Zero-pad the sequence to the next power of two
+    ZeroPad,
+    /// Transform the exact sequence length using Bluestein's (chirp-z) algorithm,
+    /// requiring a `scratch` buffer of [nist_dft_bluestein_scratch_len]
+    Bluestein,
+}
+
+/// Window applied to the sequence before the transform for [nist_dft_with_options]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum WindowFn {
+    /// No windowing, as used by the specification
+    #[default]
+    Rectangular,
+    /// Hann (raised cosine) window, `w_i = (1 - cos(2π i / n)) / 2` over the n bits
+    Hann,
+}
+
+/// Options for [nist_dft_with_options]
+///
+/// The default reproduces the specification, with a rectangular window and no padding.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct DftOptions {
+    /// Handling of lengths that are not a power of two
+    pub pad: PadMode,
+    /// Window applied before the transform
+    pub window: WindowFn,
+}
+
+impl DftOptions {
+    /// Create new options from padding and window modes
+    pub const fn new(pad: PadMode, window: WindowFn) -> Self {
+        Self { pad, window }
+    }
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test over an iterator of N bits
+///
+/// This detects periodic features by counting the DFT peaks exceeding the 95% threshold.
+/// As this is `no_std` and does not allocate, the caller provides a `scratch` buffer of
+/// at least [nist_dft_scratch_len] `f32`s, used for an in-place radix-2 FFT over
+/// interleaved real / imaginary components.
+///
+/// The bit count n must be a power of two of at least 1024, returning
+/// [Error::InvalidParameter] for other lengths or where `data` exceeds the scratch space,
+/// and [Error::InsufficientSampleSize] below [DFT_MIN_LEN]. See [nist_dft_with_options]
+/// for arbitrary lengths, padding and windowing.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_dft(data: impl AsBits, scratch: &mut [f32]) -> Result<f32, Error> {
+    nist_dft_result(data, scratch)?.check()
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test at significance level `alpha`
+///
+/// See [nist_dft] for details
+pub fn nist_dft_with_alpha(
+    data: impl AsBits,
+    scratch: &mut [f32],
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_dft_result(data, scratch)?.at_alpha(alpha)?.check()
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test returning the [TestResult], with
+/// statistic d, the normalised difference in peak counts below the threshold
+///
+/// See [nist_dft] for details
+pub fn nist_dft_result(data: impl AsBits, scratch: &mut [f32]) -> Result<TestResult, Error> {
+    nist_dft_with_options_result(data, scratch, DftOptions::default())
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test with padding and windowing options
+///
+/// With [PadMode::ZeroPad] a sequence of n bits is extended with zeros to the next power
+/// of two m, requiring a `scratch` buffer of [nist_dft_scratch_len]`(m)`, and peaks are
+/// counted over the m / 2 bins of the padded transform. With [PadMode::Bluestein] the
+/// transform is computed at the exact length n as a convolution of power-of-two length,
+/// so the statistic is that of the specification for any n (as supported by the NIST
+/// reference implementation) and no captured data is discarded. Windows other than
+/// [WindowFn::Rectangular] weight each bit by `w_i` before the transform.
+///
+/// The peak threshold is scaled to the energy `Σ w_i^2` of the (windowed) sequence, so
+/// each bin of a random sequence remains below this with probability 0.95. Padding and
+/// windowing both correlate neighbouring bins, so P-values are then approximate and
+/// somewhat more likely to fail random sequences than the specification test. Default
+/// options are identical to [nist_dft].
+pub fn nist_dft_with_options(
+    data: impl AsBits,
+    scratch: &mut [f32],
+    options: DftOptions,
+) -> Result<f32, Error> {
+    nist_dft_with_options_result(data, scratch, options)?.check()
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test with options at significance level
+/// `alpha`
+///
+/// See [nist_dft_with_options] for details
+pub fn nist_dft_with_options_with_alpha(
+    data: impl AsBits,
+    scratch: &mut [f32],
+    options: DftOptions,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_dft_with_options_result(data, scratch, options)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Discrete Fourier Transform (Spectral) Test with options returning the
+/// [TestResult], with statistic d as for [nist_dft_result]
+///
+/// See [nist_dft_with_options] for details
+pub fn nist_dft_with_options_result(
+    data: impl AsBits,
+    scratch: &mut [f32],
+    options: DftOptions,
+) -> Result<TestResult, Error> {
+    let mut n = 0;
+
+    // Load bits as -1/+1 into the real components
+    for d in data.into_bits() {
+        if nist_dft_scratch_len(n + 1) > scratch.len() {
+            return Err(Error::InvalidParameter);
+        }
+
+        scratch[2 * n] = match d {
+            true => 1.0,
+            false => -1.0,
+        };
+        scratch[2 * n + 1] = 0.0;
+
+        n += 1;
+    }
+
+    // Check sample size meets minimum requirements
+    if n < DFT_MIN_LEN {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Check length is suitable for radix-2 transform, padding where enabled
+    let m = match (n.is_power_of_two(), options.pad) {
+        (true, _) | (false, PadMode::Bluestein) => n,
+        (false, PadMode::ZeroPad) => n.next_power_of_two(),
+        (false, PadMode::Error) => return Err(Error::InvalidParameter),
+    };
+    let required = match m.is_power_of_two() {
+        true => nist_dft_scratch_len(m),
+        false => nist_dft_bluestein_scratch_len(n),
+    };
+    if required > scratch.len() {
+        return Err(Error::InvalidParameter);
+    }
+    scratch[nist_dft_scratch_len(n)..nist_dft_scratch_len(m)].fill(0.0);
+
+    // Apply window, computing the energy of the weighted sequence
+    let energy = match options.window {
+        WindowFn::Rectangular => n as f32,
+        WindowFn::Hann => {
+            let mut e = 0f64;
+            for i in 0..n {
+                let w = 0.5 - 0.5 * math::cos(2.0 * core::f64::consts::PI * i as f64 / n as f64);
+                scratch[2 * i] *= w as f32;
+                e += w * w;
+            }
+            e as f32
+        }
+    };
+
+    // Compute DFT
+    match m.is_power_of_two() {
+        true => fft(&mut scratch[..nist_dft_scratch_len(m)]),
+        false => bluestein(&mut scratch[..required], n),
+    }
+    let buff = &scratch[..nist_dft_scratch_len(m)];
+
+    // Count peaks below the 95% threshold in the first half of the spectrum
+    let t = math::sqrtf(math::logf(1.0 / 0.05) * energy);
+    let mut n1 = 0;
+    for c in buff[..m].chunks_exact(2) {
+        if math::hypotf(c[0], c[1]) < t {
+            n1 += 1;
+        }
+    }
+
+    // Compute normalised difference between observed and expected peaks
+    let n0 = 0.95 * m as f32 / 2.0;
+    let d = (n1 as f32 - n0) / math::sqrtf(m as f32 * 0.95 * 0.05 / 4.0);
+
+    // Compute P-value
+    let p = math::erfcf(math::fabsf(d) / math::sqrtf(2.0));
+
+    Ok(TestResult::new(p, d))
+}
+
+/// In-place iterative radix-2 FFT over interleaved real / imaginary components
+///
+/// The number of complex values (`buff.len() / 2`) must be a power of two
+fn fft(buff: &mut [f32]) {
+    let n = buff.len() / 2;
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            buff.swap(2 * i, 2 * j);
+            buff.swap(2 * i + 1, 2 * j + 1);
+        }
+    }
+
+    // Butterflies
+    let mut len = 2;
+    while len <= n {
+        let step = -2.0 * PI / len as f32;
+
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                // Twiddles are computed directly to avoid accumulating error
+                let (w_im, w_re) = math::sincosf(step * k as f32);
+
+                let a = start + k;
+                let b = a + len / 2;
+
+                let (b_re, b_im) = (buff[2 * b], buff[2 * b + 1]);
+                let t_re = b_re * w_re - b_im * w_im;
+                let t_im = b_re * w_im + b_im * w_re;
+
+                let (a_re, a_im) = (buff[2 * a], buff[2 * a + 1]);
+                buff[2 * b] = a_re - t_re;
+                buff[2 * b + 1] = a_im - t_im;
+                buff[2 * a] = a_re + t_re;
+                buff[2 * a + 1] = a_im + t_im;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+/// In-place DFT of the first `n` complex values of `buff` using Bluestein's algorithm
+///
+/// This rewrites `X_k = Σ x_j e^(-iπ 2jk / n)` using `2jk = j^2 + k^2 - (k - j)^2` as the
+/// convolution of the chirp-modulated input `a_j = x_j w_j` with `b_j = conj(w_j)`, for
+/// `w_j = e^(-iπ j^2 / n)`, evaluated with radix-2 FFTs of length M >= 2n - 1. `buff` must
+/// hold 2 M complex values, the first M for `a` and the second for `b`.
+fn bluestein(buff: &mut [f32], n: usize) {
+    let m = buff.len() / 4;
+    let (a, b) = buff.split_at_mut(2 * m);
+
+    // Chirp, with j^2 reduced mod 2n to keep the angle accurate for large j
+    let chirp = |j: usize| {
+        let r = (j as u64 * j as u64 % (2 * n as u64)) as f64;
+        let (im, re) = math::sincos(-core::f64::consts::PI * r / n as f64);
+        (re as f32, im as f32)
+    };
+
+    // Modulate input and build the (symmetric) filter
+    b.fill(0.0);
+    for j in 0..n {
+        let (w_re, w_im) = chirp(j);
+
+        let (x_re, x_im) = (a[2 * j], a[2 * j + 1]);
+        a[2 * j] = x_re * w_re - x_im * w_im;
+        a[2 * j + 1] = x_re * w_im + x_im * w_re;
+
+        b[2 * j] = w_re;
+        b[2 * j + 1] = -w_im;
+        if j > 0 {
+            b[2 * (m - j)] = w_re;
+            b[2 * (m - j) + 1] = -w_im;
+        }
+    }
+    a[2 * n..].fill(0.0);
+
+    // Convolve, with the inverse transform computed as conj(FFT(conj(.))) / M
+    fft(a);
+    fft(b);
+    for (a, b) in a.chunks_exact_mut(2).zip(b.chunks_exact(2)) {
+        let re = a[0] * b[0] - a[1] * b[1];
+        let im = a[0] * b[1] + a[1] * b[0];
+        a[0] = re;
+        a[1] = -im;
+    }
+    fft(a);
+
+    // Demodulate output
+    for k in 0..n {
+        let (w_re, w_im) = chirp(k);
+        let (c_re, c_im) = (a[2 * k] / m as f32, -a[2 * k + 1] / m as f32);
+
+        a[2 * k] = c_re * w_re - c_im * w_im;
+        a[2 * k + 1] = c_re * w_im + c_im * w_re;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec::Vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    /// 1024-bit random test vector
+    const VECTOR: [u8; 128] = [
+        130, 183, 14, 238, 127, 26, 80, 57, 190, 240, 126, 194, 52, 127, 6, 110, 208, 143, 93, 199,
+        81, 36, 71, 227, 64, 67, 0, 2, 107, 110, 84, 85, 148, 160, 101, 104, 93, 100, 196, 152, 11,
+        184, 212, 84, 74, 135, 33, 169, 154, 1, 173, 33, 158, 181, 156, 246, 161, 94, 246, 241, 90,
+        29, 131, 11, 183, 206, 9, 214, 187, 192, 4, 231, 23, 92, 100, 60, 125, 236, 176, 181, 128,
+        236, 55, 188, 151, 18, 221, 46, 106, 174, 185, 75, 174, 141, 47, 159, 162, 156, 90, 40, 76,
+        158, 247, 82, 24, 41, 207, 16, 121, 176, 128, 233, 215, 74, 28, 16, 252, 171, 106, 66, 67,
+        211, 54, 86, 222, 190, 76, 30,
+    ];
+
+    #[test]
+    fn nist_dft_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 512];
+        rng.fill_bytes(&mut buff);
+
+        let mut scratch = [0f32; nist_dft_scratch_len(4096)];
+        nist_dft(BitIter::new(&buff), &mut scratch).expect("DFT test failed");
+    }
+
+    #[test]
+    fn nist_dft_vector() {
+        let mut scratch = [0f32; nist_dft_scratch_len(1024)];
+        let p = nist_dft(BitIter::new(&VECTOR), &mut scratch).expect("DFT test failed");
+
+        // Check p value matches reference (N1 = 490)
+        assert_approx_eq!(p, 0.301898, 1e-5);
+    }
+
+    #[test]
+    fn nist_dft_fail() {
+        let mut scratch = [0f32; nist_dft_scratch_len(1024)];
+
+        // Periodic input concentrates the spectrum in a few peaks
+        nist_dft(BitIter::from([0x0fu8; 128]), &mut scratch).expect_err("DFT p > threshold");
+        nist_dft(BitIter::from([0b1001_0110u8; 128]), &mut scratch).expect_err("DFT p > threshold");
+    }
+
+    #[test]
+    fn nist_dft_invalid() {
+        let mut scratch = [0f32; nist_dft_scratch_len(1024)];
+
+        // Non power-of-two length
+        assert_eq!(
+            nist_dft(BitIter::new(&VECTOR).take(1000), &mut scratch),
+            Err(Error::InvalidParameter)
+        );
+
+        // Insufficient scratch space
+        assert_eq!(
+            nist_dft(BitIter::new(&VECTOR), &mut scratch[..2000]),
+            Err(Error::InvalidParameter)
+        );
+
+        // Insufficient data
+        assert_eq!(
+            nist_dft(BitIter::new(&VECTOR).take(512), &mut scratch),
+            Err(Error::InsufficientSampleSize(512))
+        );
+    }
+
+    #[test]
+    fn nist_dft_options() {
+        // Default options match the specification test
+        let mut scratch = [0f32; nist_dft_scratch_len(4096)];
+        assert_eq!(
+            nist_dft_with_options_result(
+                BitIter::new(&VECTOR),
+                &mut scratch,
+                DftOptions::default()
+            ),
+            nist_dft_result(BitIter::new(&VECTOR), &mut scratch)
+        );
+
+        let mut buff = [0u8; 375];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        // Zero-padding a non power-of-two length no longer errors
+        let padded = DftOptions::new(PadMode::ZeroPad, WindowFn::Rectangular);
+        let hann = DftOptions::new(PadMode::ZeroPad, WindowFn::Hann);
+        assert_eq!(
+            nist_dft(BitIter::new(&buff), &mut scratch),
+            Err(Error::InvalidParameter)
+        );
+        nist_dft_with_options(BitIter::new(&buff), &mut scratch, padded).expect("DFT test failed");
+        nist_dft_with_options(BitIter::new(&buff), &mut scratch, hann).expect("DFT test failed");
+
+        // Padded length must fit the scratch space
+        assert_eq!(
+            nist_dft_with_options(BitIter::new(&buff), &mut scratch[..6000], padded),
+            Err(Error::InvalidParameter)
+        );
+
+        // Injected tone, with most bits following a square wave of period 10
+        let tone = || {
+            BitIter::new(&buff).enumerate().map(|(i, b)| match i % 3 {
+                0 => b,
+                _ => i % 10 < 5,
+            })
+        };
+        for o in [padded, hann] {
+            nist_dft_with_options(tone(), &mut scratch, o).expect_err("DFT p > threshold");
+        }
+    }
+
+    #[test]
+    fn nist_dft_bluestein() {
+        let mut buff = [0u8; 375];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+
+        let exact = DftOptions::new(PadMode::Bluestein, WindowFn::Rectangular);
+        let padded = DftOptions::new(PadMode::ZeroPad, WindowFn::Rectangular);
+        let mut scratch = [0f32; nist_dft_bluestein_scratch_len(3000)];
+        assert_eq!(scratch.len(), 8192 * 4);
+
+        // Exact length and zero-padded runs of random data both pass, with the exact run
+        // counting peaks over the 1500 bins of the 3000 bit sequence
+        let r = nist_dft_with_options_result(BitIter::new(&buff), &mut scratch, exact).unwrap();
+        let pad = nist_dft_with_options_result(BitIter::new(&buff), &mut scratch, padded).unwrap();
+        assert!(r.passed && pad.passed, "{r:?} {pad:?}");
+
+        // Peak count matches a direct f64 DFT
+        let x: Vec<f64> = BitIter::new(&buff)
+            .map(|b| if b { 1.0 } else { -1.0 })
+            .collect();
+        let t = math::sqrt(math::log(20.0) * 3000.0);
+        let n1 = (0..1500)
+            .filter(|k| {
+                let (mut re, mut im) = (0.0, 0.0);
+                for (j, v) in x.iter().enumerate() {
+                    let (s, c) =
+                        math::sincos(-2.0 * core::f64::consts::PI * (j * k % 3000) as f64 / 3000.0);
+                    re += v * c;
+                    im += v * s;
+                }
+                libm::hypot(re, im) < t
+            })
+            .count();
+        let d = (n1 as f32 - 0.95 * 1500.0) / math::sqrtf(3000.0 * 0.95 * 0.05 / 4.0);
+        assert_approx_eq!(r.statistic, d, 1e-4);
+
+        // Both detect an injected tone
+        let tone = || {
+            BitIter::new(&buff).enumerate().map(|(i, b)| match i % 3 {
+                0 => b,
+                _ => i % 10 < 5,
+            })
+        };
+        for o in [exact, padded] {
+            nist_dft_with_options(tone(), &mut scratch, o).expect_err("DFT p > threshold");
+        }
+
+        // Power-of-two lengths use the radix-2 transform and smaller scratch space
+        let mut scratch = [0f32; nist_dft_scratch_len(1024)];
+        assert_eq!(
+            nist_dft_with_options_result(BitIter::new(&VECTOR), &mut scratch, exact),
+            nist_dft_result(BitIter::new(&VECTOR), &mut scratch)
+        );
+        assert_eq!(
+            nist_dft_with_options(BitIter::new(&VECTOR).take(1000), &mut scratch, exact),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn bluestein_matches_fft() {
+        // Matches the radix-2 transform on power-of-two lengths
+        let mut x = [0f32; 64];
+        for (i, v) in x.iter_mut().enumerate() {
+            *v = libm::sinf(i as f32 * 0.7) + (i % 5) as f32;
+        }
+
+        let mut expected = x;
+        fft(&mut expected);
+
+        let mut buff = [0f32; 4 * 64];
+        buff[..64].copy_from_slice(&x);
+        bluestein(&mut buff, 32);
+        for (v, e) in buff.iter().zip(&expected) {
+            assert_approx_eq!(v, e, 1e-4);
+        }
+
+        // Constant of odd length is an impulse
+        let mut buff = [0f32; 4 * 16];
+        for i in 0..5 {
+            buff[2 * i] = 1.0;
+        }
+        bluestein(&mut buff, 5);
+        for (v, e) in buff
+            .iter()
+            .zip([5.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        {
+            assert_approx_eq!(v, e, 1e-5);
+        }
+    }
+
+    #[test]
+    fn fft_impulse() {
+        // FFT of an impulse is flat
+        let mut buff = [0f32; 16];
+        buff[0] = 1.0;
+        fft(&mut buff);
+
+        for c in buff.chunks_exact(2) {
+            assert_approx_eq!(c[0], 1.0);
+            assert_approx_eq!(c[1], 0.0);
+        }
+
+        // FFT of a constant is an impulse
+        let mut buff = [1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0];
+        fft(&mut buff);
+
+        for (v, e) in buff.iter().zip([4.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]) {
+            assert_approx_eq!(v, e);
+        }
+    }
+}
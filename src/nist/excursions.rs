@@ -0,0 +1,381 @@
+//! NIST Random Excursions and Random Excursions Variant Tests
+
+use super::igamma_upper;
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult, DEFAULT_ALPHA};
+
+/// Minimum number of cycles (J) for the random excursions tests
+pub const EXCURSIONS_MIN_CYCLES: usize = 500;
+
+/// States tested by the random excursions test
+const STATES: [isize; 8] = [-4, -3, -2, -1, 1, 2, 3, 4];
+
+/// States tested by the random excursions variant test
+const VARIANT_STATES: [isize; 18] = [
+    -9, -8, -7, -6, -5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 6, 7, 8, 9,
+];
+
+/// Probabilities of a state |x| = 1..4 being visited k = 0..5+ times in a cycle
+const PROBS: [[f32; 6]; 4] = [
+    [0.5, 0.25, 0.125, 0.0625, 0.03125, 0.03125],
+    [0.75, 0.0625, 0.046875, 0.03515625, 0.026367188, 0.07910156],
+    [
+        0.8333333,
+        0.027777778,
+        0.023148148,
+        0.019290123,
+        0.016075103,
+        0.080375514,
+    ],
+    [
+        0.875,
+        0.015625,
+        0.013671875,
+        0.011962891,
+        0.010467529,
+        0.073272705,
+    ],
+];
+
+/// NIST Random Excursions Test over an iterator of N bits
+///
+/// Forms the random walk of -1/+1 partial sums, splitting this into cycles at each return
+/// to zero, and compares the number of visits to each state x in -4..=-1, 1..=4 per cycle
+/// against that expected for a random sequence. Returns the eight P-values ordered by
+/// state, failing where any of these is below the threshold.
+///
+/// This requires at least max(500, 0.005 * sqrt(n)) cycles, returning
+/// [Error::InsufficientSampleSize] with the number of cycles J otherwise. As J scales with
+/// sqrt(n) the specification recommends n >= 10^6 bits.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_random_excursions(data: impl AsBits) -> Result<[f32; 8], Error> {
+    check_all(nist_random_excursions_result(data)?, DEFAULT_ALPHA)
+}
+
+/// NIST Random Excursions Test at significance level `alpha`
+///
+/// See [nist_random_excursions] for details
+pub fn nist_random_excursions_with_alpha(data: impl AsBits, alpha: f32) -> Result<[f32; 8], Error> {
+    check_all(nist_random_excursions_result(data)?, alpha)
+}
+
+/// NIST Random Excursions Test returning a [TestResult] per state, with statistic x^2
+///
+/// See [nist_random_excursions] for details
+pub fn nist_random_excursions_result(data: impl AsBits) -> Result<[TestResult; 8], Error> {
+    // Per-cycle visits for each state, and cycle counts by number of visits
+    let mut visits = [0usize; 8];
+    let mut counts = [[0usize; 6]; 8];
+
+    let (n, j) = walk(data.into_bits(), |step| match step {
+        Step::Visit(s @ -4..=-1) => visits[(s + 4) as usize] += 1,
+        Step::Visit(s @ 1..=4) => visits[(s + 3) as usize] += 1,
+        Step::Visit(_) => (),
+        Step::CycleEnd => {
+            for (c, v) in counts.iter_mut().zip(visits.iter_mut()) {
+                c[(*v).min(5)] += 1;
+                *v = 0;
+            }
+        }
+    });
+
+    // Check we have sufficient cycles
+    check_cycles(n, j)?;
+
+    Ok(excursions_results(&counts, j))
+}
+
+/// NIST Random Excursions Variant Test over an iterator of N bits
+///
+/// Forms the random walk of -1/+1 partial sums as in [nist_random_excursions], and
+/// compares the total number of visits to each state x in -9..=-1, 1..=9 across all
+/// cycles against the number of cycles J. Returns the eighteen P-values ordered by
+/// state, failing where any of these is below the threshold.
+///
+/// This has the same minimum cycle requirements as [nist_random_excursions].
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_random_excursions_variant(data: impl AsBits) -> Result<[f32; 18], Error> {
+    check_all(nist_random_excursions_variant_result(data)?, DEFAULT_ALPHA)
+}
+
+/// NIST Random Excursions Variant Test at significance level `alpha`
+///
+/// See [nist_random_excursions_variant] for details
+pub fn nist_random_excursions_variant_with_alpha(
+    data: impl AsBits,
+    alpha: f32,
+) -> Result<[f32; 18], Error> {
+    check_all(nist_random_excursions_variant_result(data)?, alpha)
+}
+
+/// NIST Random Excursions Variant Test returning a [TestResult] per state, with statistic
+/// ξ(x), the total number of visits
+///
+/// See [nist_random_excursions_variant] for details
+pub fn nist_random_excursions_variant_result(data: impl AsBits) -> Result<[TestResult; 18], Error> {
+    // Total visits for each state
+    let mut visits = [0usize; 18];
+
+    let (n, j) = walk(data.into_bits(), |step| match step {
+        Step::Visit(s @ -9..=-1) => visits[(s + 9) as usize] += 1,
+        Step::Visit(s @ 1..=9) => visits[(s + 8) as usize] += 1,
+        _ => (),
+    });
+
+    // Check we have sufficient cycles
+    check_cycles(n, j)?;
+
+    Ok(variant_results(&visits, j))
+}
+
+/// Random walk step events
+enum Step {
+    /// Visit to a non-zero state
+    Visit(isize),
+    /// Completion of a cycle, on return to zero or at the end of the sequence
+    CycleEnd,
+}
+
+/// Walk the -1/+1 partial sums over `data`, returning the sequence length and the
+/// number of cycles J
+fn walk(data: impl Iterator<Item = bool>, mut f: impl FnMut(Step)) -> (usize, usize) {
+    let mut n = 0;
+    let mut s = 0isize;
+    let mut j = 0;
+
+    for d in data {
+        n += 1;
+
+        match d {
+            true => s += 1,
+            false => s -= 1,
+        }
+
+        match s {
+            0 => {
+                j += 1;
+                f(Step::CycleEnd);
+            }
+            _ => f(Step::Visit(s)),
+        }
+    }
+
+    // The walk is bounded by zeros, so a non-zero final sum ends a cycle
+    if s != 0 {
+        j += 1;
+        f(Step::CycleEnd);
+    }
+
+    (n, j)
+}
+
+/// Check the number of cycles J meets the minimum for a sequence of n bits
+fn check_cycles(n: usize, j: usize) -> Result<(), Error> {
+    if j < EXCURSIONS_MIN_CYCLES.max(math::ceilf(0.005 * math::sqrtf(n as f32)) as usize) {
+        return Err(Error::InsufficientSampleSize(j));
+    }
+
+    Ok(())
+}
+
+/// Check results for all states at significance level `alpha`, returning the P-values
+/// where all states pass
+fn check_all<const N: usize>(results: [TestResult; N], alpha: f32) -> Result<[f32; N], Error> {
+    let mut p = [0.0; N];
+
+    for (p, r) in p.iter_mut().zip(results) {
+        *p = r.at_alpha(alpha)?.check()?;
+    }
+
+    Ok(p)
+}
+
+/// Compute the result for each state from cycle counts by number of visits
+fn excursions_results(counts: &[[usize; 6]; 8], j: usize) -> [TestResult; 8] {
+    let mut r = [TestResult::new(0.0, 0.0); 8];
+
+    for ((r, c), x) in r.iter_mut().zip(counts).zip(STATES) {
+        let probs = &PROBS[x.unsigned_abs() - 1];
+
+        // Compute x^2 over visit categories
+        let mut x2 = 0.0;
+        for (v, pi) in c.iter().zip(probs) {
+            let e = j as f32 * pi;
+            x2 += math::powf(*v as f32 - e, 2.0) / e;
+        }
+
+        let p = igamma_upper(5.0 / 2.0, x2 / 2.0);
+        *r = TestResult::new(p, x2);
+    }
+
+    r
+}
+
+/// Compute the result for each state from total visits
+fn variant_results(visits: &[usize; 18], j: usize) -> [TestResult; 18] {
+    let mut r = [TestResult::new(0.0, 0.0); 18];
+
+    for ((r, v), x) in r.iter_mut().zip(visits).zip(VARIANT_STATES) {
+        let d = math::fabsf(*v as f32 - j as f32);
+        let s = math::sqrtf(2.0 * j as f32 * (4.0 * x.unsigned_abs() as f32 - 2.0));
+
+        let p = math::erfcf(d / s);
+        *r = TestResult::new(p, *v as f32);
+    }
+
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn nist_random_excursions_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 1_000_000 / 8];
+
+        // Cycle count is random, so retry until we have sufficient cycles
+        loop {
+            rng.fill_bytes(&mut buff);
+
+            match nist_random_excursions_result(BitIter::new(&buff)) {
+                Err(Error::InsufficientSampleSize(_)) => continue,
+                r => {
+                    // With 8 P-values occasional failures are expected
+                    let r = r.expect("Random excursions test failed");
+                    assert!(r.iter().filter(|r| !r.passed).count() <= 2);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nist_random_excursions_ex() {
+        // Example from specification, 0110110101 has J = 3 cycles
+        let mut counts = [[0usize; 6]; 8];
+        counts.iter_mut().for_each(|c| c[0] = 3);
+        counts[3] = [2, 1, 0, 0, 0, 0];
+        counts[4] = [1, 1, 0, 1, 0, 0];
+        counts[5] = [2, 0, 0, 1, 0, 0];
+
+        // Check p value for x = +1 matches test vector (x^2 = 4.333033), the
+        // specification uses probabilities rounded to four places
+        let r = excursions_results(&counts, 3);
+        assert_approx_eq!(r[4].statistic, 4.333033, 1e-3);
+        assert_approx_eq!(r[4].p_value, 0.502529, 1e-4);
+    }
+
+    #[test]
+    fn nist_random_excursions_vector() {
+        let mut buff = [0u8; 1_000_000 / 8];
+        lcg_fill(&mut buff, 8);
+
+        let p = nist_random_excursions(BitIter::new(&buff)).expect("Random excursions test failed");
+
+        // Check p values match reference (J = 859)
+        let expected = [
+            0.363498, 0.822029, 0.478589, 0.889090, 0.262404, 0.795667, 0.692854, 0.889897,
+        ];
+        for (p, e) in p.iter().zip(expected) {
+            assert_approx_eq!(p, e, 1e-3);
+        }
+    }
+
+    #[test]
+    fn nist_random_excursions_variant_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 1_000_000 / 8];
+
+        // Cycle count is random, so retry until we have sufficient cycles
+        loop {
+            rng.fill_bytes(&mut buff);
+
+            match nist_random_excursions_variant_result(BitIter::new(&buff)) {
+                Err(Error::InsufficientSampleSize(_)) => continue,
+                r => {
+                    // With 18 P-values occasional failures are expected, and as these all
+                    // depend on J failures tend to occur together
+                    let r = r.expect("Random excursions variant test failed");
+                    assert!(r.iter().filter(|r| !r.passed).count() <= 9);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nist_random_excursions_variant_ex() {
+        // Example from specification, 0110110101 has J = 3 cycles
+        let buff = bits![0, 1, 1, 0, 1, 1, 0, 1, 0, 1];
+
+        let mut visits = [0usize; 18];
+        let (n, j) = walk(buff.iter().by_vals(), |step| match step {
+            Step::Visit(s @ -9..=-1) => visits[(s + 9) as usize] += 1,
+            Step::Visit(s @ 1..=9) => visits[(s + 8) as usize] += 1,
+            _ => (),
+        });
+
+        assert_eq!((n, j), (10, 3));
+        assert_eq!(&visits[8..11], &[1, 4, 3]);
+
+        // Check p values match test vector, x = +1 has p = 0.683091
+        let r = variant_results(&visits, j);
+        let expected = [
+            0.766433, 0.751830, 0.734095, 0.711923, 0.683091, 0.643429, 0.583882, 0.479500,
+            0.414216, 0.683091, 1.0, 0.583882, 0.643429, 0.683091, 0.711923, 0.734095, 0.751830,
+            0.766433,
+        ];
+        for (r, e) in r.iter().zip(expected) {
+            assert_approx_eq!(r.p_value, e, 1e-5);
+        }
+    }
+
+    #[test]
+    fn nist_random_excursions_variant_vector() {
+        let mut buff = [0u8; 1_000_000 / 8];
+        lcg_fill(&mut buff, 8);
+
+        let p = nist_random_excursions_variant(BitIter::new(&buff))
+            .expect("Random excursions variant test failed");
+
+        // Check p values match reference (J = 859)
+        let expected = [
+            0.367522, 0.275653, 0.247023, 0.305043, 0.256824, 0.427581, 0.689736, 0.625887,
+            0.753794, 0.484140, 0.503748, 0.627301, 0.454614, 0.590013, 0.498716, 0.213279,
+            0.195077, 0.200029,
+        ];
+        for (p, e) in p.iter().zip(expected) {
+            assert_approx_eq!(p, e, 1e-4);
+        }
+    }
+
+    #[test]
+    fn nist_random_excursions_fail() {
+        // Regular excursions to +/-4 return far more cycles than expected with 4 visits
+        nist_random_excursions(BitIter::from([0xf0u8; 1024]))
+            .expect_err("Random excursions p > threshold");
+    }
+
+    #[test]
+    fn nist_random_excursions_insufficient() {
+        // Balanced bits return to zero every two steps, J = n / 2
+        assert_eq!(
+            nist_random_excursions(BitIter::from([0x55u8; 64])),
+            Err(Error::InsufficientSampleSize(256))
+        );
+        assert_eq!(
+            nist_random_excursions_variant(BitIter::from([0x55u8; 64])),
+            Err(Error::InsufficientSampleSize(256))
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Fixed-point pass / fail variants of the monobit, runs, and autocorrelation tests
+
+use super::autocorrelation::count_mismatches;
+use super::runs::count_runs;
+use super::{MONOBIT_MIN_LEN, RUNS_MIN_LEN};
+use crate::helpers::AsBits;
+use crate::Error;
+
+/// Fractional bits of the squared bound
+const Z2_SHIFT: u32 = 16;
+
+/// Precomputed `floor(z_α^2 * 2^16)` for standard significance levels
+const Z2_BOUNDS: [(f32, u64); 6] = [
+    (0.05, 251_753),
+    (0.01, 434_824),
+    (0.001, 709_595),
+    (0.0001, 991_999),
+    (0.00001, 1_278_700),
+    (0.000001, 1_568_153),
+];
+
+/// Fetch the squared bound `z_α^2` in Q16 for significance level `alpha`
+fn z2_bound(alpha: f32) -> Result<u64, Error> {
+    // Inverted to reject NaN
+    if !(alpha > 0.0 && alpha < 1.0) {
+        return Err(Error::InvalidAlpha(alpha));
+    }
+
+    if let Some((_, z2)) = Z2_BOUNDS.iter().find(|(a, _)| *a == alpha) {
+        return Ok(*z2);
+    }
+
+    let z = crate::helpers::normal_quantile_f64(1.0 - alpha as f64 / 2.0);
+    Ok((z * z * (1u64 << Z2_SHIFT) as f64) as u64)
+}
+
+/// Check a squared normal statistic `num / den` against the squared bound `z2`
+fn within(num: u128, den: u128, z2: u64) -> bool {
+    num.saturating_mul(1 << Z2_SHIFT) <= den * z2 as u128
+}
+
+/// NIST Frequency (Monobit) Test at significance level `alpha`, returning whether the
+/// sequence passes using integer arithmetic
+///
+/// For targets without an FPU this compares integer statistics against a fixed-point
+/// bound rather than computing a P-value, avoiding software `erfc` entirely. The test
+/// passes where the P-value would be at least α, that is where the normal statistic is
+/// within `z_α = Φ^-1(1 - α / 2)`, so here where `v^2 <= z_α^2 n` for the sum v of the
+/// -1/+1 bits. As with
+/// [nist_freq_monobit_with_alpha](super::nist_freq_monobit_with_alpha) this requires at
+/// least [MONOBIT_MIN_LEN] bits.
+///
+/// The bound `z_α^2` is held in Q16 fixed point and rounded down, so results match the
+/// float path except within about 2^-16 (relative) of the threshold, where this may fail
+/// a sequence that the float path passes but never the reverse. The float path has
+/// comparable rounding error of its own in f32 so neither is more correct at the
+/// boundary. Standard significance levels (0.05, [DEFAULT_ALPHA](crate::DEFAULT_ALPHA),
+/// 0.001, 1e-4, 1e-5 and 1e-6) use precomputed bounds, other values compute the bound
+/// once per call with [normal_quantile_f64](crate::helpers::normal_quantile_f64).
+pub fn nist_freq_monobit_passfail(data: impl AsBits, alpha: f32) -> Result<bool, Error> {
+    let z2 = z2_bound(alpha)?;
+
+    let mut n = 0usize;
+    let mut ones = 0usize;
+    for d in data.into_bits() {
+        n += 1;
+        ones += d as usize;
+    }
+
+    // Check sample size meets minimum requirements
+    if n < MONOBIT_MIN_LEN {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    let v = (2 * ones).abs_diff(n) as u128;
+    Ok(within(v * v, n as u128, z2))
+}
+
+/// NIST Runs Test at significance level `alpha`, returning whether the sequence passes
+/// using integer arithmetic
+///
+/// For n bits with k ones and V runs, this checks the frequency pre-condition as
+/// `(2k - n)^2 < 16 n`, then passes where `(n V - 2 k (n - k))^2 n <= 4 z_α^2 k^2 (n - k)^2`,
+/// equivalent to [nist_runs_with_alpha](super::nist_runs_with_alpha) returning `Ok`. As
+/// with the float path this requires at least [RUNS_MIN_LEN] bits.
+///
+/// See [nist_freq_monobit_passfail] for the precision of the fixed-point bound.
+pub fn nist_runs_passfail(data: impl AsBits, alpha: f32) -> Result<bool, Error> {
+    let z2 = z2_bound(alpha)?;
+
+    let (n, ones, runs) = count_runs(data);
+
+    // Check sample size meets minimum requirements
+    if n < RUNS_MIN_LEN {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Check frequency pre-condition, |k / n - 1/2| < 2 / sqrt(n)
+    let (n, k, runs) = (n as u128, ones as u128, runs as u128);
+    let f = (2 * k).abs_diff(n);
+    if f * f >= 16 * n {
+        return Ok(false);
+    }
+
+    // Compare (n V - 2 k (n - k))^2 / 4 k^2 (n - k)^2 n^-1 against the bound, dividing
+    // through by n (rounding down) to keep within 128 bits
+    let c = k * (n - k);
+    let d = (n * runs).abs_diff(2 * c);
+    Ok(within(d.saturating_mul(d), 4 * (c * c / n), z2))
+}
+
+/// Autocorrelation Test at significance level `alpha`, returning whether the sequence
+/// passes using integer arithmetic
+///
+/// This passes where `(2 A(d) - (n - d))^2 <= z_α^2 (n - d)`, equivalent to
+/// [autocorrelation_with_alpha](super::autocorrelation_with_alpha) returning `Ok`, with
+/// the same requirements on `lag` and sequence length.
+///
+/// See [nist_freq_monobit_passfail] for the precision of the fixed-point bound.
+pub fn autocorrelation_passfail(data: impl AsBits, lag: usize, alpha: f32) -> Result<bool, Error> {
+    let z2 = z2_bound(alpha)?;
+
+    let (m, a) = count_mismatches(data, lag)?;
+
+    let s = (2 * a).abs_diff(m) as u128;
+    Ok(within(s * s, m as u128, z2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::BitIter;
+    use crate::nist::{
+        autocorrelation_with_alpha, nist_freq_monobit_with_alpha, nist_runs_with_alpha,
+    };
+    use crate::test_utils::lcg_fill;
+
+    /// Build a 10,000 bit sequence with the specified number of ones
+    fn with_ones(ones: usize) -> [u8; 1250] {
+        let mut b = [0u8; 1250];
+        (0..ones).for_each(|i| b[i / 8] |= 1 << (i % 8));
+        b
+    }
+
+    #[test]
+    fn z2_bounds() {
+        // Precomputed bounds match the computed quantiles
+        for (alpha, z2) in Z2_BOUNDS {
+            let z = crate::helpers::normal_quantile_f64(1.0 - alpha as f64 / 2.0);
+            assert_eq!((z * z * 65536.0) as u64, z2, "alpha {alpha}");
+        }
+
+        for alpha in [0.0, 1.0, -0.5, f32::NAN] {
+            assert!(matches!(z2_bound(alpha), Err(Error::InvalidAlpha(_))));
+        }
+    }
+
+    #[test]
+    fn monobit_passfail_boundary() {
+        // With n = 10000 and α = 0.01 the bound is |v| <= 257.58, so v = 256 passes with
+        // p = 0.01047 and v = 258 fails with p = 0.00988
+        let pass = BitIter::new(with_ones(5128));
+        assert_eq!(nist_freq_monobit_passfail(pass, 0.01), Ok(true));
+        let fail = BitIter::new(with_ones(5129));
+        assert_eq!(nist_freq_monobit_passfail(fail, 0.01), Ok(false));
+
+        // Matches the float path for every count of ones around the boundaries
+        for alpha in [0.05, 0.01, 0.001, 0.02] {
+            for ones in 4700..5300 {
+                let b = with_ones(ones);
+                assert_eq!(
+                    nist_freq_monobit_passfail(BitIter::new(&b), alpha),
+                    Ok(nist_freq_monobit_with_alpha(BitIter::new(&b), alpha).is_ok()),
+                    "{ones} ones at alpha {alpha}"
+                );
+            }
+        }
+
+        assert_eq!(
+            nist_freq_monobit_passfail(BitIter::new(&[0u8; 12]), 0.01),
+            Err(Error::InsufficientSampleSize(96))
+        );
+    }
+
+    #[test]
+    fn runs_passfail() {
+        let mut buff = [0u8; 16 * 1000];
+        lcg_fill(&mut buff, 43);
+
+        // Matches the float path over many short sequences, including failures
+        let mut failed = 0;
+        for c in buff.chunks_exact(16) {
+            let r = nist_runs_passfail(BitIter::new(c), 0.1).unwrap();
+            assert_eq!(r, nist_runs_with_alpha(BitIter::new(c), 0.1).is_ok());
+            failed += !r as usize;
+        }
+        assert!(failed > 50);
+
+        // Frequency pre-condition failure
+        assert_eq!(
+            nist_runs_passfail(BitIter::new(&[0xffu8; 16]), 0.01),
+            Ok(false)
+        );
+        // Alternating bits have too many runs
+        assert_eq!(
+            nist_runs_passfail(BitIter::new(&[0x55u8; 16]), 0.01),
+            Ok(false)
+        );
+
+        assert_eq!(
+            nist_runs_passfail(BitIter::new(&[0u8; 12]), 0.01),
+            Err(Error::InsufficientSampleSize(96))
+        );
+    }
+
+    #[test]
+    fn autocorrelation_passfail_matches() {
+        let mut buff = [0u8; 32 * 1000];
+        lcg_fill(&mut buff, 47);
+
+        // Matches the float path over many short sequences, including failures
+        let mut failed = 0;
+        for c in buff.chunks_exact(32) {
+            for lag in [1, 8] {
+                let r = autocorrelation_passfail(BitIter::new(c), lag, 0.1).unwrap();
+                assert_eq!(
+                    r,
+                    autocorrelation_with_alpha(BitIter::new(c), lag, 0.1).is_ok()
+                );
+                failed += !r as usize;
+            }
+        }
+        assert!(failed > 100);
+
+        assert_eq!(
+            autocorrelation_passfail(BitIter::new(&buff), 0, 0.01),
+            Err(Error::InvalidParameter)
+        );
+    }
+}
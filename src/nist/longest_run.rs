@@ -0,0 +1,390 @@
+//! NIST Test for the Longest Run of Ones in a Block
+
+use super::igamma_upper;
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Minimum sequence length (in bits) for the longest run test
+pub const LONGEST_RUN_MIN_LEN: usize = 128;
+
+/// Maximum number of run categories for [nist_longest_run_custom]
+pub const LONGEST_RUN_MAX_CATEGORIES: usize = 16;
+
+/// Test parameters for a block size
+///
+/// Longest runs up to and including each boundary are bucketed into one category per
+/// probability, the last category holding all longer runs
+struct Params<'a> {
+    block_len: usize,
+    boundaries: &'a [usize],
+    probs: &'a [f32],
+}
+
+/// Parameters for M = 8, used where 128 <= n < 6272
+const PARAMS_8: Params = Params {
+    block_len: 8,
+    boundaries: &[1, 2, 3],
+    probs: &[0.21484375, 0.3671875, 0.23046875, 0.1875],
+};
+
+/// Parameters for M = 128, used where 6272 <= n < 750000
+const PARAMS_128: Params = Params {
+    block_len: 128,
+    boundaries: &[4, 5, 6, 7, 8],
+    probs: &[
+        0.117_403_58,
+        0.242_955_96,
+        0.249_363_48,
+        0.17517706,
+        0.102_701_07,
+        0.112_398_85,
+    ],
+};
+
+/// Parameters for M = 10000, used where n >= 750000
+const PARAMS_10000: Params = Params {
+    block_len: 10000,
+    boundaries: &[10, 11, 12, 13, 14, 15],
+    probs: &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+};
+
+/// Per-block-size longest run accumulator
+struct Blocks<'a> {
+    params: &'a Params<'a>,
+    /// Bits consumed in the current block
+    index: usize,
+    /// Current run of ones
+    run: usize,
+    /// Longest run of ones in the current block
+    longest: usize,
+    /// Longest run histogram over completed blocks
+    counts: [usize; LONGEST_RUN_MAX_CATEGORIES],
+}
+
+impl<'a> Blocks<'a> {
+    const fn new(params: &'a Params<'a>) -> Self {
+        Self {
+            params,
+            index: 0,
+            run: 0,
+            longest: 0,
+            counts: [0; LONGEST_RUN_MAX_CATEGORIES],
+        }
+    }
+
+    fn push(&mut self, v: bool) {
+        // Update current run
+        match v {
+            true => self.run += 1,
+            false => self.run = 0,
+        }
+        if self.run > self.longest {
+            self.longest = self.run;
+        }
+
+        self.index += 1;
+
+        // Bucket longest run on block completion
+        if self.index == self.params.block_len {
+            let c = self
+                .params
+                .boundaries
+                .partition_point(|b| *b < self.longest);
+            self.counts[c] += 1;
+
+            self.index = 0;
+            self.run = 0;
+            self.longest = 0;
+        }
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    fn chi_square(&self) -> f32 {
+        let num_blocks = self.num_blocks();
+
+        let mut x2 = 0.0;
+        for (v, p) in self.counts.iter().zip(self.params.probs) {
+            let e = num_blocks as f32 * p;
+            x2 += math::powf(*v as f32 - e, 2.0) / e;
+        }
+
+        x2
+    }
+
+    fn result(&self) -> TestResult {
+        // Compute x^2 over run categories
+        let x2 = self.chi_square();
+
+        // Compute p
+        let k = self.params.probs.len() - 1;
+        let p = igamma_upper(k as f32 / 2.0, x2 / 2.0);
+
+        TestResult::new(p, x2)
+    }
+}
+
+/// NIST Test for the Longest Run of Ones in a Block over an iterator of N bits
+///
+/// The block size M (8, 128, or 10000) and the run categories are selected from the
+/// number of bits provided, with a minimum of [LONGEST_RUN_MIN_LEN] bits. Trailing bits
+/// that do not form a full block are discarded.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_longest_run(data: impl AsBits) -> Result<f32, Error> {
+    nist_longest_run_result(data)?.check()
+}
+
+/// NIST Test for the Longest Run of Ones in a Block at significance level `alpha`
+///
+/// See [nist_longest_run] for details
+pub fn nist_longest_run_with_alpha(data: impl AsBits, alpha: f32) -> Result<f32, Error> {
+    nist_longest_run_result(data)?.at_alpha(alpha)?.check()
+}
+
+/// NIST Test for the Longest Run of Ones in a Block returning the [TestResult], with
+/// statistic x^2
+///
+/// See [nist_longest_run] for details
+pub fn nist_longest_run_result(data: impl AsBits) -> Result<TestResult, Error> {
+    // Accumulate all block sizes in one pass as n is not known up front
+    let mut blocks = [
+        Blocks::new(&PARAMS_8),
+        Blocks::new(&PARAMS_128),
+        Blocks::new(&PARAMS_10000),
+    ];
+    let mut n = 0usize;
+
+    for d in data.into_bits() {
+        n += 1;
+
+        for b in blocks.iter_mut() {
+            b.push(d);
+        }
+    }
+
+    // Check sample size meets minimum requirements and select block size
+    if n < LONGEST_RUN_MIN_LEN {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+    let b = match n {
+        0..=6271 => &blocks[0],
+        6272..=749_999 => &blocks[1],
+        _ => &blocks[2],
+    };
+
+    Ok(b.result())
+}
+
+/// NIST Test for the Longest Run of Ones in a Block with a custom block size and run
+/// categories
+///
+/// This splits the data into blocks of `m` bits, bucketing the longest run of ones in each
+/// block into categories: runs up to and including `boundaries[0]`, then each subsequent
+/// boundary in turn, with the last category holding all longer runs. `probs` gives the
+/// probability of each category, so the standard M = 8 test uses boundaries `[1, 2, 3]`
+/// with probabilities `[0.2148, 0.3672, 0.2305, 0.1875]`.
+///
+/// The boundaries must be strictly increasing, with `boundaries.len() + 1` positive
+/// probabilities summing to 1 (within 1e-3), and at most [LONGEST_RUN_MAX_CATEGORIES]
+/// categories, returning [Error::InvalidParameter] otherwise. Trailing bits that do not
+/// form a full block are discarded, and sequences without a full block return
+/// [Error::InsufficientSampleSize].
+///
+/// See [nist_longest_run] to select the standard tables from the sequence length
+pub fn nist_longest_run_custom(
+    data: impl AsBits,
+    m: usize,
+    boundaries: &[usize],
+    probs: &[f32],
+) -> Result<f32, Error> {
+    nist_longest_run_custom_result(data, m, boundaries, probs)?.check()
+}
+
+/// NIST Test for the Longest Run of Ones in a Block with custom categories at
+/// significance level `alpha`
+///
+/// See [nist_longest_run_custom] for details
+pub fn nist_longest_run_custom_with_alpha(
+    data: impl AsBits,
+    m: usize,
+    boundaries: &[usize],
+    probs: &[f32],
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_longest_run_custom_result(data, m, boundaries, probs)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Test for the Longest Run of Ones in a Block with custom categories returning the
+/// [TestResult], with statistic x^2
+///
+/// See [nist_longest_run_custom] for details
+#[allow(clippy::neg_cmp_op_on_partial_ord)]
+pub fn nist_longest_run_custom_result(
+    data: impl AsBits,
+    m: usize,
+    boundaries: &[usize],
+    probs: &[f32],
+) -> Result<TestResult, Error> {
+    // Check block size and categories, inverted to reject NaN
+    let sum = probs.iter().sum::<f32>();
+    if m == 0
+        || boundaries.is_empty()
+        || probs.len() != boundaries.len() + 1
+        || probs.len() > LONGEST_RUN_MAX_CATEGORIES
+        || boundaries.windows(2).any(|w| w[0] >= w[1])
+        || probs.iter().any(|p| !(*p > 0.0))
+        || !(math::fabsf(sum - 1.0) <= 1e-3)
+    {
+        return Err(Error::InvalidParameter);
+    }
+
+    let params = Params {
+        block_len: m,
+        boundaries,
+        probs,
+    };
+    let mut blocks = Blocks::new(&params);
+    let mut n = 0usize;
+
+    for d in data.into_bits() {
+        n += 1;
+        blocks.push(d);
+    }
+
+    // Check sample size meets minimum requirements
+    if blocks.num_blocks() == 0 {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    Ok(blocks.result())
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn nist_longest_run_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 1024];
+        rng.fill_bytes(&mut buff);
+
+        nist_longest_run(BitIter::new(&buff)).expect("Longest run test failed");
+    }
+
+    #[test]
+    fn nist_longest_run_spec() {
+        // 128-bit example from specification
+        let buff = bits![
+            1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 1, 0, 0, 0, 1, 0, 0, 1,
+            1, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 1, 1, 0, 1, 0, 1,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 1, 1, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 0, 0, 0,
+            0, 1, 1, 0, 1, 0, 1, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 1,
+            1, 0, 0, 0, 1, 0, 1, 1, 0, 0, 1, 0
+        ];
+
+        let p = nist_longest_run(buff.iter().by_vals()).expect("Longest run test failed");
+
+        // Check p value matches test vector
+        assert_approx_eq!(p, 0.180598, 1e-4);
+    }
+
+    #[test]
+    fn nist_longest_run_fail() {
+        // Runs of four ones in every block
+        nist_longest_run(BitIter::from([0x0fu8; 128])).expect_err("Longest run p > threshold");
+        nist_longest_run(BitIter::from([0x00u8; 128])).expect_err("Longest run p > threshold");
+    }
+
+    #[test]
+    fn nist_longest_run_insufficient() {
+        let r = nist_longest_run(BitIter::from([0x0fu8; 15]));
+        assert_eq!(r, Err(Error::InsufficientSampleSize(120)));
+    }
+
+    #[test]
+    fn nist_longest_run_custom_standard() {
+        let mut buff = [0u8; 1024];
+        lcg_fill(&mut buff, 41);
+
+        // Standard tables match the built-in selection
+        let r = nist_longest_run_result(BitIter::new(&buff[..783])).unwrap();
+        let c = nist_longest_run_custom_result(
+            BitIter::new(&buff[..783]),
+            PARAMS_8.block_len,
+            PARAMS_8.boundaries,
+            PARAMS_8.probs,
+        )
+        .unwrap();
+        assert_eq!(r, c);
+
+        let r = nist_longest_run_result(BitIter::new(&buff)).unwrap();
+        let c = nist_longest_run_custom_result(
+            BitIter::new(&buff),
+            PARAMS_128.block_len,
+            PARAMS_128.boundaries,
+            PARAMS_128.probs,
+        )
+        .unwrap();
+        assert_eq!(r, c);
+    }
+
+    #[test]
+    fn nist_longest_run_custom_ok() {
+        let mut buff = [0u8; 8192];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        // Custom M = 512, with categories <= 6, 7, 8, 9, 10 and >= 11 from the exact
+        // distribution of the longest run
+        let probs = [0.1299, 0.2361, 0.2418, 0.1730, 0.1033, 0.1159];
+        nist_longest_run_custom(BitIter::new(&buff), 512, &[6, 7, 8, 9, 10], &probs)
+            .expect("Longest run test failed");
+
+        // Runs of four ones in every block
+        nist_longest_run_custom(
+            BitIter::from([0x0fu8; 1024]),
+            512,
+            &[6, 7, 8, 9, 10],
+            &probs,
+        )
+        .expect_err("Longest run p > threshold");
+    }
+
+    #[test]
+    fn nist_longest_run_custom_invalid() {
+        let buff = [0x0fu8; 128];
+        let probs = [0.25; 4];
+
+        for (m, boundaries, probs) in [
+            (0, &[1, 2, 3][..], &probs[..]),
+            (8, &[], &[1.0]),
+            (8, &[1, 2], &probs),
+            (8, &[1, 3, 2], &probs),
+            (8, &[1, 2, 3], &[0.5, 0.5, 0.5, 0.5]),
+            (8, &[1, 2, 3], &[0.5, 0.5, 0.0, 0.0]),
+            (8, &[1, 2, 3], &[0.25, 0.25, 0.25, f32::NAN]),
+            (8, &[1; 16], &[1.0 / 17.0; 17]),
+        ] {
+            assert_eq!(
+                nist_longest_run_custom(BitIter::new(&buff), m, boundaries, probs),
+                Err(Error::InvalidParameter)
+            );
+        }
+
+        assert_eq!(
+            nist_longest_run_custom(BitIter::new(&buff), 2048, &[1, 2, 3], &probs),
+            Err(Error::InsufficientSampleSize(1024))
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! NIST Binary Matrix Rank Test
+
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported matrix dimension (rows are stored as `u64` bitmasks)
+pub const MATRIX_RANK_MAX_DIM: usize = 64;
+
+/// NIST Binary Matrix Rank Test over an iterator of N bits with `m` x `q` matrices
+///
+/// Bits are consumed row-wise into each matrix, with trailing bits that do not fill
+/// a matrix discarded. The specification recommends 32 x 32 matrices with at least
+/// 38 matrices worth of data, `m` and `q` must not exceed [MATRIX_RANK_MAX_DIM].
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_matrix_rank(data: impl AsBits, m: usize, q: usize) -> Result<f32, Error> {
+    nist_matrix_rank_result(data, m, q)?.check()
+}
+
+/// NIST Binary Matrix Rank Test at significance level `alpha`
+///
+/// See [nist_matrix_rank] for details
+pub fn nist_matrix_rank_with_alpha(
+    data: impl AsBits,
+    m: usize,
+    q: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_matrix_rank_result(data, m, q)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Binary Matrix Rank Test returning the [TestResult], with statistic x^2
+///
+/// See [nist_matrix_rank] for details
+pub fn nist_matrix_rank_result(data: impl AsBits, m: usize, q: usize) -> Result<TestResult, Error> {
+    // Check matrix dimensions are supported
+    if m == 0 || q == 0 || m > MATRIX_RANK_MAX_DIM || q > MATRIX_RANK_MAX_DIM {
+        return Err(Error::InvalidParameter);
+    }
+
+    let full_rank = m.min(q);
+    let mut num_matrices = 0;
+    let mut n = 0;
+    let mut data = data.into_bits();
+
+    // Counts for full rank, full rank - 1, and remaining matrices
+    let mut counts = [0usize; 3];
+
+    // Compute rank for each matrix
+    'outer: loop {
+        let mut matrix = [0u64; MATRIX_RANK_MAX_DIM];
+
+        // Fill matrix row-wise, discarding incomplete matrices
+        for row in matrix[..m].iter_mut() {
+            for j in 0..q {
+                match data.next() {
+                    Some(v) => *row |= (v as u64) << j,
+                    None => break 'outer,
+                }
+                n += 1;
+            }
+        }
+
+        // Bucket matrix rank
+        let r = gf2_rank(&mut matrix[..m], q);
+        let c = (full_rank - r).min(2);
+        counts[c] += 1;
+
+        num_matrices += 1;
+    }
+
+    // Check we have at least one matrix
+    if num_matrices == 0 {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute x^2 against expected rank probabilities
+    let probs = rank_probabilities(m, q);
+    let mut x2 = 0.0;
+    for (f, p) in counts.iter().zip(probs.iter()) {
+        let e = num_matrices as f32 * p;
+        x2 += math::powf(*f as f32 - e, 2.0) / e;
+    }
+
+    // Compute p (x^2 with two degrees of freedom)
+    let p = math::expf(-x2 / 2.0);
+
+    Ok(TestResult::new(p, x2))
+}
+
+/// Compute the rank of a binary matrix (rows as bitmasks of `q` columns) over GF(2)
+///
+/// Note this performs elimination in place, destroying the matrix
+fn gf2_rank(rows: &mut [u64], q: usize) -> usize {
+    let mut rank = 0;
+
+    for j in 0..q {
+        let mask = 1 << j;
+
+        // Find a pivot row with this column set
+        let pivot = match (rank..rows.len()).find(|i| rows[*i] & mask != 0) {
+            Some(p) => p,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+
+        // Eliminate this column from all other rows
+        for i in 0..rows.len() {
+            if i != rank && rows[i] & mask != 0 {
+                rows[i] ^= rows[rank];
+            }
+        }
+
+        rank += 1;
+    }
+
+    rank
+}
+
+/// Compute the probabilities of an `m` x `q` random binary matrix having
+/// full rank, full rank - 1, and lower rank
+fn rank_probabilities(m: usize, q: usize) -> [f32; 3] {
+    let full_rank = m.min(q);
+    let mut probs = [0.0; 3];
+
+    for (k, p) in probs[..2].iter_mut().enumerate() {
+        let r = (full_rank - k) as i32;
+        let (m, q) = (m as i32, q as i32);
+
+        let mut v = math::powf(2.0, (r * (q + m - r) - m * q) as f32);
+        for i in 0..r {
+            let a = 1.0 - math::powf(2.0, (i - q) as f32);
+            let b = 1.0 - math::powf(2.0, (i - m) as f32);
+            let c = 1.0 - math::powf(2.0, (i - r) as f32);
+            v *= a * b / c;
+        }
+
+        *p = v;
+    }
+
+    probs[2] = 1.0 - probs[0] - probs[1];
+
+    probs
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_matrix_rank_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 38 * 32 * 4];
+        rng.fill_bytes(&mut buff);
+
+        nist_matrix_rank(BitIter::new(&buff), 32, 32).expect("Matrix rank test failed");
+    }
+
+    #[test]
+    fn nist_matrix_rank_ex() {
+        // Example from specification, yielding one rank 2 and one rank 3 matrix
+        let buff = bits![0, 1, 0, 1, 1, 0, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1];
+
+        let p = nist_matrix_rank(buff.iter().by_vals(), 3, 3).expect("Matrix rank test failed");
+
+        // The specification computes p = 0.741948 using the 32 x 32 probabilities,
+        // check against the exact 3 x 3 probabilities instead
+        assert_approx_eq!(p, 0.820962, 1e-5);
+    }
+
+    #[test]
+    fn nist_matrix_rank_fail() {
+        nist_matrix_rank(BitIter::from([0x00u8; 38 * 32 * 4]), 32, 32)
+            .expect_err("Matrix rank p > threshold");
+        nist_matrix_rank(BitIter::from([0xffu8; 38 * 32 * 4]), 32, 32)
+            .expect_err("Matrix rank p > threshold");
+    }
+
+    #[test]
+    fn nist_matrix_rank_invalid() {
+        let buff = [0u8; 127];
+
+        assert_eq!(
+            nist_matrix_rank(BitIter::new(&buff), 0, 32),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_matrix_rank(BitIter::new(&buff), 32, 65),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_matrix_rank(BitIter::new(&buff), 32, 32),
+            Err(Error::InsufficientSampleSize(1016))
+        );
+    }
+
+    #[test]
+    fn gf2_ranks() {
+        // Matrices from specification example
+        let mut a = [0b010, 0b011, 0b010];
+        assert_eq!(gf2_rank(&mut a, 3), 2);
+
+        let mut b = [0b010, 0b101, 0b110];
+        assert_eq!(gf2_rank(&mut b, 3), 3);
+
+        let mut identity = [0u64; 64];
+        for (i, r) in identity.iter_mut().enumerate() {
+            *r = 1 << i;
+        }
+        assert_eq!(gf2_rank(&mut identity, 64), 64);
+    }
+
+    #[test]
+    fn rank_probabilities_32() {
+        let p = rank_probabilities(32, 32);
+
+        assert_approx_eq!(p[0], 0.2888, 1e-4);
+        assert_approx_eq!(p[1], 0.5776, 1e-4);
+        assert_approx_eq!(p[2], 0.1336, 1e-4);
+    }
+}
@@ -0,0 +1,181 @@
+//! NIST Non-overlapping Template Matching Test
+
+use super::igamma_upper;
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported template length
+pub const TEMPLATE_MAX_LEN: usize = 32;
+
+/// NIST Non-overlapping Template Matching Test over an iterator of N bits with
+/// block_len sized blocks
+///
+/// Counts the non-overlapping occurrences of an m-bit `template` in each block, with the
+/// search window jumping forward m bits on a match. Trailing bits that do not form a full
+/// block are discarded. Templates should be aperiodic, see [templates](super::templates)
+/// for the standard set, and must not exceed [TEMPLATE_MAX_LEN] bits.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_non_overlapping_template(
+    data: impl AsBits,
+    template: &[bool],
+    block_len: usize,
+) -> Result<f32, Error> {
+    nist_non_overlapping_template_result(data, template, block_len)?.check()
+}
+
+/// NIST Non-overlapping Template Matching Test at significance level `alpha`
+///
+/// See [nist_non_overlapping_template] for details
+pub fn nist_non_overlapping_template_with_alpha(
+    data: impl AsBits,
+    template: &[bool],
+    block_len: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_non_overlapping_template_result(data, template, block_len)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Non-overlapping Template Matching Test returning the [TestResult], with
+/// statistic x^2
+///
+/// See [nist_non_overlapping_template] for details
+pub fn nist_non_overlapping_template_result(
+    data: impl AsBits,
+    template: &[bool],
+    block_len: usize,
+) -> Result<TestResult, Error> {
+    let m = template.len();
+
+    // Check template and block lengths
+    if m == 0 || m > TEMPLATE_MAX_LEN || block_len < m {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Pack template into a window-comparable word, with the first bit highest
+    let mask = (1u64 << m) - 1;
+    let template = template.iter().fold(0u64, |a, b| (a << 1) | *b as u64);
+
+    // Theoretical mean and variance of matches per block
+    let mean = (block_len - m + 1) as f32 / math::powf(2.0, m as f32);
+    let variance = block_len as f32
+        * (1.0 / math::powf(2.0, m as f32) - (2 * m - 1) as f32 / math::powf(2.0, 2.0 * m as f32));
+
+    let mut n = 0;
+    let mut num_blocks = 0;
+    let mut x2_partial = 0.0;
+
+    let mut index = 0;
+    let mut window = 0u64;
+    let mut filled = 0;
+    let mut matches = 0;
+
+    for d in data.into_bits() {
+        n += 1;
+
+        // Shift bit into window
+        window = ((window << 1) | d as u64) & mask;
+        filled += 1;
+
+        // Count matches, restarting the window to skip the matched bits
+        if filled >= m && window == template {
+            matches += 1;
+            filled = 0;
+        }
+
+        index += 1;
+
+        // Accumulate statistic on block completion
+        if index == block_len {
+            x2_partial += math::powf(matches as f32 - mean, 2.0);
+            num_blocks += 1;
+
+            index = 0;
+            window = 0;
+            filled = 0;
+            matches = 0;
+        }
+    }
+
+    // Check we have at least one block
+    if num_blocks == 0 {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute x^2
+    let x2 = x2_partial / variance;
+
+    // Compute p
+    let p = igamma_upper(num_blocks as f32 / 2.0, x2 / 2.0);
+
+    Ok(TestResult::new(p, x2))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+    use crate::nist::templates;
+
+    #[test]
+    fn nist_non_overlapping_template_ok() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut buff = [0u8; 1024];
+        rng.fill_bytes(&mut buff);
+
+        nist_non_overlapping_template(BitIter::new(&buff), templates::M4[2], 1024)
+            .expect("Non-overlapping template test failed");
+    }
+
+    #[test]
+    fn nist_non_overlapping_template_ex() {
+        // Example from specification, template B = 001
+        let buff = bits![1, 0, 1, 0, 0, 1, 0, 0, 1, 0, 1, 1, 1, 0, 0, 1, 0, 1, 1, 0];
+
+        let p = nist_non_overlapping_template(buff.iter().by_vals(), templates::M3[0], 10)
+            .expect("Non-overlapping template test failed");
+
+        // Check p value matches test vector
+        assert_approx_eq!(p, 0.344154, 1e-5);
+    }
+
+    #[test]
+    fn nist_non_overlapping_template_fail() {
+        // Template appears far more often than expected
+        nist_non_overlapping_template(BitIter::from([0b1010_1010u8; 128]), templates::M2[0], 128)
+            .expect_err("Non-overlapping template p > threshold");
+
+        // Template never appears
+        nist_non_overlapping_template(BitIter::from([0xffu8; 128]), templates::M2[0], 128)
+            .expect_err("Non-overlapping template p > threshold");
+    }
+
+    #[test]
+    fn nist_non_overlapping_template_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            nist_non_overlapping_template(BitIter::new(&buff), &[], 10),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_non_overlapping_template(BitIter::new(&buff), &[false; 33], 64),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_non_overlapping_template(BitIter::new(&buff), templates::M3[0], 2),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_non_overlapping_template(BitIter::new(&buff), templates::M3[0], 256),
+            Err(Error::InsufficientSampleSize(128))
+        );
+    }
+}
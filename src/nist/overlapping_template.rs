@@ -0,0 +1,285 @@
+//! NIST Overlapping Template Matching Test
+
+use super::igamma_upper;
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Default template length (m) for the overlapping template test
+pub const OVERLAPPING_TEMPLATE_LEN: usize = 9;
+
+/// Default block length (M) for the overlapping template test
+pub const OVERLAPPING_BLOCK_LEN: usize = 1032;
+
+/// Category probabilities for the default m = 9, M = 1032 parameters
+///
+/// These are the corrected values from the specification, the approximation
+/// used for other parameters is less accurate here.
+pub const OVERLAPPING_PROBS: [f32; 6] =
+    [0.364091, 0.185659, 0.139381, 0.100571, 0.070432, 0.139865];
+
+/// NIST Overlapping Template Matching Test over an iterator of N bits with block_len
+/// sized blocks
+///
+/// Counts the overlapping occurrences of an all-ones template of `template_len` bits
+/// in each block, bucketing these counts into six categories (0 to 5 or more matches).
+/// Trailing bits that do not form a full block are discarded.
+///
+/// Category probabilities use [OVERLAPPING_PROBS] for the default parameters
+/// ([OVERLAPPING_TEMPLATE_LEN], [OVERLAPPING_BLOCK_LEN]) or are otherwise approximated,
+/// see [nist_overlapping_template_with_probs] to override these.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_overlapping_template(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+) -> Result<f32, Error> {
+    nist_overlapping_template_result(data, template_len, block_len)?.check()
+}
+
+/// NIST Overlapping Template Matching Test at significance level `alpha`
+///
+/// See [nist_overlapping_template] for details
+pub fn nist_overlapping_template_with_alpha(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_overlapping_template_result(data, template_len, block_len)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Overlapping Template Matching Test returning the [TestResult], with statistic x^2
+///
+/// See [nist_overlapping_template] for details
+pub fn nist_overlapping_template_result(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+) -> Result<TestResult, Error> {
+    let probs = match (template_len, block_len) {
+        (OVERLAPPING_TEMPLATE_LEN, OVERLAPPING_BLOCK_LEN) => OVERLAPPING_PROBS,
+        _ if template_len > 0 && template_len <= block_len => {
+            overlapping_probs(template_len, block_len)
+        }
+        _ => return Err(Error::InvalidParameter),
+    };
+
+    nist_overlapping_template_with_probs_result(data, template_len, block_len, &probs)
+}
+
+/// NIST Overlapping Template Matching Test with caller-provided category probabilities
+///
+/// See [nist_overlapping_template] for details
+pub fn nist_overlapping_template_with_probs(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+    probs: &[f32; 6],
+) -> Result<f32, Error> {
+    nist_overlapping_template_with_probs_result(data, template_len, block_len, probs)?.check()
+}
+
+/// NIST Overlapping Template Matching Test with caller-provided category probabilities
+/// at significance level `alpha`
+///
+/// See [nist_overlapping_template_with_probs] for details
+pub fn nist_overlapping_template_with_probs_with_alpha(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+    probs: &[f32; 6],
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_overlapping_template_with_probs_result(data, template_len, block_len, probs)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Overlapping Template Matching Test with caller-provided category probabilities
+/// returning the [TestResult], with statistic x^2
+///
+/// See [nist_overlapping_template] for details
+pub fn nist_overlapping_template_with_probs_result(
+    data: impl AsBits,
+    template_len: usize,
+    block_len: usize,
+    probs: &[f32; 6],
+) -> Result<TestResult, Error> {
+    // Check template and block lengths
+    if template_len == 0 || template_len > block_len {
+        return Err(Error::InvalidParameter);
+    }
+
+    let mut n = 0;
+    let mut counts = [0usize; 6];
+
+    let mut index = 0;
+    let mut run = 0;
+    let mut matches = 0;
+
+    for d in data.into_bits() {
+        n += 1;
+
+        // Every run of ones of at least template_len bits is a match
+        match d {
+            true => run += 1,
+            false => run = 0,
+        }
+        if run >= template_len {
+            matches += 1;
+        }
+
+        index += 1;
+
+        // Bucket matches on block completion
+        if index == block_len {
+            counts[matches.min(5)] += 1;
+
+            index = 0;
+            run = 0;
+            matches = 0;
+        }
+    }
+
+    // Check we have at least one block
+    if counts.iter().sum::<usize>() == 0 {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    Ok(overlapping_result(&counts, probs))
+}
+
+/// Compute the result for the per-category block counts
+fn overlapping_result(counts: &[usize; 6], probs: &[f32; 6]) -> TestResult {
+    let num_blocks: usize = counts.iter().sum();
+
+    // Compute x^2 over categories
+    let mut x2 = 0.0;
+    for (v, p) in counts.iter().zip(probs) {
+        let e = num_blocks as f32 * p;
+        x2 += math::powf(*v as f32 - e, 2.0) / e;
+    }
+
+    // Compute p
+    let p = igamma_upper(5.0 / 2.0, x2 / 2.0);
+
+    TestResult::new(p, x2)
+}
+
+/// Approximate the category probabilities for template length m and block length M
+fn overlapping_probs(m: usize, block_len: usize) -> [f32; 6] {
+    let lambda = (block_len - m + 1) as f32 / math::powf(2.0, m as f32);
+    let eta = lambda / 2.0;
+
+    let mut probs = [0.0; 6];
+    probs[0] = math::expf(-eta);
+
+    // pi_u = e^-eta / 2^u * sum_{l=1..u} (u-1 choose l-1) * eta^l / l!
+    for (u, p) in probs.iter_mut().enumerate().take(5).skip(1) {
+        let mut sum = 0.0;
+        let mut choose = 1.0;
+        let mut factorial = 1.0;
+
+        for l in 1..=u {
+            factorial *= l as f32;
+            sum += choose * math::powf(eta, l as f32) / factorial;
+            choose = choose * (u - l) as f32 / l as f32;
+        }
+
+        *p = math::expf(-eta) / math::powf(2.0, u as f32) * sum;
+    }
+
+    probs[5] = 1.0 - probs[..5].iter().sum::<f32>();
+
+    probs
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_overlapping_template_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 1032 * 16 / 8];
+        rng.fill_bytes(&mut buff);
+
+        nist_overlapping_template(
+            BitIter::new(&buff),
+            OVERLAPPING_TEMPLATE_LEN,
+            OVERLAPPING_BLOCK_LEN,
+        )
+        .expect("Overlapping template test failed");
+    }
+
+    #[test]
+    fn nist_overlapping_template_ex() {
+        // Example from specification, m = 2 and M = 10
+        let buff = bits![
+            1, 0, 1, 1, 1, 0, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 1, 0, 0, 1, 0, 1,
+            1, 1, 0, 1, 1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1
+        ];
+
+        // Note the specification lists counts of [0, 1, 1, 1, 1, 1] for the example,
+        // however these do not match the provided sequence, which has matches of
+        // 5, 1, 3, 4, and 1 per block.
+        let p = nist_overlapping_template(buff.iter().by_vals(), 2, 10)
+            .expect("Overlapping template test failed");
+        assert_approx_eq!(p, 0.409635, 1e-4);
+
+        // Check the P-value for the specification counts (x^2 = 3.167729), note the
+        // specification lists p = 0.274932 which is igamc(K/2, x^2) rather than x^2 / 2
+        let r = overlapping_result(&[0, 1, 1, 1, 1, 1], &overlapping_probs(2, 10));
+        assert_approx_eq!(r.statistic, 3.167729, 1e-4);
+        assert_approx_eq!(r.p_value, 0.674147, 1e-4);
+    }
+
+    #[test]
+    fn nist_overlapping_template_fail() {
+        // Runs of ones far longer than expected
+        nist_overlapping_template(BitIter::from([0xffu8; 1032 * 2]), 9, 1032)
+            .expect_err("Overlapping template p > threshold");
+
+        // Runs of ones far shorter than expected
+        nist_overlapping_template(BitIter::from([0x0fu8; 1032 * 2]), 9, 1032)
+            .expect_err("Overlapping template p > threshold");
+    }
+
+    #[test]
+    fn nist_overlapping_template_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            nist_overlapping_template(BitIter::new(&buff), 0, 10),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_overlapping_template(BitIter::new(&buff), 11, 10),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_overlapping_template(BitIter::new(&buff), 9, 1032),
+            Err(Error::InsufficientSampleSize(128))
+        );
+    }
+
+    #[test]
+    fn overlapping_probabilities() {
+        // Probabilities from specification example
+        let probs = overlapping_probs(2, 10);
+        let expected = [0.324652, 0.182617, 0.142670, 0.106645, 0.077147, 0.166269];
+
+        for (p, e) in probs.iter().zip(expected) {
+            assert_approx_eq!(p, e, 1e-5);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Overlapping pattern counting shared by the serial and approximate entropy tests
+
+use crate::Error;
+
+/// Maximum supported pattern length
+pub(super) const PATTERN_MAX_LEN: usize = 11;
+
+/// Overlapping m-bit pattern counts over a sequence, wrapped around by appending the
+/// first m - 1 bits to the end so there are exactly n windows
+///
+/// Counts for shorter patterns are derived by summing over the longer patterns they
+/// prefix, so only the longest pattern length needs storage (4 bytes per pattern,
+/// 8 KiB at [PATTERN_MAX_LEN]).
+pub(super) struct PatternCounts {
+    counts: [u32; 1 << PATTERN_MAX_LEN],
+    m: usize,
+    n: usize,
+}
+
+impl PatternCounts {
+    /// Count overlapping m-bit patterns (with the first bit highest) over `data`
+    pub fn new(data: impl Iterator<Item = bool>, m: usize) -> Result<Self, Error> {
+        // Check pattern length is supported
+        if m == 0 || m > PATTERN_MAX_LEN {
+            return Err(Error::InvalidParameter);
+        }
+
+        let mut s = Self {
+            counts: [0; 1 << PATTERN_MAX_LEN],
+            m,
+            n: 0,
+        };
+
+        let mask = (1 << m) - 1;
+        let mut window = 0usize;
+        let mut head = 0usize;
+
+        for d in data {
+            // Keep the first m - 1 bits for wrapping
+            if s.n < m - 1 {
+                head = (head << 1) | d as usize;
+            }
+
+            // Shift bit into window and count once full
+            window = ((window << 1) | d as usize) & mask;
+            s.n += 1;
+
+            if s.n >= m {
+                s.counts[window] += 1;
+            }
+        }
+
+        // Check we have at least one full window
+        if s.n < m {
+            return Err(Error::InsufficientSampleSize(s.n));
+        }
+
+        // Wrap the sequence using the first m - 1 bits
+        for i in (0..m - 1).rev() {
+            window = ((window << 1) | ((head >> i) & 1)) & mask;
+            s.counts[window] += 1;
+        }
+
+        Ok(s)
+    }
+
+    /// Number of bits (and windows) counted
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Fetch the counts of each k-bit pattern, where k <= m
+    pub fn counts(&self, k: usize) -> impl Iterator<Item = u32> + '_ {
+        let width = 1 << (self.m - k);
+
+        self.counts[..1 << self.m]
+            .chunks_exact(width)
+            .map(|c| c.iter().sum())
+    }
+
+    /// Compute psi^2 for k-bit patterns, (2^k / n) * sum(count^2) - n, where k <= m
+    ///
+    /// This is computed in integers prior to the final division to avoid cancellation
+    /// in the subtraction, and is zero for k = 0.
+    pub fn psi_sq(&self, k: usize) -> f32 {
+        if k == 0 {
+            return 0.0;
+        }
+
+        let sum: u128 = self.counts(k).map(|c| c as u128 * c as u128).sum();
+        let n = self.n as u128;
+
+        let v = ((sum << k) as i128 - (n * n) as i128) as f64 / n as f64;
+        v as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn pattern_counts() {
+        // Example from specification, with wrapped sequence 0011011101 01
+        let buff = bits![0, 0, 1, 1, 0, 1, 1, 1, 0, 1];
+
+        let p = PatternCounts::new(buff.iter().by_vals(), 3).unwrap();
+        assert_eq!(p.n(), 10);
+
+        // 000, 001, ..., 111
+        let c3: [u32; 8] = [0, 1, 1, 2, 1, 2, 2, 1];
+        assert!(p.counts(3).eq(c3));
+
+        // 00, 01, 10, 11
+        assert!(p.counts(2).eq([1, 3, 3, 3]));
+
+        // 0, 1
+        assert!(p.counts(1).eq([4, 6]));
+    }
+
+    #[test]
+    fn pattern_psi_sq() {
+        let buff = bits![0, 0, 1, 1, 0, 1, 1, 1, 0, 1];
+        let p = PatternCounts::new(buff.iter().by_vals(), 3).unwrap();
+
+        assert_eq!(p.psi_sq(3), 2.8);
+        assert_eq!(p.psi_sq(2), 1.2);
+        assert_eq!(p.psi_sq(1), 0.4);
+        assert_eq!(p.psi_sq(0), 0.0);
+    }
+
+    #[test]
+    fn pattern_invalid() {
+        let buff = bits![0, 0, 1];
+
+        assert!(matches!(
+            PatternCounts::new(buff.iter().by_vals(), 0),
+            Err(Error::InvalidParameter)
+        ));
+        assert!(matches!(
+            PatternCounts::new(buff.iter().by_vals(), PATTERN_MAX_LEN + 1),
+            Err(Error::InvalidParameter)
+        ));
+        assert!(matches!(
+            PatternCounts::new(buff.iter().by_vals(), 4),
+            Err(Error::InsufficientSampleSize(3))
+        ));
+    }
+}
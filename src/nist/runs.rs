@@ -0,0 +1,188 @@
+//! NIST Runs Test
+
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Minimum recommended sequence length (n) for the runs test
+pub const RUNS_MIN_LEN: usize = 100;
+
+/// NIST Runs Test over an iterator of N bits
+///
+/// Counts the uninterrupted runs of identical bits to check whether the oscillation
+/// between zeros and ones is too fast or too slow. This requires at least
+/// [RUNS_MIN_LEN] bits, returning [Error::InsufficientSampleSize] otherwise, see
+/// [nist_runs_with_min] to override this.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_runs(data: impl AsBits) -> Result<f32, Error> {
+    nist_runs_result(data)?.check()
+}
+
+/// NIST Runs Test at significance level `alpha`
+///
+/// See [nist_runs] for details
+pub fn nist_runs_with_alpha(data: impl AsBits, alpha: f32) -> Result<f32, Error> {
+    nist_runs_result(data)?.at_alpha(alpha)?.check()
+}
+
+/// NIST Runs Test returning the [TestResult], with statistic V_n(obs), the total number
+/// of runs
+///
+/// See [nist_runs] for details
+pub fn nist_runs_result(data: impl AsBits) -> Result<TestResult, Error> {
+    nist_runs_with_min_result(data, RUNS_MIN_LEN)
+}
+
+/// NIST Runs Test requiring at least `min_samples` bits in place of [RUNS_MIN_LEN]
+///
+/// As with [nist_freq_monobit_with_min](crate::nist::nist_freq_monobit_with_min), below
+/// the recommended minimum the normal approximation is poor and P-values are unreliable.
+pub fn nist_runs_with_min(data: impl AsBits, min_samples: usize) -> Result<f32, Error> {
+    nist_runs_with_min_result(data, min_samples)?.check()
+}
+
+/// NIST Runs Test requiring at least `min_samples` bits, returning the [TestResult]
+///
+/// See [nist_runs_with_min] for details
+pub fn nist_runs_with_min_result(
+    data: impl AsBits,
+    min_samples: usize,
+) -> Result<TestResult, Error> {
+    let (n, ones, runs) = count_runs(data);
+
+    // Check sample size meets minimum requirements
+    if n < min_samples.max(1) {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute proportion of ones
+    let pi = ones as f32 / n as f32;
+
+    // Check frequency pre-condition, the spec defines the P-value
+    // as 0.0 where this fails and the test is not run
+    let tau = 2.0 / math::sqrtf(n as f32);
+    if math::fabsf(pi - 0.5) >= tau {
+        return Ok(TestResult::new(0.0, runs as f32));
+    }
+
+    // Compute test statistic
+    let e = 2.0 * n as f32 * pi * (1.0 - pi);
+    let s = math::fabsf(runs as f32 - e) / (2.0 * math::sqrtf(2.0 * n as f32) * pi * (1.0 - pi));
+
+    // Compute P-value
+    let p = math::erfcf(s);
+
+    Ok(TestResult::new(p, runs as f32))
+}
+
+/// Count the bits, ones, and runs (one run plus one per transition) of a sequence
+pub(super) fn count_runs(data: impl AsBits) -> (usize, usize, usize) {
+    let mut n = 0usize;
+    let mut ones = 0usize;
+    let mut runs = 0usize;
+    let mut last = None;
+
+    for d in data.into_bits() {
+        n += 1;
+
+        if d {
+            ones += 1;
+        }
+
+        if last != Some(d) {
+            runs += 1;
+        }
+        last = Some(d);
+    }
+
+    (n, ones, runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_runs_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 128];
+        rng.fill_bytes(&mut buff);
+
+        nist_runs(BitIter::new(&buff)).expect("Runs test failed");
+    }
+
+    #[test]
+    fn nist_runs_spec() {
+        // 100-bit test buffer
+        let buff = bits![
+            1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0,
+            1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1,
+            0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 0
+        ];
+
+        let p = nist_runs(buff.iter().by_vals()).expect("Runs test failed");
+
+        // Check p value matches test vector
+        assert_approx_eq!(p, 0.500798, 1e-5);
+
+        // Check statistic matches test vector
+        let r = nist_runs_result(buff.iter().by_vals()).expect("Runs test failed");
+        assert_eq!(r.statistic, 52.0);
+        assert!(r.passed);
+    }
+
+    #[test]
+    fn nist_runs_fail() {
+        // Alternating bits oscillate far too often
+        nist_runs(BitIter::from([0b0101_0101u8; 16])).expect_err("Runs p > threshold");
+
+        // Long runs oscillate far too rarely
+        let buff = [
+            0x00u8, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff,
+            0x00, 0xff,
+        ];
+        nist_runs(BitIter::from(buff)).expect_err("Runs p > threshold");
+    }
+
+    #[test]
+    fn nist_runs_precondition() {
+        // All ones fails the frequency pre-condition
+        let r = nist_runs(BitIter::from([0xffu8; 16]));
+        assert_eq!(
+            r,
+            Err(Error::BadPValue {
+                p: 0.0,
+                statistic: 1.0
+            })
+        );
+    }
+
+    #[test]
+    fn nist_runs_insufficient() {
+        let r = nist_runs(BitIter::from([0b0110_1001u8; 12]));
+        assert_eq!(r, Err(Error::InsufficientSampleSize(96)));
+
+        // Minimum may be lowered for smoke tests
+        let buff = bits![
+            1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0,
+            0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 1, 0, 0, 0,
+            1, 1, 0, 1, 0, 0
+        ];
+        assert_eq!(
+            nist_runs(buff.iter().by_vals()),
+            Err(Error::InsufficientSampleSize(64))
+        );
+        nist_runs_with_min(buff.iter().by_vals(), 64).expect("Runs test failed");
+        assert_eq!(
+            nist_runs_with_min(BitIter::from([0b0110_1001u8; 0]), 0),
+            Err(Error::InsufficientSampleSize(0))
+        );
+    }
+}
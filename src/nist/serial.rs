@@ -0,0 +1,129 @@
+//! NIST Serial Test
+
+use super::igamma_upper;
+use super::patterns::{PatternCounts, PATTERN_MAX_LEN};
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported block length (m) for the serial test
+pub const SERIAL_MAX_BLOCK_LEN: usize = PATTERN_MAX_LEN;
+
+/// NIST Serial Test over an iterator of N bits with block_len (m) sized patterns
+///
+/// Compares the frequencies of all overlapping m, m-1, and m-2 bit patterns, with
+/// the sequence wrapped around by appending its first m-1 bits, returning both
+/// P-values `(p1, p2)`. The test fails where either P-value is below the threshold.
+///
+/// The specification recommends m < log2(n) - 2. As this is `no_std` and does not
+/// allocate, `block_len` is limited to 2..=[SERIAL_MAX_BLOCK_LEN] with the pattern counts
+/// (8 KiB) held on the stack.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_serial(data: impl AsBits, block_len: usize) -> Result<(f32, f32), Error> {
+    let (r1, r2) = nist_serial_result(data, block_len)?;
+
+    Ok((r1.check()?, r2.check()?))
+}
+
+/// NIST Serial Test at significance level `alpha`
+///
+/// See [nist_serial] for details
+pub fn nist_serial_with_alpha(
+    data: impl AsBits,
+    block_len: usize,
+    alpha: f32,
+) -> Result<(f32, f32), Error> {
+    let (r1, r2) = nist_serial_result(data, block_len)?;
+
+    Ok((r1.at_alpha(alpha)?.check()?, r2.at_alpha(alpha)?.check()?))
+}
+
+/// NIST Serial Test returning both [TestResult]s, with statistics ∇ψ²m and ∇²ψ²m
+///
+/// See [nist_serial] for details
+pub fn nist_serial_result(
+    data: impl AsBits,
+    block_len: usize,
+) -> Result<(TestResult, TestResult), Error> {
+    let m = block_len;
+
+    // Check block length is supported
+    if m < 2 {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Count overlapping patterns
+    let counts = PatternCounts::new(data.into_bits(), m)?;
+
+    // Compute psi^2 for m, m-1, and m-2 bit patterns
+    let psi_m = counts.psi_sq(m);
+    let psi_m1 = counts.psi_sq(m - 1);
+    let psi_m2 = counts.psi_sq(m - 2);
+
+    // Compute first and second differences
+    let d1 = psi_m - psi_m1;
+    let d2 = psi_m - 2.0 * psi_m1 + psi_m2;
+
+    // Compute P-values
+    let p1 = igamma_upper(math::powf(2.0, m as f32 - 2.0), d1 / 2.0);
+    let p2 = igamma_upper(math::powf(2.0, m as f32 - 3.0), d2 / 2.0);
+
+    Ok((TestResult::new(p1, d1), TestResult::new(p2, d2)))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::helpers::BitIter;
+
+    #[test]
+    fn nist_serial_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 1024];
+        rng.fill_bytes(&mut buff);
+
+        nist_serial(BitIter::new(&buff), 4).expect("Serial test failed");
+    }
+
+    #[test]
+    fn nist_serial_ex() {
+        // Example from specification, m = 3
+        let buff = bits![0, 0, 1, 1, 0, 1, 1, 1, 0, 1];
+
+        let (p1, p2) = nist_serial(buff.iter().by_vals(), 3).expect("Serial test failed");
+
+        // Check p values match test vector
+        assert_approx_eq!(p1, 0.808792, 1e-5);
+        assert_approx_eq!(p2, 0.670320, 1e-5);
+
+        // Check statistics match test vector
+        let (r1, r2) = nist_serial_result(buff.iter().by_vals(), 3).expect("Serial test failed");
+        assert_approx_eq!(r1.statistic, 1.6, 1e-6);
+        assert_approx_eq!(r2.statistic, 0.8, 1e-6);
+    }
+
+    #[test]
+    fn nist_serial_fail() {
+        nist_serial(BitIter::from([0b1001_0110u8; 128]), 4).expect_err("Serial p > threshold");
+        nist_serial(BitIter::from([0x00u8; 128]), 4).expect_err("Serial p > threshold");
+    }
+
+    #[test]
+    fn nist_serial_invalid() {
+        let buff = [0u8; 16];
+
+        assert_eq!(
+            nist_serial(BitIter::new(&buff), 1),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            nist_serial(BitIter::new(&buff), SERIAL_MAX_BLOCK_LEN + 1),
+            Err(Error::InvalidParameter)
+        );
+    }
+}
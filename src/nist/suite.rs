@@ -0,0 +1,909 @@
+//! Batch runner for the NIST test suite
+
+use core::fmt;
+
+use super::universal::universal_block_len;
+use super::*;
+use crate::helpers::{BitIter, BitOrder};
+use crate::DEFAULT_ALPHA;
+
+/// Maximum block length (L) for the universal test in [run_suite], bounding the stack
+/// table to 4 KiB
+pub const SUITE_UNIVERSAL_MAX_BLOCK_LEN: usize = 10;
+
+/// Sequence length (in bits) recommended by the specification for the overlapping
+/// template, linear complexity, and random excursions tests
+const RECOMMENDED_LEN: usize = 1_000_000;
+
+/// Number of tests in the suite
+pub const SUITE_TESTS: usize = SuiteTest::ALL.len();
+
+/// Identifier for each NIST test, for enumerating the available tests
+///
+/// Unlike [SuiteTest] this includes the Discrete Fourier Transform test and identifies
+/// the cumulative sums test independently of mode.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TestId {
+    /// Frequency (monobit) test, see [nist_freq_monobit]
+    FrequencyMonobit,
+    /// Frequency test within a block, see [nist_freq_block]
+    FrequencyBlock,
+    /// Runs test, see [nist_runs]
+    Runs,
+    /// Longest run of ones in a block test, see [nist_longest_run]
+    LongestRun,
+    /// Binary matrix rank test, see [nist_matrix_rank]
+    MatrixRank,
+    /// Discrete Fourier Transform (spectral) test, see [nist_dft]
+    Dft,
+    /// Non-overlapping template matching test, see [nist_non_overlapping_template]
+    NonOverlappingTemplate,
+    /// Overlapping template matching test, see [nist_overlapping_template]
+    OverlappingTemplate,
+    /// Maurer's "universal statistical" test, see [nist_universal]
+    Universal,
+    /// Linear complexity test, see [nist_linear_complexity]
+    LinearComplexity,
+    /// Serial test, see [nist_serial]
+    Serial,
+    /// Approximate entropy test, see [nist_approx_entropy]
+    ApproxEntropy,
+    /// Cumulative sums test, see [nist_cusum]
+    Cusum,
+    /// Random excursions test, see [nist_random_excursions]
+    RandomExcursions,
+    /// Random excursions variant test, see [nist_random_excursions_variant]
+    RandomExcursionsVariant,
+}
+
+impl TestId {
+    /// All tests, in specification order
+    pub const fn all() -> &'static [TestId] {
+        &[
+            TestId::FrequencyMonobit,
+            TestId::FrequencyBlock,
+            TestId::Runs,
+            TestId::LongestRun,
+            TestId::MatrixRank,
+            TestId::Dft,
+            TestId::NonOverlappingTemplate,
+            TestId::OverlappingTemplate,
+            TestId::Universal,
+            TestId::LinearComplexity,
+            TestId::Serial,
+            TestId::ApproxEntropy,
+            TestId::Cusum,
+            TestId::RandomExcursions,
+            TestId::RandomExcursionsVariant,
+        ]
+    }
+
+    /// Test name, as given in the specification
+    pub fn name(&self) -> &'static str {
+        match self {
+            TestId::FrequencyMonobit => "Frequency (Monobit) Test",
+            TestId::FrequencyBlock => "Frequency Test within a Block",
+            TestId::Runs => "Runs Test",
+            TestId::LongestRun => "Test for the Longest Run of Ones in a Block",
+            TestId::MatrixRank => "Binary Matrix Rank Test",
+            TestId::Dft => "Discrete Fourier Transform (Spectral) Test",
+            TestId::NonOverlappingTemplate => "Non-overlapping Template Matching Test",
+            TestId::OverlappingTemplate => "Overlapping Template Matching Test",
+            TestId::Universal => "Maurer's \"Universal Statistical\" Test",
+            TestId::LinearComplexity => "Linear Complexity Test",
+            TestId::Serial => "Serial Test",
+            TestId::ApproxEntropy => "Approximate Entropy Test",
+            TestId::Cusum => "Cumulative Sums (Cusum) Test",
+            TestId::RandomExcursions => "Random Excursions Test",
+            TestId::RandomExcursionsVariant => "Random Excursions Variant Test",
+        }
+    }
+
+    /// Stable short key, for machine-readable output
+    pub fn id(&self) -> &'static str {
+        match self {
+            TestId::FrequencyMonobit => "monobit",
+            TestId::FrequencyBlock => "block_frequency",
+            TestId::Runs => "runs",
+            TestId::LongestRun => "longest_run",
+            TestId::MatrixRank => "matrix_rank",
+            TestId::Dft => "dft",
+            TestId::NonOverlappingTemplate => "non_overlapping_template",
+            TestId::OverlappingTemplate => "overlapping_template",
+            TestId::Universal => "universal",
+            TestId::LinearComplexity => "linear_complexity",
+            TestId::Serial => "serial",
+            TestId::ApproxEntropy => "approx_entropy",
+            TestId::Cusum => "cusum",
+            TestId::RandomExcursions => "random_excursions",
+            TestId::RandomExcursionsVariant => "random_excursions_variant",
+        }
+    }
+
+    /// Look up a test by [TestId::id]
+    pub fn from_id(id: &str) -> Option<TestId> {
+        TestId::all().iter().find(|t| t.id() == id).copied()
+    }
+}
+
+/// Tests executed by [run_suite]
+///
+/// The Discrete Fourier Transform test is not included as this requires a scratch buffer
+/// of twice the sequence length, see [nist_dft].
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SuiteTest {
+    /// Frequency (monobit) test
+    Monobit,
+    /// Frequency test within a block
+    BlockFrequency,
+    /// Runs test
+    Runs,
+    /// Longest run of ones in a block test
+    LongestRun,
+    /// Binary matrix rank test
+    MatrixRank,
+    /// Non-overlapping template matching test
+    NonOverlappingTemplate,
+    /// Overlapping template matching test
+    OverlappingTemplate,
+    /// Maurer's "universal statistical" test
+    Universal,
+    /// Linear complexity test
+    LinearComplexity,
+    /// Serial test
+    Serial,
+    /// Approximate entropy test
+    ApproxEntropy,
+    /// Cumulative sums test, forward mode
+    CusumForward,
+    /// Cumulative sums test, reverse mode
+    CusumReverse,
+    /// Random excursions test
+    RandomExcursions,
+    /// Random excursions variant test
+    RandomExcursionsVariant,
+}
+
+impl SuiteTest {
+    /// All tests, in the order executed
+    pub const ALL: [SuiteTest; 15] = [
+        SuiteTest::Monobit,
+        SuiteTest::BlockFrequency,
+        SuiteTest::Runs,
+        SuiteTest::LongestRun,
+        SuiteTest::MatrixRank,
+        SuiteTest::NonOverlappingTemplate,
+        SuiteTest::OverlappingTemplate,
+        SuiteTest::Universal,
+        SuiteTest::LinearComplexity,
+        SuiteTest::Serial,
+        SuiteTest::ApproxEntropy,
+        SuiteTest::CusumForward,
+        SuiteTest::CusumReverse,
+        SuiteTest::RandomExcursions,
+        SuiteTest::RandomExcursionsVariant,
+    ];
+
+    /// Test name, for reporting
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuiteTest::Monobit => "monobit",
+            SuiteTest::BlockFrequency => "block_frequency",
+            SuiteTest::Runs => "runs",
+            SuiteTest::LongestRun => "longest_run",
+            SuiteTest::MatrixRank => "matrix_rank",
+            SuiteTest::NonOverlappingTemplate => "non_overlapping_template",
+            SuiteTest::OverlappingTemplate => "overlapping_template",
+            SuiteTest::Universal => "universal",
+            SuiteTest::LinearComplexity => "linear_complexity",
+            SuiteTest::Serial => "serial",
+            SuiteTest::ApproxEntropy => "approx_entropy",
+            SuiteTest::CusumForward => "cusum_forward",
+            SuiteTest::CusumReverse => "cusum_reverse",
+            SuiteTest::RandomExcursions => "random_excursions",
+            SuiteTest::RandomExcursionsVariant => "random_excursions_variant",
+        }
+    }
+
+    /// Identifier for the underlying test
+    pub fn test_id(&self) -> TestId {
+        match self {
+            SuiteTest::Monobit => TestId::FrequencyMonobit,
+            SuiteTest::BlockFrequency => TestId::FrequencyBlock,
+            SuiteTest::Runs => TestId::Runs,
+            SuiteTest::LongestRun => TestId::LongestRun,
+            SuiteTest::MatrixRank => TestId::MatrixRank,
+            SuiteTest::NonOverlappingTemplate => TestId::NonOverlappingTemplate,
+            SuiteTest::OverlappingTemplate => TestId::OverlappingTemplate,
+            SuiteTest::Universal => TestId::Universal,
+            SuiteTest::LinearComplexity => TestId::LinearComplexity,
+            SuiteTest::Serial => TestId::Serial,
+            SuiteTest::ApproxEntropy => TestId::ApproxEntropy,
+            SuiteTest::CusumForward | SuiteTest::CusumReverse => TestId::Cusum,
+            SuiteTest::RandomExcursions => TestId::RandomExcursions,
+            SuiteTest::RandomExcursionsVariant => TestId::RandomExcursionsVariant,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Control flow for [run_suite] on test failure
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SuiteMode {
+    /// Run every enabled test, for diagnostics
+    #[default]
+    RunAll,
+    /// Stop at the first test returning an error (in [SuiteTest::ALL] order), leaving
+    /// the results of later tests as `None`, for gating health checks
+    StopOnFail,
+}
+
+/// Configuration for [run_suite]
+#[derive(Clone, PartialEq, Debug)]
+pub struct SuiteConfig {
+    /// Enabled tests, see [SuiteConfig::with_test]
+    enabled: [bool; SUITE_TESTS],
+
+    /// Significance level (α) for all tests
+    pub alpha: f32,
+
+    /// Bit order for reading the input bytes
+    pub order: BitOrder,
+
+    /// Block length (M) for the block frequency test, or `None` to select the minimum
+    /// meeting the specification requirements (see [nist_freq_block_min_len]) rounded up
+    /// to a whole number of bytes
+    pub block_frequency_len: Option<usize>,
+
+    /// Matrix dimensions (M x Q) for the binary matrix rank test
+    pub matrix_dim: (usize, usize),
+
+    /// Template for the non-overlapping template matching test
+    pub template: &'static [bool],
+
+    /// Block length (M) for the non-overlapping template matching test, or `None` to
+    /// split the sequence into 8 blocks
+    pub template_block_len: Option<usize>,
+
+    /// Block length (M) for the linear complexity test
+    pub linear_complexity_len: usize,
+
+    /// Pattern length (m) for the serial test
+    pub serial_len: usize,
+
+    /// Pattern length (m) for the approximate entropy test
+    pub approx_entropy_len: usize,
+
+    /// Report [Error::InsufficientSampleSize] for tests where the sequence is shorter
+    /// than [recommended_min_bits], rather than running these where possible
+    pub require_recommended_len: bool,
+
+    /// Whether to continue after a failing test, see [SuiteMode]
+    pub mode: SuiteMode,
+}
+
+impl Default for SuiteConfig {
+    /// Default configuration with all tests enabled, using the parameters recommended by
+    /// the specification where these are supported
+    fn default() -> Self {
+        Self {
+            enabled: [true; SUITE_TESTS],
+            alpha: DEFAULT_ALPHA,
+            order: BitOrder::Lsb,
+            block_frequency_len: None,
+            matrix_dim: (32, 32),
+            template: &[false, false, false, false, false, false, false, false, true],
+            template_block_len: None,
+            linear_complexity_len: 500,
+            serial_len: 8,
+            approx_entropy_len: 6,
+            require_recommended_len: false,
+            mode: SuiteMode::RunAll,
+        }
+    }
+}
+
+impl SuiteConfig {
+    /// Enable or disable a test
+    pub fn with_test(mut self, test: SuiteTest, enabled: bool) -> Self {
+        self.enabled[test.index()] = enabled;
+        self
+    }
+
+    /// Check whether a test is enabled
+    pub fn is_enabled(&self, test: SuiteTest) -> bool {
+        self.enabled[test.index()]
+    }
+}
+
+/// Results from [run_suite]
+///
+/// Tests returning multiple P-values report the lowest of these on success, or the first
+/// failure.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuiteReport {
+    results: [Option<Result<f32, Error>>; SUITE_TESTS],
+}
+
+impl SuiteReport {
+    /// Create a report with no results, as `Error` is not `Copy`
+    fn empty() -> Self {
+        Self {
+            results: core::array::from_fn(|_| None),
+        }
+    }
+
+    /// Fetch the result for a test, or `None` where this was disabled or skipped
+    /// following a failure in [SuiteMode::StopOnFail]
+    pub fn get(&self, test: SuiteTest) -> Option<&Result<f32, Error>> {
+        self.results[test.index()].as_ref()
+    }
+
+    /// Iterate over the results of executed tests
+    pub fn iter(&self) -> impl Iterator<Item = (SuiteTest, &Result<f32, Error>)> {
+        SuiteTest::ALL
+            .iter()
+            .zip(self.results.iter())
+            .filter_map(|(t, r)| r.as_ref().map(|r| (*t, r)))
+    }
+
+    /// Number of enabled tests that passed
+    pub fn passed(&self) -> usize {
+        self.iter().filter(|(_, r)| r.is_ok()).count()
+    }
+
+    /// Number of enabled tests that failed or could not be executed
+    pub fn failed(&self) -> usize {
+        self.iter().filter(|(_, r)| r.is_err()).count()
+    }
+
+    /// Check whether all enabled tests passed
+    pub fn is_ok(&self) -> bool {
+        self.failed() == 0
+    }
+}
+
+impl fmt::Display for SuiteReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (t, r) in self.iter() {
+            match r {
+                Ok(p) => writeln!(f, "{}: pass (p = {:.6})", t.name(), p)?,
+                Err(e) => writeln!(f, "{}: fail ({})", t.name(), e)?,
+            }
+        }
+
+        write!(
+            f,
+            "{} / {} passed",
+            self.passed(),
+            self.passed() + self.failed()
+        )
+    }
+}
+
+/// Compute the minimum sequence length (in bits) recommended by the specification for a
+/// test with the parameters from [SuiteConfig]
+///
+/// This allows the data required to be determined before collection, see the input size
+/// recommendations for each test in SP 800-22 section 2. The standard gives no minimum
+/// for the non-overlapping template test, so this requires one template length per block.
+///
+/// Shorter sequences are rejected by [run_suite] where
+/// [SuiteConfig::require_recommended_len] is set, and by the [Test](super::Test) builders
+/// with `require_recommended_len()`. Otherwise tests run over any sequence long enough to
+/// compute their statistic, returning [Error::InsufficientSampleSize] only below the
+/// (smaller) minimum documented for each, so results for short sequences are usable but
+/// less reliable.
+pub fn recommended_min_bits(test: TestId, config: &SuiteConfig) -> usize {
+    match test {
+        TestId::FrequencyMonobit => MONOBIT_MIN_LEN,
+        // n >= 100 with at least one block
+        TestId::FrequencyBlock => config.block_frequency_len.unwrap_or(0).max(100),
+        TestId::Runs => RUNS_MIN_LEN,
+        TestId::LongestRun => LONGEST_RUN_MIN_LEN,
+        TestId::Dft => DFT_MIN_LEN,
+        // n >= 38 M Q, for at least 38 matrices
+        TestId::MatrixRank => 38 * config.matrix_dim.0 * config.matrix_dim.1,
+        TestId::NonOverlappingTemplate => {
+            non_overlapping_min_bits(config.template.len(), config.template_block_len)
+        }
+        TestId::OverlappingTemplate => RECOMMENDED_LEN,
+        TestId::Universal => UNIVERSAL_MIN_LEN,
+        // n >= 10^6 with at least 200 blocks
+        TestId::LinearComplexity => RECOMMENDED_LEN.max(200 * config.linear_complexity_len),
+        // m < floor(log2 n) - 2
+        TestId::Serial => 1 << (config.serial_len + 3),
+        // m < floor(log2 n) - 5
+        TestId::ApproxEntropy => 1 << (config.approx_entropy_len + 6),
+        TestId::Cusum => CUSUM_MIN_LEN,
+        TestId::RandomExcursions | TestId::RandomExcursionsVariant => RECOMMENDED_LEN,
+    }
+}
+
+/// Compute the recommended minimum length for the non-overlapping template test, one
+/// template length per block
+pub(super) const fn non_overlapping_min_bits(
+    template_len: usize,
+    block_len: Option<usize>,
+) -> usize {
+    match block_len {
+        Some(m) if m > template_len => m,
+        Some(_) => template_len,
+        None => 8 * template_len,
+    }
+}
+
+/// Run each enabled NIST test over `data`, using the parameters from [SuiteConfig]
+///
+/// The universal test selects L from the sequence length as in [nist_universal], limited
+/// to [SUITE_UNIVERSAL_MAX_BLOCK_LEN]. Note the random excursions tests require around
+/// 10^6 bits, so report [Error::InsufficientSampleSize] for shorter sequences.
+///
+/// As the individual tests each consume an iterator, output from a live RNG must be
+/// captured into a buffer first. Each test then reads the same sequence afresh from
+/// `data` through a new [BitIter] in the configured [SuiteConfig::order], so all tests
+/// see identical bits and results do not depend on which other tests are enabled or the
+/// order in which they run. Nothing is cached between tests and `data` is never
+/// modified, so a single capture may be re-used across calls or configurations.
+///
+/// With [SuiteMode::StopOnFail] the suite stops at the first test returning an error,
+/// including where the sequence is too short for that test.
+///
+/// ```
+/// use rand::{rngs::StdRng, RngCore, SeedableRng};
+/// use rngcheck::nist::{run_suite, SuiteConfig};
+///
+/// // Capture once, then run every test over the same bytes
+/// let mut data = [0u8; 1 << 14];
+/// StdRng::seed_from_u64(1).fill_bytes(&mut data);
+///
+/// let report = run_suite(&data, &SuiteConfig::default());
+/// assert_eq!(report, run_suite(&data, &SuiteConfig::default()));
+/// ```
+pub fn run_suite(data: &[u8], config: &SuiteConfig) -> SuiteReport {
+    let mut report = SuiteReport::empty();
+
+    for t in SuiteTest::ALL {
+        if !config.is_enabled(t) {
+            continue;
+        }
+
+        let r = run_test(t, data, config);
+        let failed = r.is_err();
+        report.results[t.index()] = Some(r);
+
+        if failed && config.mode == SuiteMode::StopOnFail {
+            break;
+        }
+    }
+
+    report
+}
+
+/// Run each enabled NIST test over each of `sequences` in parallel, using the parameters
+/// from [SuiteConfig]
+///
+/// This distributes both sequences and the tests for each sequence over the rayon thread
+/// pool, returning a [SuiteReport] per sequence identical to that from [run_suite].
+/// With [SuiteMode::StopOnFail] the tests for each sequence are run in order, so only
+/// the sequences are distributed.
+#[cfg(feature = "rayon")]
+pub fn run_suite_parallel(sequences: &[&[u8]], config: &SuiteConfig) -> std::vec::Vec<SuiteReport> {
+    use rayon::prelude::*;
+
+    sequences
+        .par_iter()
+        .map(|data| {
+            if config.mode == SuiteMode::StopOnFail {
+                return run_suite(data, config);
+            }
+
+            let mut report = SuiteReport::empty();
+
+            report
+                .results
+                .par_iter_mut()
+                .zip(SuiteTest::ALL.par_iter())
+                .filter(|(_, t)| config.is_enabled(**t))
+                .for_each(|(r, t)| *r = Some(run_test(*t, data, config)));
+
+            report
+        })
+        .collect()
+}
+
+/// Run a single test, returning the (lowest) P-value at the configured α
+fn run_test(test: SuiteTest, data: &[u8], config: &SuiteConfig) -> Result<f32, Error> {
+    let bits = || BitIter::with_order(data, config.order);
+    let n = data.len() * 8;
+
+    // Check for empty input, as parameters derived from n are otherwise invalid
+    if n == 0 {
+        return Err(Error::InsufficientSampleSize(0));
+    }
+
+    if config.require_recommended_len && n < recommended_min_bits(test.test_id(), config) {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    let r = match test {
+        SuiteTest::Monobit => nist_freq_monobit_bytes_result(data)?,
+        SuiteTest::BlockFrequency => {
+            // Select whole byte blocks by default, using popcount over each block
+            let block_len = config
+                .block_frequency_len
+                .unwrap_or(nist_freq_block_min_len(n).next_multiple_of(8));
+
+            match block_len % 8 == 0 {
+                true => freq_block_bytes_result(data, block_len)?,
+                false => nist_freq_block_result(bits(), block_len)?,
+            }
+        }
+        SuiteTest::Runs => nist_runs_result(bits())?,
+        SuiteTest::LongestRun => nist_longest_run_result(bits())?,
+        SuiteTest::MatrixRank => {
+            let (m, q) = config.matrix_dim;
+            nist_matrix_rank_result(bits(), m, q)?
+        }
+        SuiteTest::NonOverlappingTemplate => {
+            let block_len = config.template_block_len.unwrap_or(n / 8);
+            nist_non_overlapping_template_result(bits(), config.template, block_len)?
+        }
+        SuiteTest::OverlappingTemplate => nist_overlapping_template_result(
+            bits(),
+            OVERLAPPING_TEMPLATE_LEN,
+            OVERLAPPING_BLOCK_LEN,
+        )?,
+        SuiteTest::Universal => {
+            let l = universal_block_len(n)?.min(SUITE_UNIVERSAL_MAX_BLOCK_LEN);
+            let mut table = [0u32; nist_universal_table_len(SUITE_UNIVERSAL_MAX_BLOCK_LEN)];
+            nist_universal_with_params_result(bits(), l, 10 * (1 << l), &mut table)?
+        }
+        SuiteTest::LinearComplexity => {
+            nist_linear_complexity_result(bits(), config.linear_complexity_len)?
+        }
+        SuiteTest::Serial => {
+            let (r1, r2) = nist_serial_result(bits(), config.serial_len)?;
+            return check_min(&[r1, r2], config.alpha);
+        }
+        SuiteTest::ApproxEntropy => nist_approx_entropy_result(bits(), config.approx_entropy_len)?,
+        SuiteTest::CusumForward => nist_cusum_result(bits(), CusumMode::Forward)?,
+        SuiteTest::CusumReverse => nist_cusum_result(bits(), CusumMode::Reverse)?,
+        SuiteTest::RandomExcursions => {
+            return check_min(&nist_random_excursions_result(bits())?, config.alpha);
+        }
+        SuiteTest::RandomExcursionsVariant => {
+            return check_min(
+                &nist_random_excursions_variant_result(bits())?,
+                config.alpha,
+            );
+        }
+    };
+
+    r.at_alpha(config.alpha)?.check()
+}
+
+/// Check all results at α, returning the lowest P-value
+fn check_min(results: &[TestResult], alpha: f32) -> Result<f32, Error> {
+    let mut min = f32::INFINITY;
+
+    for r in results {
+        min = min.min(r.at_alpha(alpha)?.check()?);
+    }
+
+    Ok(min)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::string::ToString;
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn suite_results() {
+        let mut buff = [0u8; 1 << 14];
+        lcg_fill(&mut buff, 5);
+
+        let report = run_suite(&buff, &SuiteConfig::default());
+        assert_eq!(report.iter().count(), SUITE_TESTS);
+        assert_eq!(report.passed() + report.failed(), SUITE_TESTS);
+
+        // Results match the individual tests
+        assert_eq!(
+            report.get(SuiteTest::Monobit),
+            Some(&nist_freq_monobit(BitIter::new(&buff)))
+        );
+        assert_eq!(
+            report.get(SuiteTest::BlockFrequency),
+            Some(&nist_freq_block(BitIter::new(&buff), 1312))
+        );
+        assert_eq!(
+            report.get(SuiteTest::CusumReverse),
+            Some(&nist_cusum(BitIter::new(&buff), CusumMode::Reverse))
+        );
+
+        let (p1, p2) = nist_serial(BitIter::new(&buff), 8).unwrap();
+        assert_eq!(report.get(SuiteTest::Serial), Some(&Ok(p1.min(p2))));
+
+        // Sequence is too short for the universal and excursions tests
+        assert_eq!(
+            report.get(SuiteTest::Universal),
+            Some(&Err(Error::InsufficientSampleSize(buff.len() * 8)))
+        );
+        assert!(matches!(
+            report.get(SuiteTest::RandomExcursions),
+            Some(Err(Error::InsufficientSampleSize(_)))
+        ));
+    }
+
+    #[test]
+    fn suite_shared_buffer() {
+        let mut buff = [0u8; 1 << 14];
+        lcg_fill(&mut buff, 7);
+        let copy = buff;
+
+        let config = SuiteConfig {
+            order: BitOrder::Msb,
+            ..SuiteConfig::default()
+        };
+        let report = run_suite(&buff, &config);
+
+        // Input is unchanged and re-running gives identical results
+        assert_eq!(buff, copy);
+        assert_eq!(run_suite(&buff, &config), report);
+
+        // Each test sees the full sequence in the configured order, independent of the others
+        for t in SuiteTest::ALL {
+            let single = SuiteTest::ALL
+                .iter()
+                .filter(|o| **o != t)
+                .fold(config.clone(), |c, o| c.with_test(*o, false));
+            assert_eq!(
+                run_suite(&buff, &single).get(t),
+                report.get(t),
+                "{}",
+                t.name()
+            );
+        }
+        assert_eq!(
+            report.get(SuiteTest::Runs),
+            Some(&nist_runs(BitIter::with_order(&buff, BitOrder::Msb)))
+        );
+        assert_eq!(
+            report.get(SuiteTest::ApproxEntropy),
+            Some(&nist_approx_entropy(
+                BitIter::with_order(&buff, BitOrder::Msb),
+                6
+            ))
+        );
+    }
+
+    #[test]
+    fn suite_recommended_len() {
+        // Default parameters, from the input size recommendations in SP 800-22
+        let config = SuiteConfig::default();
+        let expected = [
+            100,
+            100,
+            100,
+            128,
+            38 * 32 * 32,
+            1000,
+            8 * 9,
+            1_000_000,
+            387_840,
+            1_000_000,
+            1 << 11,
+            1 << 12,
+            100,
+            1_000_000,
+            1_000_000,
+        ];
+        for (t, n) in TestId::all().iter().zip(expected) {
+            assert_eq!(recommended_min_bits(*t, &config), n, "{}", t.name());
+        }
+
+        // Parameter dependent minimums
+        let config = SuiteConfig {
+            block_frequency_len: Some(128),
+            matrix_dim: (8, 8),
+            linear_complexity_len: 5000,
+            serial_len: 16,
+            approx_entropy_len: 10,
+            ..SuiteConfig::default()
+        };
+        assert_eq!(recommended_min_bits(TestId::FrequencyBlock, &config), 128);
+        assert_eq!(recommended_min_bits(TestId::MatrixRank, &config), 2432);
+        assert_eq!(
+            recommended_min_bits(TestId::LinearComplexity, &config),
+            1_000_000
+        );
+        assert_eq!(recommended_min_bits(TestId::Serial, &config), 1 << 19);
+        assert_eq!(
+            recommended_min_bits(TestId::ApproxEntropy, &config),
+            1 << 16
+        );
+
+        // Shorter sequences are rejected where required
+        let mut buff = [0u8; 1 << 14];
+        lcg_fill(&mut buff, 5);
+
+        let config = SuiteConfig {
+            require_recommended_len: true,
+            ..SuiteConfig::default()
+        };
+        let report = run_suite(&buff, &config);
+        for (t, r) in report.iter() {
+            match recommended_min_bits(t.test_id(), &config) <= buff.len() * 8 {
+                true => assert_eq!(Some(r), run_suite(&buff, &SuiteConfig::default()).get(t)),
+                false => assert_eq!(r, &Err(Error::InsufficientSampleSize(buff.len() * 8))),
+            }
+        }
+    }
+
+    #[test]
+    fn suite_test_id() {
+        // Keys are unique and round-trip
+        for (i, t) in TestId::all().iter().enumerate() {
+            assert_eq!(TestId::from_id(t.id()), Some(*t));
+            assert!(TestId::all()[i + 1..].iter().all(|u| u.id() != t.id()));
+        }
+        assert_eq!(TestId::all().len(), 15);
+        assert_eq!(TestId::from_id("dieharder"), None);
+
+        // Suite tests map to their underlying test, sharing keys other than cusum
+        for t in SuiteTest::ALL {
+            match t {
+                SuiteTest::CusumForward | SuiteTest::CusumReverse => {
+                    assert_eq!(t.test_id(), TestId::Cusum)
+                }
+                _ => assert_eq!(t.test_id().id(), t.name()),
+            }
+        }
+        assert_eq!(TestId::FrequencyMonobit.name(), "Frequency (Monobit) Test");
+    }
+
+    #[test]
+    fn suite_disabled() {
+        let buff = [0u8; 1024];
+
+        let config = SuiteConfig::default()
+            .with_test(SuiteTest::Universal, false)
+            .with_test(SuiteTest::LinearComplexity, false);
+        let report = run_suite(&buff, &config);
+
+        assert_eq!(report.get(SuiteTest::Universal), None);
+        assert_eq!(report.get(SuiteTest::LinearComplexity), None);
+        assert_eq!(report.iter().count(), SUITE_TESTS - 2);
+
+        // Constant data fails
+        assert!(!report.is_ok());
+        assert!(matches!(
+            report.get(SuiteTest::Monobit),
+            Some(Err(Error::BadPValue { .. }))
+        ));
+    }
+
+    #[test]
+    fn suite_mode() {
+        // Constant data fails most tests
+        let buff = [0u8; 1024];
+
+        let config = SuiteConfig::default().with_test(SuiteTest::Monobit, false);
+        let all = run_suite(&buff, &config);
+        assert_eq!(all.iter().count(), SUITE_TESTS - 1);
+        assert!(all.failed() > 1);
+
+        // Stops at the first failure, with identical results up to this
+        let config = SuiteConfig {
+            mode: SuiteMode::StopOnFail,
+            ..config
+        };
+        let stopped = run_suite(&buff, &config);
+
+        assert_eq!(stopped.iter().count(), 1);
+        assert_eq!(stopped.failed(), 1);
+        assert_eq!(stopped.get(SuiteTest::Monobit), None);
+        assert_eq!(
+            stopped.get(SuiteTest::BlockFrequency),
+            all.get(SuiteTest::BlockFrequency)
+        );
+        for t in &SuiteTest::ALL[2..] {
+            assert_eq!(stopped.get(*t), None, "{}", t.name());
+        }
+
+        // Passing tests do not stop the suite, which runs until the excursions tests
+        // report the sequence is too short
+        let mut buff = [0u8; 1 << 14];
+        lcg_fill(&mut buff, 5);
+        let config = SuiteConfig {
+            mode: SuiteMode::StopOnFail,
+            ..SuiteConfig::default()
+        }
+        .with_test(SuiteTest::Universal, false);
+        let stopped = run_suite(&buff, &config);
+        let all = run_suite(&buff, &SuiteConfig::default());
+
+        assert_eq!(stopped.failed(), 1);
+        for (t, r) in stopped.iter() {
+            assert_eq!(Some(r), all.get(t), "{}", t.name());
+        }
+        assert!(matches!(
+            stopped.get(SuiteTest::RandomExcursions),
+            Some(Err(Error::InsufficientSampleSize(_)))
+        ));
+        assert_eq!(stopped.get(SuiteTest::RandomExcursionsVariant), None);
+        assert_eq!(stopped.iter().count(), SUITE_TESTS - 2);
+    }
+
+    #[test]
+    fn suite_display() {
+        let buff = [0u8; 1024];
+
+        let config = SuiteConfig::default();
+        let config = SuiteTest::ALL
+            .iter()
+            .filter(|t| **t != SuiteTest::Monobit)
+            .fold(config, |c, t| c.with_test(*t, false));
+
+        let s = run_suite(&buff, &config).to_string();
+        assert_eq!(
+            s,
+            "monobit: fail (P-value 0 outside required bounds (statistic 90.50967))\n0 / 1 passed"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn suite_parallel() {
+        use std::vec::Vec;
+
+        let mut buffs = [[0u8; 1 << 13]; 4];
+        for (i, b) in buffs.iter_mut().enumerate() {
+            lcg_fill(b, i as u32);
+        }
+        buffs[3].fill(0);
+
+        // Parallel reports match the serial runner, including failures and disabled tests
+        let config = SuiteConfig::default().with_test(SuiteTest::LinearComplexity, false);
+        let sequences: Vec<&[u8]> = buffs.iter().map(|b| &b[..]).collect();
+        let reports = run_suite_parallel(&sequences, &config);
+
+        assert_eq!(reports.len(), sequences.len());
+        for (r, s) in reports.iter().zip(&sequences) {
+            assert_eq!(r, &run_suite(s, &config));
+        }
+
+        let config = SuiteConfig {
+            mode: SuiteMode::StopOnFail,
+            ..config
+        };
+        let reports = run_suite_parallel(&sequences, &config);
+        for (r, s) in reports.iter().zip(&sequences) {
+            assert_eq!(r, &run_suite(s, &config));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn suite_serde() {
+        let mut buff = [0u8; 1 << 14];
+        lcg_fill(&mut buff, 5);
+
+        // Reports with passed, failed, and disabled tests round-trip
+        let config = SuiteConfig::default().with_test(SuiteTest::Universal, false);
+        let report = run_suite(&buff, &config);
+
+        let s = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<SuiteReport>(&s).unwrap(), report);
+    }
+}
@@ -0,0 +1,79 @@
+//! Standard aperiodic templates for the
+//! [Non-overlapping Template Matching Test](super::nist_non_overlapping_template)
+//!
+//! Templates are aperiodic, such that no proper shift of a template overlaps itself,
+//! and are listed in the same (lexicographic) order as the NIST reference templates.
+
+/// Aperiodic templates of length 2
+pub const M2: &[&[bool]] = &[&[false, true], &[true, false]];
+
+/// Aperiodic templates of length 3
+pub const M3: &[&[bool]] = &[
+    &[false, false, true],
+    &[false, true, true],
+    &[true, false, false],
+    &[true, true, false],
+];
+
+/// Aperiodic templates of length 4
+pub const M4: &[&[bool]] = &[
+    &[false, false, false, true],
+    &[false, false, true, true],
+    &[false, true, true, true],
+    &[true, false, false, false],
+    &[true, true, false, false],
+    &[true, true, true, false],
+];
+
+/// Aperiodic templates of length 5
+pub const M5: &[&[bool]] = &[
+    &[false, false, false, false, true],
+    &[false, false, false, true, true],
+    &[false, false, true, false, true],
+    &[false, false, true, true, true],
+    &[false, true, false, true, true],
+    &[false, true, true, true, true],
+    &[true, false, false, false, false],
+    &[true, false, true, false, false],
+    &[true, true, false, false, false],
+    &[true, true, false, true, false],
+    &[true, true, true, false, false],
+    &[true, true, true, true, false],
+];
+
+/// Fetch the aperiodic templates of length `m`, where available
+pub fn aperiodic(m: usize) -> Option<&'static [&'static [bool]]> {
+    match m {
+        2 => Some(M2),
+        3 => Some(M3),
+        4 => Some(M4),
+        5 => Some(M5),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_aperiodic() {
+        for m in 2..=5 {
+            let templates = aperiodic(m).unwrap();
+
+            for t in templates {
+                assert_eq!(t.len(), m);
+
+                // No proper shift of the template may overlap itself
+                for k in 1..m {
+                    assert_ne!(t[k..], t[..m - k], "template {:?} is periodic", t);
+                }
+            }
+        }
+
+        assert_eq!(M2.len(), 2);
+        assert_eq!(M3.len(), 4);
+        assert_eq!(M4.len(), 6);
+        assert_eq!(M5.len(), 12);
+    }
+}
@@ -0,0 +1,305 @@
+//! NIST Maurer's "Universal Statistical" Test
+
+use crate::helpers::AsBits;
+use crate::math;
+use crate::{Error, TestResult};
+
+/// Maximum supported block length (L)
+pub const UNIVERSAL_MAX_BLOCK_LEN: usize = 16;
+
+/// Minimum sequence length (in bits) for the universal test, `(Q + K) L` for L = 6
+pub const UNIVERSAL_MIN_LEN: usize = MIN_BITS[0];
+
+/// Minimum sequence length for each block length L, from L = 6
+const MIN_BITS: [usize; 11] = [
+    387_840,
+    904_960,
+    2_068_480,
+    4_654_080,
+    10_342_400,
+    22_753_280,
+    49_643_520,
+    107_560_960,
+    231_669_760,
+    496_435_200,
+    1_059_061_760,
+];
+
+/// Expected value of the test statistic for each block length L
+const EXPECTED: [f32; 17] = [
+    0.0, 0.7326495, 1.5374383, 2.4016068, 3.3112247, 4.2534266, 5.2177052, 6.196251, 7.1836656,
+    8.176425, 9.172324, 10.170032, 11.168765, 12.16807, 13.167693, 14.167488, 15.167379,
+];
+
+/// Variance of the test statistic for each block length L
+const VARIANCE: [f32; 17] = [
+    0.0, 0.690, 1.338, 1.901, 2.358, 2.705, 2.954, 3.125, 3.238, 3.311, 3.356, 3.384, 3.401, 3.410,
+    3.416, 3.419, 3.421,
+];
+
+/// Compute the table length (in `u32`s) required by [nist_universal] for block length L
+pub const fn nist_universal_table_len(l: usize) -> usize {
+    1 << l
+}
+
+/// NIST Maurer's "Universal Statistical" Test over an iterator of N bits
+///
+/// This selects the block length L (6 to 16) and the number of initialisation blocks Q
+/// from the sequence length n, requiring at least [UNIVERSAL_MIN_LEN] bits. As n is
+/// required up front this takes an [ExactSizeIterator] such as
+/// [BitIter](crate::helpers::BitIter), see [nist_universal_with_params] for other
+/// iterators.
+///
+/// As this is `no_std` and does not allocate, the caller provides a `table` of at least
+/// [nist_universal_table_len] `u32`s to hold the last occurrence of each L-bit pattern.
+/// This is 256 bytes for L = 6 (n < 904,960) and grows to 256 KiB for L = 16.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn nist_universal(
+    data: impl ExactSizeIterator<Item = bool>,
+    table: &mut [u32],
+) -> Result<f32, Error> {
+    nist_universal_result(data, table)?.check()
+}
+
+/// NIST Maurer's "Universal Statistical" Test at significance level `alpha`
+///
+/// See [nist_universal] for details
+pub fn nist_universal_with_alpha(
+    data: impl ExactSizeIterator<Item = bool>,
+    table: &mut [u32],
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_universal_result(data, table)?.at_alpha(alpha)?.check()
+}
+
+/// NIST Maurer's "Universal Statistical" Test returning the [TestResult], with statistic
+/// f_n
+///
+/// See [nist_universal] for details
+pub fn nist_universal_result(
+    data: impl ExactSizeIterator<Item = bool>,
+    table: &mut [u32],
+) -> Result<TestResult, Error> {
+    let l = universal_block_len(data.len())?;
+    let q = 10 * (1 << l);
+
+    nist_universal_with_params_result(data, l, q, table)
+}
+
+/// Select the block length L for a sequence of n bits
+pub(super) fn universal_block_len(n: usize) -> Result<usize, Error> {
+    match MIN_BITS.iter().rposition(|m| n >= *m) {
+        Some(i) => Ok(i + 6),
+        None => Err(Error::InsufficientSampleSize(n)),
+    }
+}
+
+/// NIST Maurer's "Universal Statistical" Test with a block length L and Q initialisation
+/// blocks
+///
+/// The specification recommends Q >= 10 * 2^L and at least 1000 * 2^L test blocks,
+/// where the remaining blocks after initialisation are used for testing.
+///
+/// See [nist_universal] for details
+pub fn nist_universal_with_params(
+    data: impl AsBits,
+    l: usize,
+    q: usize,
+    table: &mut [u32],
+) -> Result<f32, Error> {
+    nist_universal_with_params_result(data, l, q, table)?.check()
+}
+
+/// NIST Maurer's "Universal Statistical" Test with a block length L and Q initialisation
+/// blocks at significance level `alpha`
+///
+/// See [nist_universal_with_params] for details
+pub fn nist_universal_with_params_with_alpha(
+    data: impl AsBits,
+    l: usize,
+    q: usize,
+    table: &mut [u32],
+    alpha: f32,
+) -> Result<f32, Error> {
+    nist_universal_with_params_result(data, l, q, table)?
+        .at_alpha(alpha)?
+        .check()
+}
+
+/// NIST Maurer's "Universal Statistical" Test with a block length L and Q initialisation
+/// blocks returning the [TestResult], with statistic f_n
+///
+/// See [nist_universal] for details
+pub fn nist_universal_with_params_result(
+    data: impl AsBits,
+    l: usize,
+    q: usize,
+    table: &mut [u32],
+) -> Result<TestResult, Error> {
+    let (f, k) = universal_statistic(data.into_bits(), l, q, table)?;
+
+    // Compute standard deviation of the test statistic
+    let c = 0.7 - 0.8 / l as f32
+        + (4.0 + 32.0 / l as f32) * math::powf(k as f32, -3.0 / l as f32) / 15.0;
+    let sigma = c * math::sqrtf(VARIANCE[l] / k as f32);
+
+    // Compute P-value
+    let p = math::erfcf(math::fabsf(f - EXPECTED[l]) / (math::sqrtf(2.0) * sigma));
+
+    Ok(TestResult::new(p, f))
+}
+
+/// Compute the universal test statistic, returning the statistic and the number of
+/// test blocks K
+fn universal_statistic(
+    mut data: impl Iterator<Item = bool>,
+    l: usize,
+    q: usize,
+    table: &mut [u32],
+) -> Result<(f32, usize), Error> {
+    // Check block length is supported
+    if l == 0 || l > UNIVERSAL_MAX_BLOCK_LEN || q == 0 {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Check table size
+    let table = match table.get_mut(..nist_universal_table_len(l)) {
+        Some(t) => t,
+        None => return Err(Error::InvalidParameter),
+    };
+    table.fill(0);
+
+    let mut n = 0;
+    let mut i = 0;
+    let mut sum = 0f64;
+
+    'outer: loop {
+        // Read the next L-bit block, with the first bit highest, discarding incomplete blocks
+        let mut v = 0;
+        for _ in 0..l {
+            match data.next() {
+                Some(d) => v = (v << 1) | d as usize,
+                None => break 'outer,
+            }
+            n += 1;
+        }
+
+        i += 1;
+
+        // Accumulate log2 of the distance since the last occurrence of this block,
+        // in f64 as the sum spans up to 2^26 blocks
+        if i > q {
+            sum += math::log2((i - table[v] as usize) as f64);
+        }
+
+        // Update last occurrence
+        table[v] = i as u32;
+    }
+
+    // Check we have test blocks following initialisation
+    if i <= q {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    let k = i - q;
+    Ok(((sum / k as f64) as f32, k))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use bitvec::prelude::*;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn nist_universal_ok() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut buff = [0u8; 387_840 / 8];
+        rng.fill_bytes(&mut buff);
+
+        let mut table = [0u32; nist_universal_table_len(6)];
+        nist_universal_with_params(BitIter::new(&buff), 6, 640, &mut table)
+            .expect("Universal test failed");
+    }
+
+    #[test]
+    fn nist_universal_ex() {
+        // Example from specification, L = 2 and Q = 4
+        let buff = bits![0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 1, 0, 1, 0, 1, 1, 1];
+
+        let mut table = [0u32; nist_universal_table_len(2)];
+        let (f, k) = universal_statistic(buff.iter().by_vals(), 2, 4, &mut table).unwrap();
+
+        // Check statistic matches test vector, note the specification P-value of
+        // 0.767189 omits the correction factor c and the 1/K scaling of the variance
+        // so is not comparable for such short sequences
+        assert_approx_eq!(f, 1.1949875, 1e-6);
+        assert_eq!(k, 6);
+    }
+
+    #[test]
+    fn nist_universal_vector() {
+        let mut buff = [0u8; 387_840 / 8];
+        lcg_fill(&mut buff, 1);
+
+        // BitIter provides an exact length for L / Q selection
+        let mut table = [0u32; nist_universal_table_len(6)];
+        let p = nist_universal(BitIter::new(&buff), &mut table).expect("Universal test failed");
+
+        // Check p value matches reference (L = 6, Q = 640, statistic 5.219248)
+        assert_approx_eq!(p, 0.689882, 1e-3);
+
+        // Check with explicit parameters
+        let mut buff = [0u8; 2048];
+        lcg_fill(&mut buff, 7);
+
+        let mut table = [0u32; nist_universal_table_len(4)];
+        let p = nist_universal_with_params(BitIter::new(&buff), 4, 160, &mut table)
+            .expect("Universal test failed");
+
+        // Check p value matches reference (statistic 3.307411)
+        assert_approx_eq!(p, 0.756107, 1e-3);
+    }
+
+    #[test]
+    fn nist_universal_fail() {
+        let mut table = [0u32; nist_universal_table_len(4)];
+
+        nist_universal_with_params(BitIter::from([0xa5u8; 2048]), 4, 160, &mut table)
+            .expect_err("Universal p > threshold");
+    }
+
+    #[test]
+    fn nist_universal_invalid() {
+        let buff = [0u8; 256];
+        let mut table = [0u32; nist_universal_table_len(4)];
+
+        // Insufficient table space
+        assert_eq!(
+            nist_universal_with_params(BitIter::new(&buff), 5, 320, &mut table),
+            Err(Error::InvalidParameter)
+        );
+
+        // Unsupported block length
+        assert_eq!(
+            nist_universal_with_params(BitIter::new(&buff), 17, 320, &mut table),
+            Err(Error::InvalidParameter)
+        );
+
+        // No test blocks
+        assert_eq!(
+            nist_universal_with_params(BitIter::new(&buff), 4, 1024, &mut table),
+            Err(Error::InsufficientSampleSize(2048))
+        );
+
+        // Insufficient data for L selection
+        assert_eq!(
+            nist_universal(BitIter::new(&buff), &mut table),
+            Err(Error::InsufficientSampleSize(2048))
+        );
+    }
+}
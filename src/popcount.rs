@@ -0,0 +1,119 @@
+//! Popcount over byte buffers, with an AVX2 implementation behind the `simd` feature
+//!
+//! This is the only module permitted to use `unsafe`, and only with the `simd` feature.
+
+#![cfg_attr(feature = "simd", allow(unsafe_code))]
+
+/// Count the ones in a buffer
+///
+/// With the `simd` feature on x86_64 this uses AVX2 where detected at runtime, falling
+/// back to the scalar implementation otherwise.
+pub(crate) fn popcount(data: &[u8]) -> usize {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        // SAFETY: AVX2 support is checked above
+        return unsafe { avx2::popcount(data) };
+    }
+
+    popcount_scalar(data)
+}
+
+/// Count the ones in a buffer, eight bytes at a time
+pub(crate) fn popcount_scalar(data: &[u8]) -> usize {
+    let chunks = data.chunks_exact(8);
+    let tail = chunks.remainder();
+
+    let mut n = 0;
+    for c in chunks {
+        n += u64::from_ne_bytes(c.try_into().unwrap()).count_ones() as usize;
+    }
+    for b in tail {
+        n += b.count_ones() as usize;
+    }
+
+    n
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+pub(crate) mod avx2 {
+    use core::arch::x86_64::*;
+
+    /// Count the ones in a buffer 32 bytes at a time, using a nibble lookup table per
+    /// byte with the byte counts summed into 64-bit lanes
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure AVX2 is supported
+    #[target_feature(enable = "avx2")]
+    pub(crate) unsafe fn popcount(data: &[u8]) -> usize {
+        #[rustfmt::skip]
+        let lookup = _mm256_setr_epi8(
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+            0, 1, 1, 2, 1, 2, 2, 3, 1, 2, 2, 3, 2, 3, 3, 4,
+        );
+        let mask = _mm256_set1_epi8(0x0f);
+        let zero = _mm256_setzero_si256();
+
+        let chunks = data.chunks_exact(32);
+        let tail = chunks.remainder();
+
+        let mut acc = zero;
+        for c in chunks {
+            // SAFETY: chunks are 32 bytes, and loadu permits unaligned reads
+            let v = unsafe { _mm256_loadu_si256(c.as_ptr() as *const __m256i) };
+
+            // Count the ones in the low and high nibbles of each byte
+            let lo = _mm256_shuffle_epi8(lookup, _mm256_and_si256(v, mask));
+            let hi = _mm256_shuffle_epi8(lookup, _mm256_and_si256(_mm256_srli_epi16(v, 4), mask));
+
+            // Sum byte counts (at most 8 each) into 64-bit lanes
+            acc = _mm256_add_epi64(acc, _mm256_sad_epu8(_mm256_add_epi8(lo, hi), zero));
+        }
+
+        let mut lanes = [0u64; 4];
+        // SAFETY: lanes is 32 bytes, and storeu permits unaligned writes
+        unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc) };
+
+        lanes.iter().sum::<u64>() as usize + super::popcount_scalar(tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn popcount_scalar_bytes() {
+        let mut buff = [0u8; 1027];
+        StdRng::seed_from_u64(1).fill_bytes(&mut buff);
+
+        // Matches byte-wise counts at every length
+        for len in 0..buff.len() {
+            let n: usize = buff[..len].iter().map(|b| b.count_ones() as usize).sum();
+            assert_eq!(popcount_scalar(&buff[..len]), n);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn popcount_avx2() {
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
+        }
+
+        let mut buff = [0u8; 4099];
+        StdRng::seed_from_u64(2).fill_bytes(&mut buff);
+        buff[..64].fill(0xff);
+
+        // Matches scalar counts over unaligned starts and partial chunks
+        for start in 0..33 {
+            for len in [0, 1, 31, 32, 33, 64, 1000, 4096 - 33] {
+                let data = &buff[start..start + len];
+                // SAFETY: AVX2 support is checked above
+                assert_eq!(unsafe { avx2::popcount(data) }, popcount_scalar(data));
+            }
+        }
+    }
+}
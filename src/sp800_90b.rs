@@ -0,0 +1,39 @@
+//! NIST SP 800-90B entropy source health tests and min-entropy estimates
+//!
+//! Health tests are online tests over individual (non-binary) samples from a noise source,
+//! returning [Error::HealthTestFailed](crate::Error::HealthTestFailed) on failure. Estimates
+//! are computed over a buffer of samples, returning the min-entropy in bits per sample.
+//! Estimates over bytes have `_symbols` variants for other alphabets, such as nibbles.
+
+/// Minimum number of samples for the min-entropy estimates
+pub const ESTIMATE_MIN_SAMPLES: usize = 100;
+
+mod adaptive_proportion;
+pub use adaptive_proportion::{
+    adaptive_proportion_cutoff, AdaptiveProportion, ADAPTIVE_PROPORTION_WINDOW,
+    ADAPTIVE_PROPORTION_WINDOW_BINARY,
+};
+
+mod collision;
+pub use collision::{collision_estimate, collision_estimate_symbols};
+
+#[cfg(any(feature = "rand_core_0_6", feature = "rand_core_0_9"))]
+mod iid;
+#[cfg(feature = "rand_core_0_6")]
+pub use iid::iid_permutation_test;
+#[cfg(feature = "rand_core_0_9")]
+pub use iid::iid_permutation_test_09;
+#[cfg(any(feature = "rand_core_0_6", feature = "rand_core_0_9"))]
+pub use iid::IID_PERMUTATIONS;
+
+mod markov;
+pub use markov::markov_estimate;
+
+mod most_common_value;
+pub use most_common_value::{most_common_value, most_common_value_symbols};
+
+mod repetition_count;
+pub use repetition_count::{repetition_count_cutoff, RepetitionCount};
+
+mod tuple;
+pub use tuple::{lrs_estimate, t_tuple_estimate, tuple_scratch_len};
@@ -0,0 +1,166 @@
+//! SP 800-90B Adaptive Proportion Test
+
+use crate::math;
+use crate::Error;
+
+/// Adaptive Proportion Test window size for binary noise sources
+pub const ADAPTIVE_PROPORTION_WINDOW_BINARY: usize = 1024;
+
+/// Adaptive Proportion Test window size for non-binary noise sources
+pub const ADAPTIVE_PROPORTION_WINDOW: usize = 512;
+
+/// Compute the Adaptive Proportion Test cutoff C = 1 + CRITBINOM(W, 2^-H, 1 - α) for a
+/// window W, estimated per-sample min-entropy H, and false positive probability α
+///
+/// This is the smallest C for which a sample with probability 2^-H occurs at least C
+/// times within a window with probability at most α, for example C = 589 for binary
+/// sources with H = 1, W = 1024, and α = 2^-20.
+pub fn adaptive_proportion_cutoff(window: usize, h: f32, alpha: f32) -> u32 {
+    let w = window as f64;
+    let p = math::exp2(-h as f64);
+    let target = 1.0 - alpha as f64;
+
+    // Accumulate the binomial CDF, computing each term in log space to avoid underflow
+    let mut cdf = 0.0;
+    for k in 0..window {
+        let k = k as f64;
+        let ln_pmf = math::lgamma(w + 1.0) - math::lgamma(k + 1.0) - math::lgamma(w - k + 1.0)
+            + k * math::log(p)
+            + (w - k) * math::log1p(-p);
+        cdf += math::exp(ln_pmf);
+
+        if cdf >= target {
+            return 1 + k as u32;
+        }
+    }
+
+    // Every sample in the window matching
+    1 + window as u32
+}
+
+/// SP 800-90B §4.4.2 Adaptive Proportion Test
+///
+/// Detects a large loss of entropy by counting the occurrences of the first sample in
+/// each window of W samples, failing where this reaches the cutoff C. The specification
+/// uses W = [ADAPTIVE_PROPORTION_WINDOW_BINARY] for binary and
+/// W = [ADAPTIVE_PROPORTION_WINDOW] for non-binary sources, see
+/// [adaptive_proportion_cutoff] for computing C.
+pub struct AdaptiveProportion<T: PartialEq> {
+    window: usize,
+    cutoff: u32,
+    first: Option<T>,
+    index: usize,
+    count: u32,
+}
+
+impl<T: PartialEq> AdaptiveProportion<T> {
+    /// Create a new test with window size W and cutoff C
+    pub fn new(window: usize, cutoff: u32) -> Self {
+        Self {
+            window,
+            cutoff,
+            first: None,
+            index: 0,
+            count: 0,
+        }
+    }
+
+    /// Push a sample, returning [Error::HealthTestFailed] where the count of the first
+    /// sample in the current window reaches C
+    pub fn push(&mut self, sample: T) -> Result<(), Error> {
+        // Start a new window
+        if self.index >= self.window {
+            self.first = None;
+        }
+
+        match &self.first {
+            None => {
+                self.first = Some(sample);
+                self.index = 1;
+                self.count = 1;
+            }
+            Some(f) => {
+                if *f == sample {
+                    self.count += 1;
+                }
+                self.index += 1;
+            }
+        }
+
+        match self.count >= self.cutoff {
+            true => Err(Error::HealthTestFailed),
+            false => Ok(()),
+        }
+    }
+
+    /// Reset the test, discarding the current window
+    pub fn reset(&mut self) {
+        self.first = None;
+        self.index = 0;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+
+    #[test]
+    fn adaptive_proportion_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 4096];
+        rng.fill_bytes(&mut buff);
+
+        // Full entropy bytes at α = 2^-20
+        let c = adaptive_proportion_cutoff(ADAPTIVE_PROPORTION_WINDOW, 8.0, 1.0 / (1 << 20) as f32);
+        let mut t = AdaptiveProportion::new(ADAPTIVE_PROPORTION_WINDOW, c);
+
+        for b in buff {
+            t.push(b).expect("Adaptive proportion test failed");
+        }
+    }
+
+    #[test]
+    fn adaptive_proportion_fail() {
+        let c = adaptive_proportion_cutoff(ADAPTIVE_PROPORTION_WINDOW, 8.0, 1.0 / (1 << 20) as f32);
+        let mut t = AdaptiveProportion::new(ADAPTIVE_PROPORTION_WINDOW, c);
+
+        // Constant streams fail on the C-th sample
+        for _ in 1..c {
+            t.push(0xa5u8).unwrap();
+        }
+        assert_eq!(t.push(0xa5), Err(Error::HealthTestFailed));
+    }
+
+    #[test]
+    fn adaptive_proportion_windows() {
+        let mut t = AdaptiveProportion::new(4, 3);
+
+        // Matches of the first sample are counted within each window
+        for s in [1u8, 2, 1, 2, 2, 1, 1, 2] {
+            t.push(s).unwrap();
+        }
+        t.push(1).unwrap();
+        t.push(1).unwrap();
+        assert_eq!(t.push(1), Err(Error::HealthTestFailed));
+
+        // Reset discards the window
+        t.reset();
+        t.push(1).unwrap();
+        t.push(1).unwrap();
+    }
+
+    #[test]
+    fn adaptive_proportion_cutoffs() {
+        // Values from specification, α = 2^-20
+        let alpha = 1.0 / (1 << 20) as f32;
+
+        assert_eq!(adaptive_proportion_cutoff(1024, 1.0, alpha), 589);
+        assert_eq!(adaptive_proportion_cutoff(1024, 0.5, alpha), 793);
+        assert_eq!(adaptive_proportion_cutoff(512, 1.0, alpha), 311);
+        assert_eq!(adaptive_proportion_cutoff(512, 0.5, alpha), 410);
+        assert_eq!(adaptive_proportion_cutoff(512, 8.0, alpha), 13);
+    }
+}
@@ -0,0 +1,220 @@
+//! SP 800-90B Collision Estimate
+
+use super::ESTIMATE_MIN_SAMPLES;
+use crate::math;
+use crate::{helpers::MAX_ALPHABET_SIZE, Error};
+
+/// Bisection iterations when solving for p
+const ITERATIONS: usize = 64;
+
+/// SP 800-90B §6.3.2 Collision Estimate over byte samples
+///
+/// Splits the sequence at each collision (the first repeated value since the last
+/// collision), computing the mean X̄ and standard deviation σ̂ of the number of samples
+/// to each collision. The lower bound X̄' = X̄ - 2.576σ̂ / sqrt(v) over v collisions is
+/// then inverted to find the probability p of the most likely value by binary search
+/// over the expected collision time, with the remaining k - 1 = 255 values equally
+/// likely. Returns the min-entropy -log2(p) in bits per sample, or 8 where X̄' exceeds
+/// the expected collision time for uniform samples.
+///
+/// As each collision spans only ~20 samples this is conservative, estimating around 5.5
+/// bits per sample for 64 KiB of full entropy data.
+///
+/// This requires at least [ESTIMATE_MIN_SAMPLES] samples containing at least two
+/// collisions, returning [Error::InsufficientSampleSize] otherwise.
+pub fn collision_estimate(samples: &[u8]) -> Result<f32, Error> {
+    collision_estimate_symbols(samples.iter().copied(), MAX_ALPHABET_SIZE)
+}
+
+/// SP 800-90B §6.3.2 Collision Estimate over samples from an alphabet of `k` symbols
+///
+/// As [collision_estimate] for sources where the natural unit is not a byte, with the
+/// remaining k - 1 values equally likely and returning at most log2(k) bits per sample.
+/// This requires 2 <= k <= [MAX_ALPHABET_SIZE] and each sample less than k, returning
+/// [Error::InvalidParameter] otherwise.
+pub fn collision_estimate_symbols<S: Into<usize>>(
+    samples: impl Iterator<Item = S>,
+    k: usize,
+) -> Result<f32, Error> {
+    if !(2..=MAX_ALPHABET_SIZE).contains(&k) {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Accumulate the number of samples to each collision
+    let mut l = 0;
+    let mut v = 0usize;
+    let mut sum = 0f64;
+    let mut sum_sq = 0f64;
+
+    let mut seen = [false; MAX_ALPHABET_SIZE];
+    let mut t = 0;
+
+    for s in samples {
+        let s = s.into();
+        if s >= k {
+            return Err(Error::InvalidParameter);
+        }
+
+        l += 1;
+        t += 1;
+
+        if !seen[s] {
+            seen[s] = true;
+            continue;
+        }
+
+        // Collision, restarting from the next sample
+        v += 1;
+        sum += t as f64;
+        sum_sq += (t * t) as f64;
+
+        seen = [false; MAX_ALPHABET_SIZE];
+        t = 0;
+    }
+
+    // Check we have enough samples, and enough collisions for the standard deviation
+    if l < ESTIMATE_MIN_SAMPLES || v < 2 {
+        return Err(Error::InsufficientSampleSize(l));
+    }
+
+    // Compute lower bound on the mean collision time
+    let mean = sum / v as f64;
+    let sigma = math::sqrt(((sum_sq - sum * mean) / (v - 1) as f64).max(0.0));
+    let x = mean - 2.576 * sigma / math::sqrt(v as f64);
+
+    // Check for a solution with p >= 1 / k
+    let mut lo = 1.0 / k as f64;
+    let mut hi = 1.0;
+
+    if expected_collision_time(lo, k) <= x {
+        return Ok(math::log2(k as f64) as f32);
+    }
+
+    // Solve for p, where the expected collision time decreases with p
+    for _ in 0..ITERATIONS {
+        let mid = (lo + hi) / 2.0;
+        match expected_collision_time(mid, k) > x {
+            true => lo = mid,
+            false => hi = mid,
+        }
+    }
+
+    Ok(-math::log2((lo + hi) / 2.0) as f32)
+}
+
+/// Compute the expected number of samples to a collision where the most likely value
+/// has probability p and the remaining k - 1 values probability q = (1 - p) / (k - 1)
+///
+/// The specification gives this as p q^-2 (1 + (p^-1 - q^-1) / k) F(q) - p q^-1 (p^-1 -
+/// q^-1) / k where F(1/z) = Γ(k + 1, z) z^(-k-1) e^z, which cancels badly as p -> 1.
+/// This instead sums the equivalent probabilities that the first n samples are distinct,
+/// (k - 1)!/(k - 1 - n)! q^n + n p (k - 1)!/(k - n)! q^(n - 1), over n = 0..=k.
+fn expected_collision_time(p: f64, k: usize) -> f64 {
+    let q = (1.0 - p) / (k - 1) as f64;
+
+    // Falling factorial term (k - 1)!/(k - 1 - n)! q^n, and the sum from n = 0
+    let mut a = 1.0;
+    let mut e = 1.0;
+
+    for n in 1..=k {
+        let prev = a;
+        a *= (k - n) as f64 * q;
+        e += a + n as f64 * p * prev;
+    }
+
+    e
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn collision_estimate_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 1 << 16];
+        rng.fill_bytes(&mut buff);
+
+        let h = collision_estimate(&buff).unwrap();
+        assert!(h > 5.0 && h <= 8.0, "Unexpected estimate {h}");
+    }
+
+    #[test]
+    fn collision_estimate_vector() {
+        let mut buff = [0u8; 4096];
+        lcg_fill(&mut buff, 9);
+
+        // Check estimates match reference (201 collisions, X̄ = 20.308, σ̂ = 9.592)
+        assert_approx_eq!(collision_estimate(&buff).unwrap(), 4.521084, 1e-4);
+
+        // Biased towards zero
+        for b in buff.iter_mut() {
+            if *b < 128 {
+                *b = 0;
+            }
+        }
+        assert_approx_eq!(collision_estimate(&buff).unwrap(), 0.916714, 1e-4);
+    }
+
+    #[test]
+    fn collision_estimate_nibbles() {
+        let mut buff = [0u8; 1 << 12];
+        lcg_fill(&mut buff, 9);
+        let nibbles = || buff.iter().flat_map(|b| [b & 0x0f, b >> 4]);
+
+        // Conservative, as for bytes, with at most 4 bits per nibble
+        let h = collision_estimate_symbols(nibbles(), 16).unwrap();
+        assert!(h > 2.0 && h <= 4.0, "Unexpected estimate {h}");
+
+        // Bytes match the byte-wise estimate
+        assert_eq!(
+            collision_estimate_symbols(buff.iter().copied(), 256),
+            collision_estimate(&buff)
+        );
+
+        // Samples must be within the alphabet
+        assert_eq!(
+            collision_estimate_symbols(nibbles(), 15),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            collision_estimate_symbols(nibbles(), 1),
+            Err(Error::InvalidParameter)
+        );
+    }
+
+    #[test]
+    fn collision_expected_time() {
+        // Uniform samples, approximately 1 + sqrt(pi * k / 2)
+        assert_approx_eq!(expected_collision_time(1.0 / 256.0, 256), 20.74, 0.1);
+
+        // Always colliding on the second sample
+        assert_eq!(expected_collision_time(1.0, 256), 2.0);
+        assert_approx_eq!(expected_collision_time(1.0 - 1e-9, 256), 2.0, 1e-6);
+    }
+
+    #[test]
+    fn collision_estimate_fail() {
+        // Constant samples have no entropy
+        assert_approx_eq!(collision_estimate(&[0xa5; 128]).unwrap(), 0.0, 1e-6);
+
+        assert_eq!(
+            collision_estimate(&[0u8; 99]),
+            Err(Error::InsufficientSampleSize(99))
+        );
+
+        // Too few collisions
+        let mut buff = [0u8; 256];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        assert_eq!(
+            collision_estimate(&buff),
+            Err(Error::InsufficientSampleSize(256))
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! SP 800-90B IID Permutation Testing
+//!
+//! Computes a battery of statistics over the samples and over [IID_PERMUTATIONS]
+//! shuffles of these, rejecting the IID assumption where the original statistics rank
+//! among the most extreme. As shuffling destroys any ordering, a non-IID source produces
+//! statistics that stand out from the shuffled sequences.
+
+use super::ESTIMATE_MIN_SAMPLES;
+use crate::{helpers::lz_compression_ratio, Error};
+
+/// Number of shuffles for the permutation test
+pub const IID_PERMUTATIONS: usize = 10_000;
+
+/// Lags for the periodicity and covariance statistics
+const LAGS: [usize; 5] = [1, 2, 8, 16, 32];
+
+/// Number of statistics, with a periodicity and covariance statistic per lag
+const STATISTICS: usize = 9 + 2 * LAGS.len();
+
+/// Proportion of shuffles (as 1 / RANK_DIVISOR) bounding the rank of the original in
+/// each tail, 5 in 10,000
+const RANK_DIVISOR: usize = 2000;
+
+/// SP 800-90B §5.1 Permutation Testing over byte samples, shuffled with a `rand_core`
+/// 0.6 RNG
+///
+/// Computes the statistics of §5.1.1 to §5.1.11 (excursion, number and length of
+/// directional runs, number of increases and decreases, number and length of runs based
+/// on the median, average and maximum collision, periodicity and covariance at lags 1,
+/// 2, 8, 16, and 32, and compression) over the samples and over [IID_PERMUTATIONS]
+/// Fisher-Yates shuffles, returning `false` where the original ranks within the top or
+/// bottom 0.05% of any statistic. Binary samples (all 0 or 1) use the specified
+/// conversions, with the directional, periodicity and covariance statistics computed over
+/// the Hamming weights of 8-sample blocks (Conversion I) and the collision statistics over
+/// bytes packed from 8-sample blocks (Conversion II). The compression statistic uses
+/// [lz_compression_ratio] over the samples in place of bzip2 over a text encoding, as
+/// this is `no_std`.
+///
+/// As this does not allocate, the caller provides a `scratch` buffer of at least the
+/// sample length for shuffling, returning [Error::InvalidParameter] otherwise. This
+/// requires at least [ESTIMATE_MIN_SAMPLES] samples, returning
+/// [Error::InsufficientSampleSize] otherwise. The RNG is a parameter so that results are
+/// reproducible, note this computes each statistic 10,001 times so is slow for long
+/// sequences.
+#[cfg(feature = "rand_core_0_6")]
+pub fn iid_permutation_test(
+    samples: &[u8],
+    scratch: &mut [u8],
+    rng: &mut impl rand_core_0_6::RngCore,
+) -> Result<bool, Error> {
+    permutation_test(samples, scratch, IID_PERMUTATIONS, || rng.next_u32())
+}
+
+/// SP 800-90B §5.1 Permutation Testing over byte samples, shuffled with a `rand_core`
+/// 0.9 RNG
+///
+/// See `iid_permutation_test` for details.
+#[cfg(feature = "rand_core_0_9")]
+pub fn iid_permutation_test_09(
+    samples: &[u8],
+    scratch: &mut [u8],
+    rng: &mut impl rand_core_0_9::RngCore,
+) -> Result<bool, Error> {
+    permutation_test(samples, scratch, IID_PERMUTATIONS, || rng.next_u32())
+}
+
+/// Permutation test over `permutations` shuffles, using `next` as the shuffle source
+fn permutation_test(
+    samples: &[u8],
+    scratch: &mut [u8],
+    permutations: usize,
+    mut next: impl FnMut() -> u32,
+) -> Result<bool, Error> {
+    let l = samples.len();
+
+    // Check parameters are supported
+    if scratch.len() < l || l > u32::MAX as usize {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Check we have enough samples
+    if l < ESTIMATE_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(l));
+    }
+
+    let shuffled = &mut scratch[..l];
+    shuffled.copy_from_slice(samples);
+
+    // Binary samples use a median of 0.5 (§5.1.5), so runs are of zeros and ones
+    let binary = samples.iter().all(|s| *s <= 1);
+    let median = match binary {
+        true => 1,
+        false => median2(samples),
+    };
+    let original = statistics(samples, binary, median);
+
+    // Count shuffles with statistics above (c0) and equal to (c1) the original
+    let mut c0 = [0usize; STATISTICS];
+    let mut c1 = [0usize; STATISTICS];
+
+    for _ in 0..permutations {
+        shuffle(shuffled, &mut next);
+
+        let t = statistics(shuffled, binary, median);
+        for i in 0..STATISTICS {
+            if t[i] > original[i] {
+                c0[i] += 1;
+            } else if t[i] == original[i] {
+                c1[i] += 1;
+            }
+        }
+    }
+
+    // Reject where the original ranks among the highest or lowest
+    let cutoff = permutations / RANK_DIVISOR;
+    let rejected = c0
+        .iter()
+        .zip(&c1)
+        .any(|(c0, c1)| c0 + c1 <= cutoff || *c0 >= permutations - cutoff);
+
+    Ok(!rejected)
+}
+
+/// Fisher-Yates shuffle with unbiased indices from `next`
+fn shuffle(s: &mut [u8], next: &mut impl FnMut() -> u32) {
+    for i in (1..s.len()).rev() {
+        // Lemire's multiply and reject for an index in 0..=i
+        let n = i as u32 + 1;
+        let threshold = n.wrapping_neg() % n;
+        let j = loop {
+            let m = next() as u64 * n as u64;
+            if m as u32 >= threshold {
+                break (m >> 32) as usize;
+            }
+        };
+
+        s.swap(i, j);
+    }
+}
+
+/// Compute twice the median, which is independent of ordering
+fn median2(s: &[u8]) -> u32 {
+    let mut counts = [0usize; 256];
+    for v in s {
+        counts[*v as usize] += 1;
+    }
+
+    // Values at (sorted) indices (L - 1) / 2 and L / 2
+    let nth = |n: usize| {
+        let mut total = 0;
+        counts
+            .iter()
+            .position(|c| {
+                total += c;
+                total > n
+            })
+            .unwrap_or(0) as u32
+    };
+
+    nth((s.len() - 1) / 2) + nth(s.len() / 2)
+}
+
+/// Compute all statistics, ordered as in the specification
+fn statistics(s: &[u8], binary: bool, median2: u32) -> [f64; STATISTICS] {
+    let l = s.len();
+    let mut t = [0f64; STATISTICS];
+
+    // Sequence for the directional, periodicity, and covariance statistics, with Hamming
+    // weights of 8-sample blocks for binary samples
+    let n = match binary {
+        true => l / 8,
+        false => l,
+    };
+    let x = |i: usize| match binary {
+        true => s[8 * i..8 * i + 8].iter().map(|v| *v as u64).sum::<u64>(),
+        false => s[i] as u64,
+    };
+
+    // §5.1.1 Excursion, scaled by L to remain integral
+    let total = s.iter().map(|v| *v as i64).sum::<i64>();
+    let mut sum = 0;
+    let mut excursion = 0;
+    for (i, v) in s.iter().enumerate() {
+        sum += *v as i64;
+        excursion = excursion.max((l as i64 * sum - (i as i64 + 1) * total).abs());
+    }
+    t[0] = excursion as f64 / l as f64;
+
+    // §5.1.2 to §5.1.4 Directional runs, with ties counted as increases
+    let (runs, longest, increases) = count_runs((1..n).map(|i| x(i) >= x(i - 1)));
+    t[1] = runs as f64;
+    t[2] = longest as f64;
+    t[3] = increases.max(n.saturating_sub(1) - increases) as f64;
+
+    // §5.1.5 and §5.1.6 Runs based on the median
+    let (runs, longest, _) = count_runs(s.iter().map(|v| 2 * *v as u32 >= median2));
+    t[4] = runs as f64;
+    t[5] = longest as f64;
+
+    // §5.1.7 and §5.1.8 Collisions, packing 8-sample blocks for binary samples
+    let m = n;
+    let y = |i: usize| match binary {
+        true => s[8 * i..8 * i + 8].iter().fold(0, |b, v| (b << 1) | v),
+        false => s[i],
+    };
+    let (mut count, mut total, mut max) = (0usize, 0usize, 0usize);
+    let mut seen = [usize::MAX; 256];
+    let mut start = 0;
+    for i in 0..m {
+        let v = y(i) as usize;
+        if seen[v] != start {
+            seen[v] = start;
+            continue;
+        }
+
+        // Collision, restarting from the next sample
+        let len = i - start + 1;
+        count += 1;
+        total += len;
+        max = max.max(len);
+        start = i + 1;
+    }
+    if count > 0 {
+        t[6] = total as f64 / count as f64;
+        t[7] = max as f64;
+    }
+
+    // §5.1.9 and §5.1.10 Periodicity and covariance
+    for (k, p) in LAGS.iter().enumerate() {
+        let (mut matches, mut products) = (0u64, 0u64);
+        for i in 0..n.saturating_sub(*p) {
+            let (a, b) = (x(i), x(i + p));
+            matches += (a == b) as u64;
+            products += a * b;
+        }
+        t[8 + k] = matches as f64;
+        t[8 + LAGS.len() + k] = products as f64;
+    }
+
+    // §5.1.11 Compression
+    t[STATISTICS - 1] = lz_compression_ratio(s) as f64;
+
+    t
+}
+
+/// Count the runs, longest run, and number of `true` values
+fn count_runs(v: impl Iterator<Item = bool>) -> (usize, usize, usize) {
+    let (mut runs, mut longest, mut ones) = (0, 0, 0);
+    let mut prev = None;
+    let mut len = 0;
+
+    for b in v {
+        match prev == Some(b) {
+            true => len += 1,
+            false => {
+                runs += 1;
+                len = 1;
+            }
+        }
+        longest = longest.max(len);
+        ones += b as usize;
+        prev = Some(b);
+    }
+
+    (runs, longest, ones)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn iid_permutation_ok() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut buff = vec![0u8; 500];
+        rng.fill_bytes(&mut buff);
+        let mut scratch = vec![0u8; buff.len()];
+
+        let r = permutation_test(&buff, &mut scratch, 1000, || rng.next_u32());
+        assert_eq!(r, Ok(true));
+
+        // Binary samples, with conversions
+        let buff: vec::Vec<u8> = (0..800).map(|_| (rng.next_u32() & 1) as u8).collect();
+        let mut scratch = vec![0u8; buff.len()];
+
+        let r = permutation_test(&buff, &mut scratch, 1000, || rng.next_u32());
+        assert_eq!(r, Ok(true));
+    }
+
+    #[test]
+    #[cfg(feature = "rand_core_0_6")]
+    fn iid_permutation_rng() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut buff = [0u8; 200];
+        rng.fill_bytes(&mut buff);
+        let mut scratch = [0u8; 200];
+
+        assert_eq!(
+            iid_permutation_test(&buff, &mut scratch, &mut rng),
+            Ok(true)
+        );
+
+        // Shuffles are reproducible for a given RNG
+        let (mut a, mut b) = (StdRng::seed_from_u64(3), StdRng::seed_from_u64(3));
+        let (mut x, mut y) = (buff, buff);
+        shuffle(&mut x, &mut || a.next_u32());
+        shuffle(&mut y, &mut || b.next_u32());
+        assert_eq!(x, y);
+        assert_ne!(x, buff);
+    }
+
+    #[test]
+    fn iid_permutation_fail() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut scratch = vec![0u8; 1000];
+
+        // Slowly varying samples, with long directional runs
+        let mut buff = vec![0u8; 1000];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = ((i / 4) as u8).wrapping_add((rng.next_u32() % 8) as u8);
+        }
+        let r = permutation_test(&buff, &mut scratch, 1000, || rng.next_u32());
+        assert_eq!(r, Ok(false));
+
+        // Periodic samples
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = [0, 1, 2, 3][i % 4] * 64 + (rng.next_u32() % 64) as u8;
+        }
+        let r = permutation_test(&buff, &mut scratch, 1000, || rng.next_u32());
+        assert_eq!(r, Ok(false));
+
+        // Binary samples, mostly zeros, with long runs of each value
+        let mut v = 0;
+        for b in buff.iter_mut() {
+            if rng.next_u32() % 16 == 0 {
+                v = (rng.next_u32() % 3 == 0) as u8;
+            }
+            *b = v;
+        }
+        assert!(buff.iter().filter(|b| **b == 0).count() > 500);
+        let r = permutation_test(&buff, &mut scratch, 1000, || rng.next_u32());
+        assert_eq!(r, Ok(false));
+    }
+
+    #[test]
+    fn iid_permutation_invalid() {
+        let buff = [0u8; 100];
+        let mut scratch = [0u8; 100];
+
+        assert_eq!(
+            permutation_test(&buff, &mut scratch[..99], 10, || 0),
+            Err(Error::InvalidParameter)
+        );
+        assert_eq!(
+            permutation_test(&buff[..99], &mut scratch, 10, || 0),
+            Err(Error::InsufficientSampleSize(99))
+        );
+    }
+
+    #[test]
+    fn iid_statistics() {
+        // Median of odd and even lengths
+        assert_eq!(median2(&[3, 1, 2]), 4);
+        assert_eq!(median2(&[4, 1, 2, 3]), 5);
+
+        // Runs of increases (true) and decreases
+        assert_eq!(count_runs([true, true, false, true].into_iter()), (3, 2, 3));
+
+        // Statistics from the specification example for sequence S
+        let s = [2, 15, 4, 10, 9];
+        let t = statistics(&s, false, median2(&s));
+        assert_eq!(t[0], 6.0);
+        assert_eq!((t[1], t[2], t[3]), (4.0, 1.0, 2.0));
+        assert_eq!((t[4], t[5]), (4.0, 2.0));
+
+        // Binary runs based on the median of 0.5, with zeros the majority
+        let s = [0u8, 0, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0];
+        let t = statistics(&s, true, 1);
+        assert_eq!((t[4], t[5]), (5.0, 8.0));
+    }
+}
@@ -0,0 +1,123 @@
+//! SP 800-90B Markov Estimate
+
+use super::ESTIMATE_MIN_SAMPLES;
+use crate::math;
+use crate::Error;
+
+/// Path length for the Markov estimate
+const PATH_LEN: usize = 128;
+
+/// SP 800-90B §6.3.3 Markov Estimate over an iterator of binary samples
+///
+/// Models the sequence as a first-order Markov chain, estimating the initial and
+/// transition probabilities from the bit and 2x2 transition frequencies, then finds the
+/// probability p_max of the most likely 128-bit path through the chain. Returns the
+/// min-entropy min(-log2(p_max) / 128, 1) in bits per sample.
+///
+/// This requires at least [ESTIMATE_MIN_SAMPLES] samples, returning
+/// [Error::InsufficientSampleSize] otherwise.
+///
+/// See [BitIter](crate::helpers::BitIter) for use with buffers
+pub fn markov_estimate(bits: impl Iterator<Item = bool>) -> Result<f32, Error> {
+    let mut n = 0;
+    let mut ones = 0;
+    let mut transitions = [[0usize; 2]; 2];
+    let mut prev = None;
+
+    // Count bits and transitions
+    for b in bits {
+        if let Some(p) = prev {
+            transitions[p as usize][b as usize] += 1;
+        }
+        prev = Some(b);
+
+        ones += b as usize;
+        n += 1;
+    }
+
+    // Check we have enough samples to estimate probabilities
+    if n < ESTIMATE_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(n));
+    }
+
+    // Compute initial and transition log probabilities, with ln(0) = -inf for
+    // unobserved transitions
+    let p1 = ones as f64 / n as f64;
+    let initial = [math::log(1.0 - p1), math::log(p1)];
+
+    let mut p = [[f64::NEG_INFINITY; 2]; 2];
+    for (i, t) in transitions.iter().enumerate() {
+        let total = t[0] + t[1];
+        if total > 0 {
+            p[i][0] = math::log(t[0] as f64 / total as f64);
+            p[i][1] = math::log(t[1] as f64 / total as f64);
+        }
+    }
+
+    // Find the most likely path ending in each state, one step at a time
+    let mut best = initial;
+    for _ in 1..PATH_LEN {
+        best = [
+            (best[0] + p[0][0]).max(best[1] + p[1][0]),
+            (best[0] + p[0][1]).max(best[1] + p[1][1]),
+        ];
+    }
+
+    // Convert path probability to min-entropy per bit
+    let ln_p_max = best[0].max(best[1]);
+    let h = -ln_p_max / core::f64::consts::LN_2 / PATH_LEN as f64;
+
+    Ok(h.min(1.0) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::{helpers::BitIter, test_utils::lcg_fill};
+
+    #[test]
+    fn markov_estimate_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 1 << 14];
+        rng.fill_bytes(&mut buff);
+
+        let h = markov_estimate(BitIter::new(&buff)).unwrap();
+        assert!(h > 0.95 && h <= 1.0, "Unexpected estimate {h}");
+    }
+
+    #[test]
+    fn markov_estimate_vector() {
+        let mut buff = [0u8; 1024];
+        lcg_fill(&mut buff, 7);
+
+        // Check estimate matches reference (computed from the specification's most
+        // likely sequences)
+        let h = markov_estimate(BitIter::new(&buff)).unwrap();
+        assert_approx_eq!(h, 0.976142, 1e-5);
+
+        // Biased with P(1) = 1/4
+        for b in buff.iter_mut() {
+            *b &= *b >> 1;
+        }
+        let h = markov_estimate(BitIter::new(&buff)).unwrap();
+        assert_approx_eq!(h, 0.250977, 1e-5);
+    }
+
+    #[test]
+    fn markov_estimate_fail() {
+        // Constant sequences have no entropy, alternating sequences only the initial bit
+        assert_eq!(markov_estimate(BitIter::from([0x00u8; 16])), Ok(0.0));
+        assert_eq!(
+            markov_estimate(BitIter::from([0x55u8; 16])),
+            Ok(1.0 / 128.0)
+        );
+
+        assert_eq!(
+            markov_estimate(BitIter::from([0x00u8; 12])),
+            Err(Error::InsufficientSampleSize(96))
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! SP 800-90B Most Common Value Estimate
+
+use super::ESTIMATE_MIN_SAMPLES;
+use crate::math;
+use crate::{helpers::MAX_ALPHABET_SIZE, Error};
+
+/// SP 800-90B §6.3.1 Most Common Value Estimate over byte samples
+///
+/// Computes the proportion p̂ of the most common value, forming the 99% upper confidence
+/// bound p_u = min(1, p̂ + 2.576 * sqrt(p̂(1 - p̂) / (L - 1))) and returning the
+/// min-entropy -log2(p_u) in bits per sample.
+///
+/// This requires at least [ESTIMATE_MIN_SAMPLES] samples, returning
+/// [Error::InsufficientSampleSize] otherwise.
+pub fn most_common_value(samples: &[u8]) -> Result<f32, Error> {
+    most_common_value_symbols(samples.iter().copied(), MAX_ALPHABET_SIZE)
+}
+
+/// SP 800-90B §6.3.1 Most Common Value Estimate over samples from an alphabet of `k`
+/// symbols
+///
+/// As [most_common_value] for sources where the natural unit is not a byte, such as a
+/// TRNG emitting 4-bit nibbles. This requires 2 <= k <= [MAX_ALPHABET_SIZE] and each
+/// sample less than k, returning [Error::InvalidParameter] otherwise.
+pub fn most_common_value_symbols<S: Into<usize>>(
+    samples: impl Iterator<Item = S>,
+    k: usize,
+) -> Result<f32, Error> {
+    if !(2..=MAX_ALPHABET_SIZE).contains(&k) {
+        return Err(Error::InvalidParameter);
+    }
+
+    // Count occurrences of each value
+    let mut counts = [0usize; MAX_ALPHABET_SIZE];
+    let mut l = 0;
+    for s in samples {
+        let s = s.into();
+        if s >= k {
+            return Err(Error::InvalidParameter);
+        }
+
+        counts[s] += 1;
+        l += 1;
+    }
+
+    // Check we have enough samples to bound the estimate
+    if l < ESTIMATE_MIN_SAMPLES {
+        return Err(Error::InsufficientSampleSize(l));
+    }
+
+    let max = counts.iter().max().copied().unwrap_or(0);
+
+    Ok(mcv_entropy(max, l))
+}
+
+/// Compute the min-entropy bound for the most common value occurring `max` times in `l`
+/// samples
+pub(super) fn mcv_entropy(max: usize, l: usize) -> f32 {
+    bound_entropy(max as f64 / l as f64, l)
+}
+
+/// Compute the min-entropy from the 99% upper confidence bound on a probability p
+/// estimated from `l` samples
+pub(super) fn bound_entropy(p: f64, l: usize) -> f32 {
+    let p_u = (p + 2.576 * math::sqrt(p * (1.0 - p) / (l - 1) as f64)).min(1.0);
+
+    -math::log2(p_u) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn most_common_value_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 1 << 16];
+        rng.fill_bytes(&mut buff);
+
+        // Full entropy bytes estimate close to 8 bits
+        let h = most_common_value(&buff).unwrap();
+        assert!(h > 7.0 && h <= 8.0, "Unexpected estimate {h}");
+    }
+
+    #[test]
+    fn most_common_value_ex() {
+        // Example from specification,
+        // S = (0, 1, 1, 2, 0, 1, 2, 2, 0, 1, 0, 1, 1, 0, 2, 2, 1, 0, 2, 1)
+        // with p̂ = 0.4 and p_u = 0.6895
+        assert_approx_eq!(mcv_entropy(8, 20), 0.5363, 1e-4);
+
+        // Repeated to meet the minimum sample size
+        let s = [0, 1, 1, 2, 0, 1, 2, 2, 0, 1, 0, 1, 1, 0, 2, 2, 1, 0, 2, 1];
+        let mut buff = [0u8; 100];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = s[i % s.len()];
+        }
+
+        assert_approx_eq!(most_common_value(&buff).unwrap(), 0.924581, 1e-5);
+    }
+
+    #[test]
+    fn most_common_value_fail() {
+        // Constant samples have no entropy
+        assert_eq!(most_common_value(&[0xa5; 128]), Ok(0.0));
+
+        assert_eq!(
+            most_common_value(&[0u8; 99]),
+            Err(Error::InsufficientSampleSize(99))
+        );
+    }
+
+    #[test]
+    fn most_common_value_nibbles() {
+        let mut buff = [0u8; 1 << 12];
+        lcg_fill(&mut buff, 3);
+        let nibbles = || buff.iter().flat_map(|b| [b & 0x0f, b >> 4]);
+
+        // Full entropy nibbles estimate close to 4 bits
+        let h = most_common_value_symbols(nibbles(), 16).unwrap();
+        assert!(h > 3.5 && h <= 4.0, "Unexpected estimate {h}");
+
+        // Bytes match the byte-wise estimate
+        assert_eq!(
+            most_common_value_symbols(buff.iter().copied(), 256),
+            most_common_value(&buff)
+        );
+
+        // Samples must be within the alphabet
+        assert_eq!(
+            most_common_value_symbols(nibbles(), 8),
+            Err(Error::InvalidParameter)
+        );
+        for k in [0, 1, 257] {
+            assert_eq!(
+                most_common_value_symbols(nibbles(), k),
+                Err(Error::InvalidParameter)
+            );
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! SP 800-90B Repetition Count Test
+
+use crate::math;
+use crate::Error;
+
+/// Compute the Repetition Count Test cutoff C = 1 + ceil(-log2(α) / H) for an estimated
+/// per-sample min-entropy H and false positive probability α
+///
+/// The specification recommends 2^-40 <= α <= 2^-20.
+pub fn repetition_count_cutoff(h: f32, alpha: f32) -> u32 {
+    1 + math::ceilf(-math::log2f(alpha) / h) as u32
+}
+
+/// SP 800-90B §4.4.1 Repetition Count Test
+///
+/// Detects catastrophic failures where a noise source becomes stuck on a single value,
+/// failing where the same sample is repeated C times in a row. See
+/// [repetition_count_cutoff] for computing C, which must be at least 2.
+pub struct RepetitionCount<T: PartialEq> {
+    cutoff: u32,
+    prev: Option<T>,
+    count: u32,
+}
+
+impl<T: PartialEq> RepetitionCount<T> {
+    /// Create a new test with cutoff C
+    pub fn new(cutoff: u32) -> Self {
+        Self {
+            cutoff,
+            prev: None,
+            count: 0,
+        }
+    }
+
+    /// Push a sample, returning [Error::HealthTestFailed] on the C-th consecutive repeat
+    /// (and any further repeats)
+    pub fn push(&mut self, sample: T) -> Result<(), Error> {
+        match &self.prev {
+            Some(p) if *p == sample => self.count = self.count.saturating_add(1),
+            _ => {
+                self.prev = Some(sample);
+                self.count = 1;
+            }
+        }
+
+        match self.count >= self.cutoff {
+            true => Err(Error::HealthTestFailed),
+            false => Ok(()),
+        }
+    }
+
+    /// Reset the test, discarding the previous sample
+    pub fn reset(&mut self) {
+        self.prev = None;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+
+    #[test]
+    fn repetition_count_ok() {
+        let mut rng = OsRng {};
+        let mut buff = [0u8; 4096];
+        rng.fill_bytes(&mut buff);
+
+        // Full entropy bytes at α = 2^-20
+        let mut t = RepetitionCount::new(repetition_count_cutoff(8.0, math::powf(2.0, -20.0)));
+        for b in buff {
+            t.push(b).expect("Repetition count test failed");
+        }
+    }
+
+    #[test]
+    fn repetition_count_fail() {
+        let mut t = RepetitionCount::new(3);
+
+        t.push(1u8).unwrap();
+        t.push(1).unwrap();
+        t.push(2).unwrap();
+        t.push(2).unwrap();
+        assert_eq!(t.push(2), Err(Error::HealthTestFailed));
+        assert_eq!(t.push(2), Err(Error::HealthTestFailed));
+
+        // Recovers on a new value
+        t.push(3).unwrap();
+
+        // Reset discards the count
+        t.push(3).unwrap();
+        t.reset();
+        t.push(3).unwrap();
+        t.push(3).unwrap();
+    }
+
+    #[test]
+    fn repetition_count_generic() {
+        let mut t = RepetitionCount::new(2);
+
+        t.push([0u16, 1]).unwrap();
+        assert_eq!(t.push([0, 1]), Err(Error::HealthTestFailed));
+    }
+
+    #[test]
+    fn repetition_count_cutoffs() {
+        // Examples from specification, H = 1 and 2^-20 -> 21
+        assert_eq!(repetition_count_cutoff(1.0, math::powf(2.0, -20.0)), 21);
+        assert_eq!(repetition_count_cutoff(8.0, math::powf(2.0, -20.0)), 4);
+        assert_eq!(repetition_count_cutoff(0.5, math::powf(2.0, -30.0)), 61);
+    }
+}
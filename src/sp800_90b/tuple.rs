@@ -0,0 +1,380 @@
+//! SP 800-90B t-Tuple and Longest Repeated Substring (LRS) Estimates
+//!
+//! Both estimates count the occurrences of overlapping tuples (substrings) of each
+//! length W, found from a suffix array of the samples with the longest common prefix
+//! (LCP) of adjacent suffixes. Suffixes sharing a W-tuple are adjacent in the suffix
+//! array, so the tuples of each length correspond to intervals with LCP >= W.
+//!
+//! As this is `no_std` and does not allocate, the caller provides a `scratch` buffer of
+//! at least [tuple_scratch_len] `u32`s, 24 bytes per sample.
+
+use super::{most_common_value::bound_entropy, ESTIMATE_MIN_SAMPLES};
+use crate::math;
+use crate::Error;
+
+/// Minimum occurrences of the most common tuple for the t-tuple estimate
+const CUTOFF: usize = 35;
+
+/// Compute the scratch buffer length (in `u32`s) required by [t_tuple_estimate] and
+/// [lrs_estimate] for `l` samples
+pub const fn tuple_scratch_len(l: usize) -> usize {
+    6 * l + 4
+}
+
+/// SP 800-90B §6.3.5 t-Tuple Estimate over byte samples
+///
+/// Finds the largest t such that the most common t-tuple occurs at least 35 times, with
+/// Q_i occurrences of the most common i-tuple for i <= t. The maximum of
+/// P_i^(1/i) = (Q_i / (L - i + 1))^(1/i) gives p̂, bounded as for the
+/// [most common value](super::most_common_value) to return the min-entropy in bits per
+/// sample.
+///
+/// This requires at least [ESTIMATE_MIN_SAMPLES] samples with some value occurring 35
+/// times, returning [Error::InsufficientSampleSize] otherwise, and a `scratch` buffer of
+/// at least [tuple_scratch_len] `u32`s, returning [Error::InvalidParameter] otherwise.
+pub fn t_tuple_estimate(samples: &[u8], scratch: &mut [u32]) -> Result<f32, Error> {
+    let l = samples.len();
+    let counts = TupleCounts::new(samples, scratch)?;
+
+    // Check the most common value meets the cutoff
+    let t = counts.t();
+    if t == 0 {
+        return Err(Error::InsufficientSampleSize(l));
+    }
+
+    let p = (1..=t)
+        .map(|i| math::pow(counts.q(i) as f64 / (l - i + 1) as f64, 1.0 / i as f64))
+        .fold(0.0, f64::max);
+
+    Ok(bound_entropy(p, l))
+}
+
+/// SP 800-90B §6.3.6 Longest Repeated Substring (LRS) Estimate over byte samples
+///
+/// Takes u as the smallest tuple length where the most common tuple occurs fewer than
+/// 35 times (t + 1 from the [t-tuple](t_tuple_estimate) estimate), and v as the length of
+/// the longest repeated substring. For each W from u to v the collision probability
+/// P_W = Σ C(C_i, 2) / C(L - W + 1, 2) is computed over the counts C_i of each W-tuple,
+/// with the maximum of P_W^(1/W) giving p̂, bounded as for the
+/// [most common value](super::most_common_value) to return the min-entropy in bits per
+/// sample.
+///
+/// This requires at least [ESTIMATE_MIN_SAMPLES] samples with a repeated u-tuple
+/// (v >= u), returning [Error::InsufficientSampleSize] otherwise, and a `scratch` buffer
+/// of at least [tuple_scratch_len] `u32`s, returning [Error::InvalidParameter] otherwise.
+pub fn lrs_estimate(samples: &[u8], scratch: &mut [u32]) -> Result<f32, Error> {
+    let l = samples.len();
+    let counts = TupleCounts::new(samples, scratch)?;
+
+    // Check a u-tuple repeats
+    let (u, v) = (counts.t() + 1, counts.v);
+    if v < u {
+        return Err(Error::InsufficientSampleSize(l));
+    }
+
+    let p = (u..=v)
+        .map(|w| {
+            let n = (l - w + 1) as f64;
+            let p = counts.pairs(w) as f64 / (n * (n - 1.0) / 2.0);
+            math::pow(p, 1.0 / w as f64)
+        })
+        .fold(0.0, f64::max);
+
+    Ok(bound_entropy(p, l))
+}
+
+/// Tuple counts for each length W, computed over the scratch buffer
+struct TupleCounts<'a> {
+    /// Occurrences of the most common tuple of length W ending an LCP interval, indexed
+    /// by W, then the maximum over lengths >= W
+    q: &'a [u32],
+
+    /// Differences in the number of pairs of equal W-tuples from W - 1, indexed by W as
+    /// low and high `u32`s, then the number of pairs
+    pairs: &'a [u32],
+
+    /// Length of the longest repeated substring
+    v: usize,
+}
+
+impl<'a> TupleCounts<'a> {
+    fn new(samples: &[u8], scratch: &'a mut [u32]) -> Result<Self, Error> {
+        let l = samples.len();
+
+        // Check parameters are supported
+        if scratch.len() < tuple_scratch_len(l) || l >= u32::MAX as usize {
+            return Err(Error::InvalidParameter);
+        }
+
+        // Check we have enough samples
+        if l < ESTIMATE_MIN_SAMPLES {
+            return Err(Error::InsufficientSampleSize(l));
+        }
+
+        let (sa, rest) = scratch.split_at_mut(l);
+        let (rank, rest) = rest.split_at_mut(l);
+        let (lcp, rest) = rest.split_at_mut(l);
+        let (q, pairs) = rest.split_at_mut(l + 1);
+        let pairs = &mut pairs[..2 * (l + 1)];
+
+        suffix_array(samples, sa, rank, lcp);
+        lcp_array(samples, sa, rank, lcp);
+
+        q.fill(0);
+        pairs.fill(0);
+
+        let add = |pairs: &mut [u32], w: usize, v: u64| {
+            let d = u64::from(pairs[2 * w]) | u64::from(pairs[2 * w + 1]) << 32;
+            let d = d.wrapping_add(v);
+            pairs[2 * w] = d as u32;
+            pairs[2 * w + 1] = (d >> 32) as u32;
+        };
+
+        // Walk LCP intervals bottom up, with the stack of LCP values and left bounds held
+        // in the (no longer required) suffix and rank arrays. Each interval of size c with
+        // LCP ℓ within a parent of LCP ℓp is the set of suffixes sharing a W-tuple for
+        // ℓp < W <= ℓ.
+        let (stack_lcp, stack_lb) = (sa, rank);
+        let mut depth = 1;
+        stack_lcp[0] = 0;
+        stack_lb[0] = 0;
+
+        for i in 1..=l {
+            let cur = lcp.get(i).map_or(0, |v| *v as usize);
+            let mut lb = i - 1;
+
+            while cur < stack_lcp[depth - 1] as usize {
+                depth -= 1;
+                let (top, top_lb) = (stack_lcp[depth] as usize, stack_lb[depth] as usize);
+                let parent = cur.max(stack_lcp[depth - 1] as usize);
+                let c = (i - top_lb) as u64;
+
+                q[top] = q[top].max(c as u32);
+                add(pairs, parent + 1, c * (c - 1) / 2);
+                add(pairs, top + 1, (c * (c - 1) / 2).wrapping_neg());
+
+                lb = top_lb;
+            }
+
+            if cur > stack_lcp[depth - 1] as usize {
+                stack_lcp[depth] = cur as u32;
+                stack_lb[depth] = lb as u32;
+                depth += 1;
+            }
+        }
+
+        // Accumulate maximum counts from the longest tuples, and pair counts from the
+        // shortest
+        let mut v = 0;
+        for w in (1..l).rev() {
+            q[w] = q[w].max(q[w + 1]);
+            if v == 0 && q[w] != 0 {
+                v = w;
+            }
+        }
+        for w in 1..=l {
+            let prev = u64::from(pairs[2 * w - 2]) | u64::from(pairs[2 * w - 1]) << 32;
+            add(pairs, w, prev);
+        }
+
+        Ok(Self { q, pairs, v })
+    }
+
+    /// Occurrences of the most common W-tuple
+    fn q(&self, w: usize) -> usize {
+        (self.q[w] as usize).max(1)
+    }
+
+    /// Number of pairs of equal W-tuples
+    fn pairs(&self, w: usize) -> u64 {
+        u64::from(self.pairs[2 * w]) | u64::from(self.pairs[2 * w + 1]) << 32
+    }
+
+    /// Largest tuple length with the most common tuple occurring at least [CUTOFF] times
+    fn t(&self) -> usize {
+        (1..=self.v).take_while(|w| self.q(*w) >= CUTOFF).count()
+    }
+}
+
+/// Build the suffix array of `s` by prefix doubling, leaving the inverse in `rank`
+fn suffix_array(s: &[u8], sa: &mut [u32], rank: &mut [u32], tmp: &mut [u32]) {
+    let l = s.len();
+
+    for (i, (a, r)) in sa.iter_mut().zip(rank.iter_mut()).enumerate() {
+        *a = i as u32;
+        *r = s[i] as u32;
+    }
+
+    let mut k = 1;
+    loop {
+        // Sort by rank of the first k values then the next k, with shorter suffixes first
+        let key = |rank: &[u32], i: u32| {
+            let i = i as usize;
+            let next = rank.get(i + k).map_or(0, |r| *r as u64 + 1);
+            (rank[i] as u64) << 32 | next
+        };
+        sa.sort_unstable_by_key(|i| key(rank, *i));
+
+        // Re-rank from sorted keys
+        tmp[sa[0] as usize] = 0;
+        for j in 1..l {
+            let step = (key(rank, sa[j]) != key(rank, sa[j - 1])) as u32;
+            tmp[sa[j] as usize] = tmp[sa[j - 1] as usize] + step;
+        }
+        rank.copy_from_slice(tmp);
+
+        // Stop once all suffixes are distinct
+        if rank[sa[l - 1] as usize] as usize == l - 1 {
+            break;
+        }
+        k *= 2;
+    }
+}
+
+/// Compute the LCP of each suffix with the previous in the suffix array (Kasai et al.)
+fn lcp_array(s: &[u8], sa: &[u32], rank: &[u32], lcp: &mut [u32]) {
+    let l = s.len();
+    let mut h = 0;
+
+    lcp[0] = 0;
+    for i in 0..l {
+        let r = rank[i] as usize;
+        if r == 0 {
+            h = 0;
+            continue;
+        }
+
+        let j = sa[r - 1] as usize;
+        while i + h < l && j + h < l && s[i + h] == s[j + h] {
+            h += 1;
+        }
+        lcp[r] = h as u32;
+        h = h.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use std::vec;
+
+    use assert_approx_eq::assert_approx_eq;
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::test_utils::lcg_fill;
+
+    #[test]
+    fn tuple_ok() {
+        let mut buff = vec![0u8; 1 << 16];
+        OsRng.fill_bytes(&mut buff);
+        let mut scratch = vec![0u32; tuple_scratch_len(buff.len())];
+
+        // Full entropy bytes estimate below, but close to, 8 bits
+        let h = t_tuple_estimate(&buff, &mut scratch).unwrap();
+        assert!(h > 6.5 && h <= 8.0, "Unexpected estimate {h}");
+
+        let h = lrs_estimate(&buff, &mut scratch).unwrap();
+        assert!(h > 6.5 && h <= 8.0, "Unexpected estimate {h}");
+    }
+
+    #[test]
+    fn tuple_vector() {
+        let mut buff = vec![0u8; 1 << 14];
+        lcg_fill(&mut buff, 13);
+        let mut scratch = vec![0u32; tuple_scratch_len(buff.len())];
+
+        // Check estimates match reference (t = 1, u = 2, v = 3)
+        assert_approx_eq!(
+            t_tuple_estimate(&buff, &mut scratch).unwrap(),
+            7.235943,
+            1e-4
+        );
+        assert_approx_eq!(lrs_estimate(&buff, &mut scratch).unwrap(), 7.593831, 1e-4);
+
+        // Nibble samples (t = 1, u = 2, v = 5)
+        let buff: vec::Vec<u8> = buff[..4096].iter().map(|b| b >> 4).collect();
+        assert_approx_eq!(
+            t_tuple_estimate(&buff, &mut scratch).unwrap(),
+            3.680452,
+            1e-4
+        );
+        assert_approx_eq!(lrs_estimate(&buff, &mut scratch).unwrap(), 3.777574, 1e-4);
+
+        // Two bit samples (t = 2, u = 3, v = 11)
+        let mut buff = [0u8; 1000];
+        lcg_fill(&mut buff, 7);
+        buff.iter_mut().for_each(|b| *b &= 0x03);
+        assert_approx_eq!(
+            t_tuple_estimate(&buff, &mut scratch).unwrap(),
+            1.733776,
+            1e-4
+        );
+        assert_approx_eq!(lrs_estimate(&buff, &mut scratch).unwrap(), 1.550481, 1e-4);
+    }
+
+    #[test]
+    fn tuple_counts() {
+        // Suffix array and LCP of "banana"
+        let s = b"banana";
+        let mut scratch = [0u32; 18];
+        let (sa, rest) = scratch.split_at_mut(6);
+        let (rank, lcp) = rest.split_at_mut(6);
+        suffix_array(s, sa, rank, lcp);
+        lcp_array(s, sa, rank, lcp);
+        assert_eq!(sa, &[5, 3, 1, 0, 4, 2]);
+        assert_eq!(lcp, &[0, 1, 3, 0, 0, 2]);
+
+        // Periodic samples repeat up to L - 2
+        let mut buff = [0u8; 1000];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = (i % 2) as u8;
+        }
+        let mut scratch = vec![0u32; tuple_scratch_len(buff.len())];
+        let counts = TupleCounts::new(&buff, &mut scratch).unwrap();
+        assert_eq!((counts.t(), counts.v), (932, 998));
+        assert_eq!(counts.q(1), 500);
+        assert_eq!(counts.pairs(998), 1);
+    }
+
+    #[test]
+    fn tuple_fail() {
+        let mut scratch = vec![0u32; tuple_scratch_len(1000)];
+
+        // Constant samples have no entropy
+        assert_approx_eq!(
+            t_tuple_estimate(&[0xa5; 1000], &mut scratch).unwrap(),
+            0.0,
+            1e-6
+        );
+        assert_approx_eq!(
+            lrs_estimate(&[0xa5; 1000], &mut scratch).unwrap(),
+            0.0,
+            1e-6
+        );
+
+        // Too few samples, or no value meeting the cutoff
+        let mut buff = [0u8; 256];
+        for (i, b) in buff.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        assert_eq!(
+            t_tuple_estimate(&buff[..99], &mut scratch),
+            Err(Error::InsufficientSampleSize(99))
+        );
+        assert_eq!(
+            t_tuple_estimate(&buff, &mut scratch),
+            Err(Error::InsufficientSampleSize(256))
+        );
+        assert_eq!(
+            lrs_estimate(&buff, &mut scratch),
+            Err(Error::InsufficientSampleSize(256))
+        );
+
+        // Scratch too short
+        assert_eq!(
+            lrs_estimate(&[0u8; 1000], &mut scratch[..6003]),
+            Err(Error::InvalidParameter)
+        );
+    }
+}